@@ -1,5 +1,6 @@
 use crate::ast::ast_node::AstNode;
 use crate::ast::column_name_list::ColumnNameList;
+use crate::ast::identifier::Identifier;
 use crate::ast::paren::{LParen, RParen, LPAREN, RPAREN};
 use crate::ast::reserved_word as word;
 use crate::ast::reserved_word::UPDATE;
@@ -9,9 +10,9 @@ use std::rc::Rc;
 pub enum Action {
     SELECT,
     DELETE,
-    INSERT(LParen, ColumnNameList<String>, RParen),
-    UPDATE(LParen, ColumnNameList<String>, RParen),
-    REFERENCES(LParen, ColumnNameList<String>, RParen),
+    INSERT(LParen, ColumnNameList, RParen),
+    UPDATE(LParen, ColumnNameList, RParen),
+    REFERENCES(LParen, ColumnNameList, RParen),
     USAGE,
 }
 
@@ -52,10 +53,10 @@ pub fn extract_action_exp(node_name: &str, mut node: AstNode) -> Result<Action,
     };
 }
 
-pub fn extract_column_name_list(nodes: &Vec<AstNode>) -> Option<ColumnNameList<String>> {
+pub fn extract_column_name_list(nodes: &Vec<AstNode>) -> Option<ColumnNameList> {
     let comma = ",";
     let mut is_expect_comma = false;
-    let mut column_name_list: Vec<String> = vec![];
+    let mut column_name_list: ColumnNameList = vec![];
 
     'l: for node in nodes.iter() {
         let name = node.name.clone();
@@ -76,7 +77,7 @@ pub fn extract_column_name_list(nodes: &Vec<AstNode>) -> Option<ColumnNameList<S
             continue 'l;
         }
 
-        column_name_list.push(name);
+        column_name_list.push(Identifier::parse(&name).ok()?);
         is_expect_comma = true;
     }
 
@@ -95,15 +96,24 @@ fn test_update_exp() {
 
     lp_node.add(AstNode::from("bob"));
     lp_node.add(AstNode::from(","));
-    lp_node.add(AstNode::from("alice"));
+    lp_node.add(AstNode::from("\"Alice\""));
 
     update_node.add(lp_node);
     update_node.add(rp_node);
 
     let action = extract_action_rule_exp(update_node).unwrap();
+    match &action {
+        Action::UPDATE(_, columns, _) => {
+            let values: Vec<&str> = columns.iter().map(Identifier::value).collect();
+            assert_eq!(values, vec!["bob", "Alice"]);
+        }
+        _ => panic!("expected UPDATE"),
+    }
 
-    let expected_column_name_list: ColumnNameList<String> =
-        ["bob".to_string(), "alice".to_string()].to_vec();
+    let expected_column_name_list: ColumnNameList = vec![
+        Identifier::Regular("bob".to_string()),
+        Identifier::Delimited("Alice".to_string()),
+    ];
     assert_eq!(
         Action::UPDATE(
             LPAREN.to_string(),
@@ -0,0 +1,396 @@
+use crate::ast::ast_node::AstNode;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+/// Handle of a node inside `AstArena`.
+/// Stores index + 1, so `Option<NodeId>` stays 4 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(NonZeroU32);
+
+impl NodeId {
+    fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+}
+
+/// Handle of an interned node name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+struct ArenaNode {
+    name: Symbol,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+}
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.ids.get(name) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        let name: Rc<str> = Rc::from(name);
+        self.names.push(name.clone());
+        self.ids.insert(name, symbol);
+        symbol
+    }
+}
+
+/// Index-based AST storage, a copy of an `AstNode` tree made for
+/// searching it many times. Every node lives in one `Vec`, names are
+/// interned once, and `NodeId`/`NodeRef` handles are plain `Copy`
+/// values. `AstNode` itself stays an owned tree: convert with
+/// `from_ast_node` and `to_ast_node`.
+#[derive(Default)]
+pub struct AstArena {
+    nodes: Vec<ArenaNode>,
+    interner: Interner,
+}
+
+impl AstArena {
+    pub fn new() -> Self {
+        AstArena::default()
+    }
+
+    /// Arena holding a copy of the tree, with the id of its root
+    pub fn from_ast_node(node: &AstNode) -> (Self, NodeId) {
+        let mut arena = AstArena::new();
+        arena.nodes.reserve_exact(node.pre_order().count());
+        let root = arena.import(node);
+        (arena, root)
+    }
+
+    /// New detached node
+    pub fn alloc(&mut self, name: &str) -> NodeId {
+        let name = self.interner.intern(name);
+        self.alloc_symbol(name)
+    }
+
+    fn alloc_symbol(&mut self, name: Symbol) -> NodeId {
+        let id = NodeId(NonZeroU32::new(self.nodes.len() as u32 + 1).unwrap());
+        self.nodes.push(ArenaNode {
+            name,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            next_sibling: None,
+        });
+        id
+    }
+
+    /// Append detached node `child` as the last child of `parent`.
+    /// Panics when `child` has a parent or is `parent` or one of its
+    /// ancestors, which would make a cycle.
+    pub fn add(&mut self, parent: NodeId, child: NodeId) -> NodeId {
+        assert!(
+            self.get(child).parent.is_none(),
+            "arena add error: node already has a parent"
+        );
+        let mut ancestor = Some(parent);
+        while let Some(id) = ancestor {
+            assert!(id != child, "arena add error: node is an ancestor");
+            ancestor = self.get(id).parent;
+        }
+        self.get_mut(child).parent = Some(parent);
+        match self.get(parent).last_child {
+            Some(last) => self.get_mut(last).next_sibling = Some(child),
+            None => self.get_mut(parent).first_child = Some(child),
+        }
+        self.get_mut(parent).last_child = Some(child);
+        parent
+    }
+
+    /// Copy the tree into the arena, returns the id of its root
+    pub fn import(&mut self, node: &AstNode) -> NodeId {
+        let name = self.interner.intern(&node.name);
+        let id = self.alloc_symbol(name);
+        for child in node.children() {
+            let child_id = self.import(child);
+            self.add(id, child_id);
+        }
+        id
+    }
+
+    /// Copy the subtree back into an owned `AstNode`
+    pub fn to_ast_node(&self, id: NodeId) -> AstNode {
+        let mut node = AstNode::new(String::new());
+        node.name = self.name(id).to_string();
+        for child in self.node(id).children() {
+            node.add(self.to_ast_node(child.id()));
+        }
+        node
+    }
+
+    pub fn node(&self, id: NodeId) -> NodeRef<'_> {
+        NodeRef { arena: self, id }
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.interner.names[self.get(id).name.0 as usize]
+    }
+
+    pub fn symbol(&self, id: NodeId) -> Symbol {
+        self.get(id).name
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Count of distinct node names
+    pub fn symbol_count(&self) -> usize {
+        self.interner.names.len()
+    }
+
+    /// Approximate heap usage in bytes
+    pub fn heap_size(&self) -> usize {
+        let nodes = self.nodes.capacity() * std::mem::size_of::<ArenaNode>();
+        let names: usize = self.interner.names.iter().map(|name| name.len()).sum();
+        let interner = self.interner.names.capacity() * std::mem::size_of::<Rc<str>>()
+            + self.interner.ids.capacity()
+                * (std::mem::size_of::<Rc<str>>() + std::mem::size_of::<Symbol>());
+        nodes + names + interner
+    }
+
+    fn get(&self, id: NodeId) -> &ArenaNode {
+        &self.nodes[id.index()]
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> &mut ArenaNode {
+        &mut self.nodes[id.index()]
+    }
+}
+
+/// Borrowed node of an arena, as cheap to copy as a reference
+#[derive(Clone, Copy)]
+pub struct NodeRef<'a> {
+    arena: &'a AstArena,
+    id: NodeId,
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn id(self) -> NodeId {
+        self.id
+    }
+
+    pub fn name(self) -> &'a str {
+        self.arena.name(self.id)
+    }
+
+    pub fn name_uppercase(self) -> String {
+        self.name().to_uppercase()
+    }
+
+    pub fn parent(self) -> Option<NodeRef<'a>> {
+        let parent = self.arena.get(self.id).parent?;
+        Some(self.arena.node(parent))
+    }
+
+    pub fn children(self) -> Children<'a> {
+        Children {
+            arena: self.arena,
+            next: self.arena.get(self.id).first_child,
+        }
+    }
+
+    /// The node and all of its descendants in document order
+    pub fn pre_order(self) -> impl Iterator<Item = NodeRef<'a>> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let mut children: Vec<NodeRef<'a>> = node.children().collect();
+            children.reverse();
+            stack.extend(children);
+            Some(node)
+        })
+    }
+
+    /// Same as `AstNode::search`, but hands out a handle instead of a clone
+    pub fn search(self, lambda: &dyn Fn(NodeRef<'a>) -> bool) -> Option<NodeRef<'a>> {
+        self.pre_order().find(|node| lambda(*node))
+    }
+
+    /// Same as `AstNode::search_range_by_name`, without cloning
+    pub fn search_range_by_name(
+        self,
+        start_name: &str,
+        end_name: &str,
+    ) -> Option<Vec<NodeRef<'a>>> {
+        let nodes: Vec<NodeRef<'a>> = self.pre_order().collect();
+
+        let start = nodes.iter().position(|node| node.name() == start_name)?;
+        let end = start
+            + nodes[start..]
+                .iter()
+                .position(|node| node.name() == end_name)?;
+
+        Some(nodes[start..=end].to_vec())
+    }
+}
+
+pub struct Children<'a> {
+    arena: &'a AstArena,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next?;
+        self.next = self.arena.get(id).next_sibling;
+        Some(self.arena.node(id))
+    }
+}
+
+#[test]
+fn arena_round_trip() {
+    let mut update_node = AstNode::from("UPDATE");
+    let mut lp_node = AstNode::from("(");
+    lp_node.add(AstNode::from("AN"));
+    update_node
+        .add(lp_node)
+        .add(AstNode::from("ANYWAY"))
+        .add(AstNode::from(")"));
+
+    let (arena, root) = AstArena::from_ast_node(&update_node);
+    assert_eq!(arena.len(), 5);
+
+    let back = arena.to_ast_node(root);
+    let names: Vec<&str> = back.pre_order().map(|node| node.name.as_str()).collect();
+    assert_eq!(names, vec!["UPDATE", "(", "AN", "ANYWAY", ")"]);
+
+    let range = arena.node(root).search_range_by_name("(", ")").unwrap();
+    let names: Vec<&str> = range.iter().map(|node| node.name()).collect();
+    assert_eq!(names, vec!["(", "AN", "ANYWAY", ")"]);
+    assert_eq!(range[1].parent().unwrap().name(), "(");
+}
+
+#[test]
+fn arena_interns_names() {
+    let mut arena = AstArena::new();
+    let root = arena.alloc("SELECT");
+    for _ in 0..10 {
+        let column = arena.alloc("a");
+        arena.add(root, column);
+    }
+
+    assert_eq!(arena.len(), 11);
+    assert_eq!(arena.symbol_count(), 2);
+    let children: Vec<NodeRef> = arena.node(root).children().collect();
+    assert_eq!(children[0].name(), "a");
+    assert_eq!(
+        arena.symbol(children[0].id()),
+        arena.symbol(children[9].id())
+    );
+
+    let found = arena.node(root).search(&|node| node.name() == "a").unwrap();
+    assert_eq!(found.id(), children[0].id());
+}
+
+#[test]
+#[should_panic(expected = "node is an ancestor")]
+fn arena_rejects_cycles() {
+    let mut arena = AstArena::new();
+    let root = arena.alloc("UPDATE");
+    let lp = arena.alloc("(");
+    arena.add(root, lp);
+    arena.add(lp, root);
+}
+
+#[test]
+#[should_panic(expected = "node is an ancestor")]
+fn arena_rejects_own_child() {
+    let mut arena = AstArena::new();
+    let root = arena.alloc("UPDATE");
+    arena.add(root, root);
+}
+
+#[test]
+fn arena_uses_less_memory() {
+    let mut script = AstNode::from("script");
+    for i in 0..1_000 {
+        let mut update = AstNode::from("UPDATE");
+        update
+            .add(AstNode::from("t"))
+            .add(AstNode::new(format!("column_{}", i % 100)));
+        script.add(update);
+    }
+    let (arena, _) = AstArena::from_ast_node(&script);
+    assert_eq!(arena.symbol_count(), 103);
+    assert!(arena.heap_size() * 2 < script.heap_size());
+}
+
+/// Memory and time of searching a large script, owned tree vs arena.
+/// `cargo test --release arena_bench -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn arena_bench() {
+    use std::time::Instant;
+
+    let statements = 50_000;
+    let mut script = AstNode::from("script");
+    for i in 0..statements {
+        let mut update = AstNode::from("UPDATE");
+        let mut lp = AstNode::from("(");
+        lp.add(AstNode::new(format!("column_{}", i % 100)));
+        lp.add(AstNode::from(","));
+        lp.add(AstNode::from("name"));
+        update
+            .add(AstNode::from("t"))
+            .add(lp)
+            .add(AstNode::from(")"));
+        script.add(update);
+    }
+    let last = format!("column_{}", (statements - 1) % 100);
+    let rounds = 20;
+
+    let started = Instant::now();
+    for _ in 0..rounds {
+        let found = script.search(&|node: &AstNode| node.name == "missing");
+        assert!(found.is_none());
+        let range = script.search_range_by_name("(", ")").unwrap();
+        assert_eq!(range.len(), 5);
+        let clone = script.clone();
+        assert_eq!(clone.children().len(), statements);
+    }
+    let owned_time = started.elapsed();
+
+    let (arena, root) = AstArena::from_ast_node(&script);
+    let started = Instant::now();
+    for _ in 0..rounds {
+        let found = arena.node(root).search(&|node| node.name() == "missing");
+        assert!(found.is_none());
+        let range = arena.node(root).search_range_by_name("(", ")").unwrap();
+        assert_eq!(range.len(), 5);
+        let handle = arena.node(root);
+        assert_eq!(handle.children().count(), statements);
+    }
+    let arena_time = started.elapsed();
+
+    assert!(arena
+        .node(root)
+        .search(&|node| node.name() == last)
+        .is_some());
+    println!(
+        "nodes: {}, owned: {} bytes / {:?}, arena: {} bytes / {:?}",
+        arena.len(),
+        script.heap_size(),
+        owned_time,
+        arena.heap_size(),
+        arena_time
+    );
+}
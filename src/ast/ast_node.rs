@@ -10,8 +10,9 @@ pub struct AstNode {
 type THeapFnMut<'a, T> = Box<dyn 'a + FnMut(&T) -> bool>;
 
 impl AstNode {
+    /// Node keeps the name as written, keyword checks
+    /// go through `name_uppercase`
     pub fn new(name: String) -> Self {
-        let name = name.to_lowercase();
         return AstNode {
             name,
             children: vec![],
@@ -54,47 +55,85 @@ impl AstNode {
         None
     }
 
-    pub fn search_range_by_name(
-        &mut self,
-        start_name: &str,
-        end_name: &str,
-    ) -> Option<Vec<AstNode>> {
-        let mut isn = -1; // start
-        let mut ien = -1; // end
-        let mut icn = -1; // current
+    /// Clones of the nodes from the first `start_name` node
+    /// up to the next `end_name` node, in document order
+    pub fn search_range_by_name(&self, start_name: &str, end_name: &str) -> Option<Vec<AstNode>> {
+        let nodes: Vec<&AstNode> = self.pre_order().collect();
 
-        let mut fsn = |node: &AstNode| {
-            isn += 1;
-            node.name == start_name
-        };
-        let mut esn = |node: &AstNode| {
-            ien += 1;
-            node.name == end_name
-        };
-        let start_node_opt = self.search_mut(&mut fsn);
-        let end_node_opt = self.search_mut(&mut esn);
+        let start = nodes.iter().position(|node| node.name == start_name)?;
+        let end = start + nodes[start..].iter().position(|node| node.name == end_name)?;
+
+        Some(nodes[start..=end].iter().map(|node| (*node).clone()).collect())
+    }
+
+    pub fn name_uppercase(&self) -> String {
+        return self.name.to_uppercase();
+    }
+
+    pub fn children(&self) -> &[AstNode] {
+        return &self.children;
+    }
 
-        let is_borders_node_exist = start_node_opt.is_some() && end_node_opt.is_some();
-        if is_borders_node_exist {
-            let mut collected_nodes_vec: Vec<AstNode> = Vec::new();
+    pub fn children_mut(&mut self) -> &mut Vec<AstNode> {
+        return &mut self.children;
+    }
 
-            let mut collect_nodes = |node: &AstNode| {
-                icn += 1;
+    /// First node in document order that satisfies the lambda, borrowed
+    pub fn find(&self, lambda: impl Fn(&AstNode) -> bool) -> Option<&AstNode> {
+        self.pre_order().find(|node| lambda(node))
+    }
 
-                if icn >= isn && icn <= ien {
-                    collected_nodes_vec.push(node.clone())
-                };
-                false
-            };
+    pub fn insert(&mut self, index: usize, node: AstNode) -> &mut Self {
+        self.children.insert(index, node);
+        self
+    }
 
-            self.search_mut(&mut collect_nodes);
-            return Some(collected_nodes_vec);
+    pub fn remove(&mut self, index: usize) -> Option<AstNode> {
+        if index >= self.children.len() {
+            return None;
         }
-        return None;
+        Some(self.children.remove(index))
     }
 
-    pub fn name_uppercase(&self) -> String {
-        return self.name.to_uppercase();
+    /// Put node at index, returns the replaced one
+    pub fn replace(&mut self, index: usize, node: AstNode) -> Option<AstNode> {
+        let slot = self.children.get_mut(index)?;
+        Some(std::mem::replace(slot, node))
+    }
+
+    /// Node by child indexes from this node, empty path is the node itself
+    pub fn node_at(&self, path: &[usize]) -> Option<&AstNode> {
+        let mut node = self;
+        for &i in path {
+            node = node.children.get(i)?;
+        }
+        Some(node)
+    }
+
+    pub fn node_at_mut(&mut self, path: &[usize]) -> Option<&mut AstNode> {
+        let mut node = self;
+        for &i in path {
+            node = node.children.get_mut(i)?;
+        }
+        Some(node)
+    }
+
+    /// Cut the subtree at path out of the tree
+    pub fn detach(&mut self, path: &[usize]) -> Option<AstNode> {
+        let (last, parent_path) = path.split_last()?;
+        self.node_at_mut(parent_path)?.remove(*last)
+    }
+
+    /// Approximate heap usage of the subtree in bytes
+    pub fn heap_size(&self) -> usize {
+        let own = self.name.capacity() + self.children.capacity() * std::mem::size_of::<AstNode>();
+        own + self.children.iter().map(AstNode::heap_size).sum::<usize>()
+    }
+
+    /// Swap the subtree at path with node, returns the old subtree
+    pub fn replace_at(&mut self, path: &[usize], node: AstNode) -> Option<AstNode> {
+        let (last, parent_path) = path.split_last()?;
+        self.node_at_mut(parent_path)?.replace(*last, node)
     }
 }
 
@@ -156,8 +195,8 @@ fn search_node_by_range() {
 
     let nodes = nodes.as_slice();
     assert_eq!(nodes[0].name, "(");
-    assert_eq!(nodes[1].name, "an");
-    assert_eq!(nodes[2].name, "anyway");
+    assert_eq!(nodes[1].name, "AN");
+    assert_eq!(nodes[2].name, "ANYWAY");
     assert_eq!(nodes[3].name, ")");
 }
 
@@ -176,6 +215,46 @@ fn invalid_search_node_by_range() {
     assert_eq!(nodes.is_none(), true);
 }
 
+#[test]
+fn search_node_by_range_skips_earlier_end() {
+    let mut update_node = AstNode::new(String::from("UPDATE"));
+    let mut lp_node = AstNode::new(String::from("("));
+
+    lp_node.add(AstNode::from("a"));
+    update_node
+        .add(AstNode::from(")"))
+        .add(lp_node)
+        .add(AstNode::from(")"));
+
+    let nodes = update_node.search_range_by_name("(", ")").unwrap();
+
+    let names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+    assert_eq!(names, vec!["(", "a", ")"]);
+}
+
+#[test]
+fn edit_children_in_place() {
+    let mut root = AstNode::from("root");
+    let mut a = AstNode::from("a");
+    a.add(AstNode::from("a1"));
+    root.add(a).add(AstNode::from("c"));
+
+    root.insert(1, AstNode::from("b"));
+    assert_eq!(root.node_at(&[1]).unwrap().name, "b");
+
+    let old = root.replace_at(&[0, 0], AstNode::from("a2")).unwrap();
+    assert_eq!(old.name, "a1");
+    assert_eq!(root.node_at(&[0, 0]).unwrap().name, "a2");
+
+    let detached = root.detach(&[0]).unwrap();
+    assert_eq!(detached.children()[0].name, "a2");
+    assert_eq!(root.children().len(), 2);
+
+    assert!(root.remove(5).is_none());
+    assert!(root.detach(&[]).is_none());
+    assert_eq!(root.find(|node| node.name == "c").unwrap().name, "c");
+}
+
 mod test {
     use crate::ast::ast_node::*;
 
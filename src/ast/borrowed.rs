@@ -0,0 +1,220 @@
+use crate::ast::ast_node::AstNode;
+use crate::ast::dialect::Dialect;
+use crate::ast::extension::{find_extensions, ExtensionNode};
+use crate::ast::lexer::{Lexer, Token, TokenKind};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Node of a tree borrowing the source: every name is a slice of it.
+/// The root holds the statements, a statement holds its tokens up to
+/// and including the `;`, and a `(` holds the tokens up to and
+/// including its `)`. Whitespace and comments are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowedNode<'src> {
+    /// token text, or the whole text of the root or a statement
+    pub name: &'src str,
+    /// `None` for the root and statements
+    pub kind: Option<TokenKind>,
+    pub span: Range<usize>,
+    pub children: Vec<BorrowedNode<'src>>,
+}
+
+impl<'src> BorrowedNode<'src> {
+    fn token(token: Token<'src>) -> Self {
+        BorrowedNode {
+            name: token.text,
+            kind: Some(token.kind),
+            span: token.span,
+            children: vec![],
+        }
+    }
+
+    fn group(source: &'src str, span: Range<usize>, children: Vec<BorrowedNode<'src>>) -> Self {
+        BorrowedNode {
+            name: &source[span.clone()],
+            kind: None,
+            span,
+            children,
+        }
+    }
+
+    pub fn children(&self) -> &[BorrowedNode<'src>] {
+        &self.children
+    }
+
+    /// Name as compared by SQL, see `Token::canonical`.
+    /// Only copies when folding or unescaping changes the text.
+    pub fn canonical(&self) -> Cow<'src, str> {
+        match self.kind {
+            Some(kind) => Token {
+                kind,
+                text: self.name,
+                span: self.span.clone(),
+            }
+            .canonical(),
+            None => Cow::Borrowed(self.name),
+        }
+    }
+
+    /// The node, then its children depth first
+    pub fn pre_order(&self) -> impl Iterator<Item = &BorrowedNode<'src>> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Token nodes in source order
+    pub fn tokens(&self) -> impl Iterator<Item = &BorrowedNode<'src>> {
+        self.pre_order().filter(|node| node.kind.is_some())
+    }
+
+    /// Owned copy of the subtree
+    pub fn to_ast_node(&self) -> AstNode {
+        let mut node = AstNode::from(self.name);
+        for child in &self.children {
+            node.add(child.to_ast_node());
+        }
+        node
+    }
+}
+
+/// Tree and vendor extensions of a borrowed source
+pub struct BorrowedAst<'src> {
+    pub root: BorrowedNode<'src>,
+    pub extensions: Vec<ExtensionNode>,
+}
+
+impl<'src> BorrowedAst<'src> {
+    pub fn statements(&self) -> &[BorrowedNode<'src>] {
+        &self.root.children
+    }
+}
+
+/// Significant tokens of the source, whitespace and comments left out
+pub(crate) fn significant_tokens<'src>(
+    source: &'src str,
+    dialect: &'static dyn Dialect,
+) -> Vec<Token<'src>> {
+    Lexer::with_dialect(source, dialect)
+        .filter(|t| !t.kind.is_trivia())
+        .collect()
+}
+
+/// Zero-copy parse: only the tree itself is allocated, names are
+/// slices of `source` and are folded on demand with `canonical`.
+/// Nodes are built straight from the lexer; only the tokens of the
+/// current statement are kept, for the extension patterns.
+pub fn parse_borrowed<'src>(source: &'src str, dialect: &'static dyn Dialect) -> BorrowedAst<'src> {
+    let mut extensions = vec![];
+    let mut statement: Vec<Token> = vec![];
+
+    // open statement and `(` nodes, innermost last
+    let mut open: Vec<(usize, Vec<BorrowedNode>)> = vec![];
+    let mut statements = vec![];
+    for token in Lexer::with_dialect(source, dialect).filter(|t| !t.kind.is_trivia()) {
+        let start = token.span.start;
+        if open.is_empty() {
+            open.push((start, vec![]));
+        }
+        let (opens, closes, ends) = (
+            token.is_operator("("),
+            token.is_operator(")") && open.len() > 1,
+            token.is_operator(";"),
+        );
+        statement.push(token.clone());
+        if opens {
+            open.push((start, vec![BorrowedNode::token(token)]));
+            continue;
+        }
+        let end = token.span.end;
+        open.last_mut().unwrap().1.push(BorrowedNode::token(token));
+        if closes {
+            close_paren(&mut open);
+        }
+        if ends {
+            extensions.extend(find_extensions(&statement, dialect));
+            statement.clear();
+            statements.push(close_statement(source, &mut open, end));
+        }
+    }
+    extensions.extend(find_extensions(&statement, dialect));
+    if let Some(end) = open
+        .iter()
+        .flat_map(|(_, children)| children.last())
+        .map(|last| last.span.end)
+        .max()
+    {
+        statements.push(close_statement(source, &mut open, end));
+    }
+
+    BorrowedAst {
+        root: BorrowedNode::group(source, 0..source.len(), statements),
+        extensions,
+    }
+}
+
+/// Move the innermost `(` with its tokens into the enclosing node
+fn close_paren(open: &mut Vec<(usize, Vec<BorrowedNode>)>) {
+    let (_, mut children) = open.pop().unwrap();
+    let inner = children.split_off(1);
+    let mut paren = children.pop().unwrap();
+    paren.children = inner;
+    open.last_mut().unwrap().1.push(paren);
+}
+
+/// Statement node of everything still open, unclosed `(` included
+fn close_statement<'src>(
+    source: &'src str,
+    open: &mut Vec<(usize, Vec<BorrowedNode<'src>>)>,
+    end: usize,
+) -> BorrowedNode<'src> {
+    while open.len() > 1 {
+        close_paren(open);
+    }
+    let (start, children) = open.pop().unwrap();
+    BorrowedNode::group(source, start..end, children)
+}
+
+#[test]
+fn borrowed_tree() {
+    use crate::ast::dialect::PostgreSql;
+
+    let src = String::from("SELECT \"Name\", f(id) FROM t LIMIT 5; DELETE FROM (x");
+    let ast = parse_borrowed(&src, &PostgreSql);
+
+    let statements = ast.statements();
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].name, "SELECT \"Name\", f(id) FROM t LIMIT 5;");
+    assert_eq!(statements[1].name, "DELETE FROM (x");
+    assert!(std::ptr::eq(statements[1].name, &src[37..]));
+
+    fn names<'a>(node: &BorrowedNode<'a>) -> Vec<&'a str> {
+        node.children().iter().map(|child| child.name).collect()
+    }
+    assert_eq!(
+        names(&statements[0]),
+        vec!["SELECT", "\"Name\"", ",", "f", "(", "FROM", "t", "LIMIT", "5", ";"]
+    );
+    assert_eq!(names(&statements[0].children()[4]), vec!["id", ")"]);
+    assert_eq!(names(&statements[1].children()[2]), vec!["x"]);
+
+    let first = &statements[0].children();
+    assert_eq!(first[1].canonical(), "Name");
+    assert!(matches!(first[0].canonical(), Cow::Borrowed("SELECT")));
+    assert_eq!(first[6].canonical(), "T");
+    assert_eq!(ast.extensions.len(), 1);
+
+    let tokens: Vec<&str> = ast.root.tokens().map(|node| node.name).collect();
+    assert_eq!(tokens.len(), 16);
+    assert_eq!(tokens[4..7], ["(", "id", ")"]);
+
+    let owned = statements[0].children()[4].to_ast_node();
+    assert_eq!(owned.children()[0].name, "id");
+
+    assert!(parse_borrowed("  -- only a comment\n", &PostgreSql)
+        .statements()
+        .is_empty());
+}
@@ -0,0 +1,647 @@
+use crate::ast::action::Action;
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::is_reserved;
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::select_sublist::SelectSublist;
+use crate::ast::set_quantifier::SetQuantifier;
+use crate::ast::unparse::ToSql;
+
+/// Value bound to a `?` parameter marker
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Null,
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// Name which isn't a valid identifier is taken as delimited,
+/// a reserved word in upper case, the name it would fold to.
+/// An empty name is kept and rejected by `build`.
+fn identifier(name: &str) -> Identifier {
+    Identifier::parse(name).unwrap_or_else(|_| {
+        if is_reserved(name) {
+            Identifier::Delimited(name.to_uppercase())
+        } else {
+            Identifier::Delimited(name.to_string())
+        }
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    name: QualifiedName,
+    alias: Option<Identifier>,
+}
+
+/// Table by its name, `schema.table` is split on the dot
+pub fn table(name: &str) -> Table {
+    let name = QualifiedName::parse(name).unwrap_or_else(|_| identifier(name).into());
+    Table { name, alias: None }
+}
+
+impl Table {
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = Some(identifier(alias));
+        self
+    }
+
+    /// Column qualified by the alias, or by the table name
+    pub fn col(&self, name: &str) -> Column {
+        let qualifier = self
+            .alias
+            .clone()
+            .map_or_else(|| self.name.clone(), QualifiedName::from);
+        Column {
+            qualifier: Some(qualifier),
+            name: identifier(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    qualifier: Option<QualifiedName>,
+    name: Identifier,
+}
+
+impl Column {
+    /// Qualifier and name as one dotted name
+    fn reference(&self) -> QualifiedName {
+        let mut parts = self
+            .qualifier
+            .as_ref()
+            .map_or_else(Vec::new, |qualifier| qualifier.parts().to_vec());
+        parts.push(self.name.clone());
+        QualifiedName::new(parts).unwrap()
+    }
+
+    fn has_empty_name(&self) -> bool {
+        self.reference()
+            .parts()
+            .iter()
+            .any(|part| part.value().is_empty())
+    }
+}
+
+/// Unqualified column
+pub fn col(name: &str) -> Column {
+    Column {
+        qualifier: None,
+        name: identifier(name),
+    }
+}
+
+/// Right side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Column(Column),
+    Value(Value),
+}
+
+impl From<Column> for Operand {
+    fn from(column: Column) -> Self {
+        Operand::Column(column)
+    }
+}
+
+macro_rules! value_operands {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for Operand {
+            fn from(value: $ty) -> Self {
+                Operand::Value(value.into())
+            }
+        })*
+    };
+}
+
+value_operands!(Value, i32, i64, f64, &str, String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Ne => "<>",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare(Column, Comparison, Operand),
+    IsNull(Column, bool),
+    Like(Column, Operand),
+    In(Column, Vec<Operand>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Column {
+    fn compare(self, operator: Comparison, operand: Operand) -> Condition {
+        match operand {
+            // `= NULL` is never true
+            Operand::Value(Value::Null) if operator == Comparison::Eq => {
+                Condition::IsNull(self, true)
+            }
+            Operand::Value(Value::Null) if operator == Comparison::Ne => {
+                Condition::IsNull(self, false)
+            }
+            operand => Condition::Compare(self, operator, operand),
+        }
+    }
+
+    pub fn eq(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Eq, operand.into())
+    }
+
+    pub fn ne(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Ne, operand.into())
+    }
+
+    pub fn lt(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Lt, operand.into())
+    }
+
+    pub fn le(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Le, operand.into())
+    }
+
+    pub fn gt(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Gt, operand.into())
+    }
+
+    pub fn ge(self, operand: impl Into<Operand>) -> Condition {
+        self.compare(Comparison::Ge, operand.into())
+    }
+
+    pub fn like(self, pattern: impl Into<Operand>) -> Condition {
+        Condition::Like(self, pattern.into())
+    }
+
+    pub fn is_null(self) -> Condition {
+        Condition::IsNull(self, true)
+    }
+
+    pub fn is_not_null(self) -> Condition {
+        Condition::IsNull(self, false)
+    }
+
+    /// `IN` with one marker per value; an empty list is never true
+    pub fn in_list<T: Into<Operand>>(self, values: impl IntoIterator<Item = T>) -> Condition {
+        Condition::In(self, values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Condition {
+    pub fn and(self, other: Condition) -> Condition {
+        Condition::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Condition) -> Condition {
+        Condition::Or(Box::new(self), Box::new(other))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Condition {
+        Condition::Not(Box::new(self))
+    }
+}
+
+/// Statement text with the values of its `?` markers, in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+/// `SELECT` before its FROM clause
+#[derive(Debug, Clone, Default)]
+pub struct Select {
+    quantifier: Option<SetQuantifier>,
+    columns: Vec<Column>,
+}
+
+pub fn select() -> Select {
+    Select::default()
+}
+
+impl Select {
+    pub fn distinct(mut self) -> Self {
+        self.quantifier = Some(SetQuantifier::DISTINCT);
+        self
+    }
+
+    pub fn all(mut self) -> Self {
+        self.quantifier = Some(SetQuantifier::ALL);
+        self
+    }
+
+    /// Add a column to the select list, without columns it is `*`
+    pub fn column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// FROM is required, only a query with one can be built
+    pub fn from(self, table: Table) -> SelectFrom {
+        SelectFrom {
+            select: self,
+            from: table,
+            joins: vec![],
+            filter: None,
+            order: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JoinKind {
+    Inner,
+    Left,
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectFrom {
+    select: Select,
+    from: Table,
+    joins: Vec<(JoinKind, Table, Condition)>,
+    filter: Option<Condition>,
+    order: Vec<(Column, bool)>,
+}
+
+impl SelectFrom {
+    pub fn join(mut self, table: Table, on: Condition) -> Self {
+        self.joins.push((JoinKind::Inner, table, on));
+        self
+    }
+
+    pub fn left_join(mut self, table: Table, on: Condition) -> Self {
+        self.joins.push((JoinKind::Left, table, on));
+        self
+    }
+
+    /// WHERE condition, joined with AND to the previous ones
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(filter) => filter.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// Sort key, a column of the select list. SQL-92 sort keys are
+    /// unqualified: the key is written as the column name, or as its
+    /// position when another selected column has the same name.
+    /// Without a column list the name has to be unique in the FROM tables.
+    pub fn order_by(mut self, column: Column) -> Self {
+        self.order.push((column, false));
+        self
+    }
+
+    pub fn order_by_desc(mut self, column: Column) -> Self {
+        self.order.push((column, true));
+        self
+    }
+
+    /// Typed node of the query head, unparsed by `build`.
+    /// FROM, WHERE and ORDER BY have no typed nodes yet,
+    /// `build` writes them itself.
+    pub fn statement(&self) -> SelectStatementSingleRow {
+        let list = if self.select.columns.is_empty() {
+            SelectList::ASTERISK
+        } else {
+            SelectList::SUBLISTS(
+                self.select
+                    .columns
+                    .iter()
+                    .map(|column| SelectSublist(column.reference()))
+                    .collect(),
+            )
+        };
+        (Action::SELECT, self.select.quantifier.clone(), list)
+    }
+
+    /// Statement text and its parameters. Fails on an empty name,
+    /// which SQL-92 can't express, or on a sort key which isn't selected.
+    pub fn build(&self) -> Result<Query, &'static str> {
+        let mut writer = Writer::default();
+        writer.sql.push_str(&self.statement().to_sql());
+        writer.empty_name |= self.select.columns.iter().any(Column::has_empty_name);
+
+        writer.push("FROM");
+        writer.table(&self.from);
+        for (kind, table, on) in &self.joins {
+            writer.push(match kind {
+                JoinKind::Inner => "JOIN",
+                JoinKind::Left => "LEFT JOIN",
+            });
+            writer.table(table);
+            writer.sql.push_str(" ON ");
+            writer.condition(on, false);
+        }
+        if let Some(filter) = &self.filter {
+            writer.sql.push_str(" WHERE ");
+            writer.condition(filter, false);
+        }
+        for (i, (column, descending)) in self.order.iter().enumerate() {
+            writer
+                .sql
+                .push_str(if i == 0 { " ORDER BY " } else { ", " });
+            writer.sort_key(column, &self.select.columns)?;
+            if *descending {
+                writer.push("DESC");
+            }
+        }
+
+        if writer.empty_name {
+            return Err("builder: empty name");
+        }
+        Ok(Query {
+            sql: writer.sql,
+            params: writer.params,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    sql: String,
+    params: Vec<Value>,
+    empty_name: bool,
+}
+
+impl Writer {
+    fn name(&mut self, name: &Identifier) {
+        self.empty_name |= name.value().is_empty();
+        self.sql.push_str(&name.to_sql());
+    }
+
+    fn qualified_name(&mut self, name: &QualifiedName) {
+        for (i, part) in name.parts().iter().enumerate() {
+            if i > 0 {
+                self.sql.push('.');
+            }
+            self.name(part);
+        }
+    }
+
+    /// Word after a space
+    fn push(&mut self, word: &str) {
+        self.sql.push(' ');
+        self.sql.push_str(word);
+    }
+
+    fn table(&mut self, table: &Table) {
+        self.sql.push(' ');
+        self.qualified_name(&table.name);
+        if let Some(alias) = &table.alias {
+            self.sql.push(' ');
+            self.name(alias);
+        }
+    }
+
+    fn column(&mut self, column: &Column) {
+        if let Some(qualifier) = &column.qualifier {
+            self.qualified_name(qualifier);
+            self.sql.push('.');
+        }
+        self.name(&column.name);
+    }
+
+    /// Unqualified name or position in the select list
+    fn sort_key(&mut self, column: &Column, selected: &[Column]) -> Result<(), &'static str> {
+        if selected.is_empty() {
+            self.name(&column.name);
+            return Ok(());
+        }
+        let position = selected
+            .iter()
+            .position(|c| c == column)
+            .ok_or("builder: ORDER BY column isn't selected")?;
+        let shared = selected.iter().filter(|c| c.name == column.name).count() > 1;
+        if shared {
+            self.sql.push_str(&(position + 1).to_string());
+        } else {
+            self.name(&column.name);
+        }
+        Ok(())
+    }
+
+    fn operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Column(column) => self.column(column),
+            Operand::Value(value) => {
+                self.sql.push('?');
+                self.params.push(value.clone());
+            }
+        }
+    }
+
+    /// `nested` conditions are parenthesized when they are OR
+    fn condition(&mut self, condition: &Condition, nested: bool) {
+        match condition {
+            Condition::Compare(column, operator, operand) => {
+                self.column(column);
+                self.sql.push_str(&format!(" {} ", operator.as_str()));
+                self.operand(operand);
+            }
+            Condition::IsNull(column, null) => {
+                self.column(column);
+                self.push(if *null { "IS NULL" } else { "IS NOT NULL" });
+            }
+            Condition::Like(column, pattern) => {
+                self.column(column);
+                self.sql.push_str(" LIKE ");
+                self.operand(pattern);
+            }
+            // SQL-92 has no empty IN list
+            Condition::In(_, values) if values.is_empty() => self.sql.push_str("1 = 0"),
+            Condition::In(column, values) => {
+                self.column(column);
+                self.sql.push_str(" IN (");
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        self.sql.push_str(", ");
+                    }
+                    self.operand(value);
+                }
+                self.sql.push(')');
+            }
+            Condition::And(left, right) => {
+                self.condition(left, true);
+                self.sql.push_str(" AND ");
+                self.condition(right, true);
+            }
+            Condition::Or(left, right) => {
+                if nested {
+                    self.sql.push('(');
+                }
+                self.condition(left, false);
+                self.sql.push_str(" OR ");
+                self.condition(right, false);
+                if nested {
+                    self.sql.push(')');
+                }
+            }
+            Condition::Not(inner) => {
+                self.sql.push_str("NOT (");
+                self.condition(inner, false);
+                self.sql.push(')');
+            }
+        }
+    }
+}
+
+#[test]
+fn build_select_with_joins() {
+    let o = table("orders").alias("o");
+    let c = table("sales.customer").alias("c");
+    let query = select()
+        .distinct()
+        .column(o.col("id"))
+        .column(c.col("name"))
+        .column(c.col("id"))
+        .from(o.clone())
+        .join(c.clone(), o.col("customer").eq(c.col("id")))
+        .filter(o.col("amount").gt(100))
+        .filter(c.col("country").eq("NL").or(c.col("vip").eq(1)))
+        .filter(o.col("status").in_list(vec!["new", "paid"]))
+        .filter(o.col("note").eq(Value::Null))
+        .order_by_desc(o.col("id"))
+        .order_by(c.col("name"));
+    let statement = query.statement();
+    let query = query.build().unwrap();
+
+    assert_eq!(
+        query.sql,
+        "SELECT DISTINCT o.id, c.name, c.id FROM orders o JOIN sales.customer c ON o.customer = c.id \
+         WHERE o.amount > ? AND (c.country = ? OR c.vip = ?) AND o.status IN (?, ?) \
+         AND o.note IS NULL ORDER BY 1 DESC, name"
+    );
+    assert_eq!(
+        query.params,
+        vec![
+            Value::Integer(100),
+            Value::Text("NL".to_string()),
+            Value::Integer(1),
+            Value::Text("new".to_string()),
+            Value::Text("paid".to_string()),
+        ]
+    );
+
+    let tree = crate::ast::cst::CstNode::parse(&query.sql).unwrap();
+    assert_eq!(
+        tree.statements()[0].select_statement_single_row(),
+        Some(statement)
+    );
+}
+
+#[test]
+fn built_queries_are_sql_92() {
+    use crate::ast::cst::CstNode;
+    use crate::ast::flagger::{flag_source, Level};
+
+    let query = select()
+        .all()
+        .from(table("order"))
+        .left_join(table("Line Item"), col("a").eq(col("b")))
+        .filter(col("x").like("%a%").not())
+        .filter(col("y").in_list(Vec::<i32>::new()));
+    assert_eq!(
+        query.statement(),
+        (
+            Action::SELECT,
+            Some(SetQuantifier::ALL),
+            SelectList::ASTERISK
+        )
+    );
+
+    let sql = query.build().unwrap().sql;
+    assert_eq!(
+        sql,
+        "SELECT ALL * FROM \"ORDER\" LEFT JOIN \"Line Item\" ON a = b \
+         WHERE NOT (x LIKE ?) AND 1 = 0"
+    );
+    let levels = flag_source(&sql);
+    assert!(levels[0].level < Level::Extension);
+
+    let tree = CstNode::parse(&sql).unwrap();
+    assert_eq!(
+        tree.statements()[0].select_statement_single_row(),
+        Some(query.statement())
+    );
+}
+
+#[test]
+fn invalid_queries_are_rejected() {
+    let empty = [
+        select().from(table("")),
+        select().column(col("")).from(table("t")),
+        select().from(table("t").alias("")),
+        select().from(table("t")).filter(col("").eq(1)),
+    ];
+    for query in empty.iter() {
+        assert_eq!(query.build(), Err("builder: empty name"));
+    }
+
+    let t = table("t");
+    let query = select()
+        .column(t.col("a"))
+        .from(t.clone())
+        .order_by(t.col("b"));
+    assert_eq!(
+        query.build(),
+        Err("builder: ORDER BY column isn't selected")
+    );
+    let query = select().from(t.clone()).order_by_desc(t.col("b"));
+    assert_eq!(
+        query.build().unwrap().sql,
+        "SELECT * FROM t ORDER BY b DESC"
+    );
+}
@@ -1,4 +1,3 @@
-pub type ColumnNameList<T>
-where
-    T: Into<String> + Sized,
-= Vec<T>;
+use crate::ast::identifier::Identifier;
+
+pub type ColumnNameList = Vec<Identifier>;
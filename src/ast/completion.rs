@@ -0,0 +1,581 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, Token, TokenKind};
+use crate::ast::lineage::Catalog;
+use crate::ast::unparse::ToSql;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Keyword,
+    Table,
+    Column,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// text to insert
+    pub label: String,
+    pub kind: CompletionKind,
+    /// table of a column
+    pub detail: Option<String>,
+}
+
+/// Clause the cursor is in, from the last clause key word before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Clause {
+    Select,
+    From,
+    /// WHERE, HAVING or a join condition
+    Condition,
+    GroupBy,
+    OrderBy,
+    /// assignments of UPDATE
+    Set,
+    Other,
+}
+
+const STATEMENT_START: &[Keyword] = &[
+    Keyword::Select,
+    Keyword::Insert,
+    Keyword::Update,
+    Keyword::Delete,
+    Keyword::Create,
+    Keyword::Drop,
+    Keyword::Alter,
+    Keyword::Grant,
+    Keyword::Revoke,
+    Keyword::Commit,
+    Keyword::Rollback,
+    Keyword::Set,
+    Keyword::Declare,
+    Keyword::Open,
+    Keyword::Close,
+    Keyword::Fetch,
+];
+
+const EXPRESSION_START: &[Keyword] = &[
+    Keyword::Case,
+    Keyword::Cast,
+    Keyword::Coalesce,
+    Keyword::Nullif,
+    Keyword::Null,
+    Keyword::Count,
+    Keyword::Sum,
+    Keyword::Avg,
+    Keyword::Min,
+    Keyword::Max,
+    Keyword::Substring,
+    Keyword::Upper,
+    Keyword::Lower,
+    Keyword::Trim,
+    Keyword::Position,
+    Keyword::Extract,
+    Keyword::User,
+];
+
+const PRIVILEGES: &[Keyword] = &[
+    Keyword::Select,
+    Keyword::Insert,
+    Keyword::Update,
+    Keyword::Delete,
+    Keyword::References,
+    Keyword::Usage,
+    Keyword::All,
+];
+
+const SET_OPERATORS: &[Keyword] = &[Keyword::Union, Keyword::Except, Keyword::Intersect];
+
+const JOINS: &[Keyword] = &[
+    Keyword::Join,
+    Keyword::Inner,
+    Keyword::Left,
+    Keyword::Right,
+    Keyword::Full,
+    Keyword::Cross,
+    Keyword::Natural,
+];
+
+/// Completions in strict SQL-92, see `complete_with`
+pub fn complete(src: &str, offset: usize, catalog: &Catalog) -> Vec<Completion> {
+    complete_with(src, offset, catalog, dialect::DEFAULT)
+}
+
+/// Completions at `offset`, best first.
+/// Every completion starts with the part of the word before the cursor.
+/// After FROM and JOIN come the relations of the catalog, after `q.`
+/// the columns of the table or correlation name `q`, elsewhere the key
+/// words which may follow, with the columns in scope where an expression goes.
+pub fn complete_with(
+    src: &str,
+    offset: usize,
+    catalog: &Catalog,
+    dialect: &dyn Dialect,
+) -> Vec<Completion> {
+    let (tokens, _) = tokenize_with(src, dialect);
+    let inside = |t: &Token| t.span.start < offset && offset < t.span.end;
+    if tokens
+        .iter()
+        .any(|t| inside(t) && !is_word(t) && t.kind != TokenKind::Whitespace)
+    {
+        // in a comment or a literal
+        return vec![];
+    }
+    let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+
+    let start = tokens
+        .iter()
+        .rposition(|t| t.is_operator(";") && t.span.end <= offset)
+        .map_or(0, |i| i + 1);
+    let end = tokens[start..]
+        .iter()
+        .position(|t| t.is_operator(";"))
+        .map_or(tokens.len(), |i| start + i);
+    let statement = &tokens[start..end];
+
+    let mut before: Vec<&Token> = statement.iter().filter(|t| t.span.end <= offset).collect();
+    let mut prefix = "";
+    if let Some(word) = statement
+        .iter()
+        .find(|t| t.span.start < offset && offset <= t.span.end && is_word(t))
+    {
+        prefix = &src[word.span.start..offset];
+        before.retain(|t| t.span.start != word.span.start);
+    }
+
+    let mut open = vec![];
+    for (i, token) in before.iter().enumerate() {
+        if token.is_operator("(") {
+            open.push(i);
+        } else if token.is_operator(")") {
+            open.pop();
+        }
+    }
+    let context = match open.last() {
+        Some(&i) => &before[i + 1..],
+        None => &before[..],
+    };
+
+    let mut completions = Completions {
+        prefix: prefix.to_uppercase(),
+        catalog,
+        scope: scope(statement, catalog),
+        items: vec![],
+    };
+    completions.context(context, open.is_empty(), statement);
+    completions.items
+}
+
+struct Completions<'a> {
+    prefix: String,
+    catalog: &'a Catalog,
+    /// relations the statement names
+    scope: Vec<&'a QualifiedName>,
+    items: Vec<Completion>,
+}
+
+impl<'a> Completions<'a> {
+    fn push(&mut self, label: String, kind: CompletionKind, detail: Option<String>) {
+        let duplicate = self
+            .items
+            .iter()
+            .any(|item| item.label == label && item.kind == kind);
+        if label.to_uppercase().starts_with(&self.prefix) && !duplicate {
+            self.items.push(Completion {
+                label,
+                kind,
+                detail,
+            });
+        }
+    }
+
+    fn keywords(&mut self, keywords: &[Keyword]) {
+        for keyword in keywords {
+            self.push(keyword.as_str().to_string(), CompletionKind::Keyword, None);
+        }
+    }
+
+    fn tables(&mut self) {
+        for name in self.catalog.names() {
+            self.push(name.to_sql(), CompletionKind::Table, None);
+        }
+    }
+
+    fn columns(&mut self, relation: &QualifiedName) {
+        for column in self.catalog.relation_columns(relation).unwrap_or_default() {
+            self.push(
+                column.to_sql(),
+                CompletionKind::Column,
+                Some(relation.to_sql()),
+            );
+        }
+    }
+
+    /// Columns in scope, all columns of the catalog when none is
+    fn scope_columns(&mut self) {
+        let relations = match self.scope.is_empty() {
+            true => self.catalog.names(),
+            false => self.scope.clone(),
+        };
+        for relation in relations {
+            self.columns(relation);
+        }
+    }
+
+    fn expression(&mut self, clause: Clause) {
+        self.scope_columns();
+        if clause == Clause::Condition {
+            self.keywords(&[Keyword::Not, Keyword::Exists]);
+        }
+        self.keywords(EXPRESSION_START);
+    }
+
+    /// What may follow the tokens of the innermost statement
+    /// or parenthesis before the cursor
+    fn context(&mut self, context: &[&Token], top: bool, statement: &[Token]) {
+        let clause = clause(context);
+        let prev = match context.last() {
+            Some(prev) => *prev,
+            None if top => return self.keywords(STATEMENT_START),
+            None => {
+                self.keywords(&[Keyword::Select]);
+                return self.expression(Clause::Other);
+            }
+        };
+        let prev2 = context.len().checked_sub(2).map(|i| context[i]);
+        let first = |keyword| context[0].is_keyword(keyword);
+
+        if prev.is_operator(".") {
+            if let Some(relation) = prev2.and_then(|q| self.relation(q, statement)) {
+                self.columns(relation);
+            }
+            return;
+        }
+        if prev.is_operator(",") {
+            return match clause {
+                Clause::From => self.tables(),
+                Clause::Set => self.update_target(context),
+                _ => self.expression(clause),
+            };
+        }
+        if prev.kind == TokenKind::Operator && !prev.is_operator(")") && !prev.is_operator("*") {
+            return self.expression(clause);
+        }
+
+        if let TokenKind::Keyword(keyword) = prev.kind {
+            match keyword {
+                Keyword::From | Keyword::Join | Keyword::Into => return self.tables(),
+                Keyword::Table if !context[0].is_keyword(Keyword::Create) => return self.tables(),
+                Keyword::Update if context.len() == 1 && top => return self.tables(),
+                Keyword::Insert => return self.keywords(&[Keyword::Into]),
+                Keyword::Delete if context.len() == 1 => return self.keywords(&[Keyword::From]),
+                Keyword::Create => {
+                    return self.keywords(&[
+                        Keyword::Table,
+                        Keyword::View,
+                        Keyword::Domain,
+                        Keyword::Schema,
+                        Keyword::Assertion,
+                        Keyword::Global,
+                        Keyword::Local,
+                    ])
+                }
+                Keyword::Drop => {
+                    return self.keywords(&[
+                        Keyword::Table,
+                        Keyword::View,
+                        Keyword::Domain,
+                        Keyword::Schema,
+                        Keyword::Assertion,
+                    ])
+                }
+                Keyword::Alter => return self.keywords(&[Keyword::Table, Keyword::Domain]),
+                Keyword::Grant | Keyword::Revoke => return self.keywords(PRIVILEGES),
+                Keyword::Commit | Keyword::Rollback => return self.keywords(&[Keyword::Work]),
+                Keyword::Order | Keyword::Group => return self.keywords(&[Keyword::By]),
+                Keyword::Union | Keyword::Except | Keyword::Intersect => {
+                    return self.keywords(&[Keyword::Select, Keyword::All, Keyword::Corresponding])
+                }
+                Keyword::Left | Keyword::Right | Keyword::Full => {
+                    return self.keywords(&[Keyword::Outer, Keyword::Join])
+                }
+                Keyword::Inner | Keyword::Outer | Keyword::Cross => {
+                    return self.keywords(&[Keyword::Join])
+                }
+                Keyword::Natural => {
+                    return self.keywords(&[
+                        Keyword::Join,
+                        Keyword::Inner,
+                        Keyword::Left,
+                        Keyword::Right,
+                        Keyword::Full,
+                    ])
+                }
+                Keyword::Is => return self.keywords(&[Keyword::Not, Keyword::Null]),
+                Keyword::Not if prev2.is_some_and(|t| t.is_keyword(Keyword::Is)) => {
+                    return self.keywords(&[Keyword::Null])
+                }
+                Keyword::Not if prev2.is_some_and(|t| is_operand(t)) => {
+                    return self.keywords(&[Keyword::In, Keyword::Like, Keyword::Between])
+                }
+                Keyword::Set if clause == Clause::Set => return self.update_target(context),
+                Keyword::Select => {
+                    self.keywords(&[Keyword::Distinct, Keyword::All]);
+                    return self.expression(clause);
+                }
+                Keyword::Values | Keyword::As => return,
+                Keyword::Distinct
+                | Keyword::All
+                | Keyword::Where
+                | Keyword::On
+                | Keyword::Having
+                | Keyword::And
+                | Keyword::Or
+                | Keyword::Not
+                | Keyword::When
+                | Keyword::Then
+                | Keyword::Else
+                | Keyword::By
+                | Keyword::Set => return self.expression(clause),
+                _ => {}
+            }
+        }
+
+        // after an operand
+        let table_position = prev2.is_some_and(|t| {
+            t.is_keyword(Keyword::From)
+                || t.is_keyword(Keyword::Join)
+                || t.is_keyword(Keyword::Into)
+                || t.is_keyword(Keyword::Update)
+                || t.is_operator(",") && clause == Clause::From
+        });
+        if table_position && first(Keyword::Update) {
+            return self.keywords(&[Keyword::Set]);
+        }
+        if table_position && first(Keyword::Insert) {
+            return self.keywords(&[Keyword::Values, Keyword::Select, Keyword::Default]);
+        }
+        if table_position && first(Keyword::Delete) {
+            return self.keywords(&[Keyword::Where]);
+        }
+        match clause {
+            Clause::Select => self.keywords(&[Keyword::From, Keyword::As]),
+            Clause::From => {
+                self.keywords(&[Keyword::Where]);
+                // ON only completes a join which has none yet
+                let joined = context
+                    .iter()
+                    .rev()
+                    .take_while(|t| !t.is_keyword(Keyword::On))
+                    .any(|t| t.is_keyword(Keyword::Join));
+                if joined {
+                    self.keywords(&[Keyword::On]);
+                }
+                self.keywords(JOINS);
+                self.keywords(&[Keyword::Group, Keyword::Order, Keyword::Having]);
+                self.keywords(SET_OPERATORS);
+                if table_position {
+                    self.keywords(&[Keyword::As]);
+                }
+            }
+            Clause::Condition => {
+                self.keywords(&[
+                    Keyword::And,
+                    Keyword::Or,
+                    Keyword::Is,
+                    Keyword::Not,
+                    Keyword::In,
+                    Keyword::Like,
+                    Keyword::Between,
+                ]);
+                if context.iter().any(|t| t.is_keyword(Keyword::On)) {
+                    self.keywords(&[Keyword::Where]);
+                    self.keywords(JOINS);
+                }
+                self.keywords(&[Keyword::Group, Keyword::Order, Keyword::Having]);
+                self.keywords(SET_OPERATORS);
+            }
+            Clause::GroupBy => {
+                self.keywords(&[Keyword::Having, Keyword::Order]);
+                self.keywords(SET_OPERATORS);
+            }
+            Clause::OrderBy => self.keywords(&[Keyword::Asc, Keyword::Desc]),
+            Clause::Set
+                if !prev2.is_some_and(|t| t.is_keyword(Keyword::Set) || t.is_operator(",")) =>
+            {
+                self.keywords(&[Keyword::Where])
+            }
+            Clause::Set | Clause::Other => {}
+        }
+    }
+
+    /// Columns of the table after UPDATE
+    fn update_target(&mut self, context: &[&Token]) {
+        let target = context
+            .get(1)
+            .and_then(|t| QualifiedName::parse(t.text).ok())
+            .and_then(|name| self.catalog_name(&name));
+        if let Some(target) = target {
+            self.columns(target);
+        }
+    }
+
+    fn catalog_name(&self, name: &QualifiedName) -> Option<&'a QualifiedName> {
+        self.catalog
+            .names()
+            .into_iter()
+            .find(|known| known.object() == name.object())
+    }
+
+    /// Relation of the catalog named by a table or correlation name
+    fn relation(&self, qualifier: &Token, statement: &[Token]) -> Option<&'a QualifiedName> {
+        let name = QualifiedName::parse(qualifier.text).ok()?;
+        if let Some(relation) = self.catalog_name(&name) {
+            return Some(relation);
+        }
+        (1..statement.len())
+            .filter(|&i| statement[i].text == qualifier.text)
+            .find_map(|i| {
+                let table = match &statement[i - 1] {
+                    t if t.is_keyword(Keyword::As) && i >= 2 => &statement[i - 2],
+                    t => t,
+                };
+                self.catalog_name(&QualifiedName::parse(table.text).ok()?)
+            })
+    }
+}
+
+/// Relations of the catalog which the statement names
+fn scope<'a>(statement: &[Token], catalog: &'a Catalog) -> Vec<&'a QualifiedName> {
+    let mut scope = vec![];
+    for token in statement.iter().filter(|t| t.is_name()) {
+        let name = match Identifier::parse(token.text) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        for relation in catalog.names() {
+            if *relation.object() == name && !scope.contains(&relation) {
+                scope.push(relation);
+            }
+        }
+    }
+    scope
+}
+
+/// Last clause at the top level of the context
+fn clause(context: &[&Token]) -> Clause {
+    let mut clause = Clause::Other;
+    let mut depth = 0;
+    for token in context {
+        if token.is_operator("(") {
+            depth += 1;
+        } else if token.is_operator(")") {
+            depth -= 1;
+        }
+        if depth != 0 {
+            continue;
+        }
+        clause = match token.kind {
+            TokenKind::Keyword(Keyword::Select) => Clause::Select,
+            TokenKind::Keyword(Keyword::From) | TokenKind::Keyword(Keyword::Join) => Clause::From,
+            TokenKind::Keyword(Keyword::Where)
+            | TokenKind::Keyword(Keyword::Having)
+            | TokenKind::Keyword(Keyword::On) => Clause::Condition,
+            TokenKind::Keyword(Keyword::By) if clause == Clause::OrderBy => Clause::OrderBy,
+            TokenKind::Keyword(Keyword::By) => Clause::GroupBy,
+            TokenKind::Keyword(Keyword::Order) => Clause::OrderBy,
+            TokenKind::Keyword(Keyword::Set) if context[0].is_keyword(Keyword::Update) => {
+                Clause::Set
+            }
+            TokenKind::Keyword(Keyword::Values) | TokenKind::Keyword(Keyword::Into) => {
+                Clause::Other
+            }
+            _ => clause,
+        };
+    }
+    clause
+}
+
+fn is_word(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::Identifier | TokenKind::Keyword(_))
+}
+
+fn is_operand(token: &Token) -> bool {
+    token.is_name()
+        || token.kind.is_literal()
+        || token.kind == TokenKind::Parameter
+        || token.is_operator(")")
+}
+
+#[cfg(test)]
+fn labels(src: &str) -> Vec<String> {
+    let catalog = Catalog::new()
+        .table("orders", &["id", "amount", "customer"])
+        .unwrap()
+        .table("customer", &["id", "name"])
+        .unwrap()
+        .view("big", "SELECT id, amount AS total FROM orders")
+        .unwrap();
+    let offset = src.find('|').unwrap();
+    let src = src.replacen('|', "", 1);
+    complete(&src, offset, &catalog)
+        .into_iter()
+        .map(|c| c.label)
+        .collect()
+}
+
+#[test]
+fn complete_names_from_catalog() {
+    assert_eq!(labels("SELECT * FROM |"), vec!["orders", "customer", "big"]);
+    assert_eq!(labels("SELECT * FROM orders, c|"), vec!["customer"]);
+    assert_eq!(
+        labels("SELECT o.| FROM orders AS o"),
+        vec!["id", "amount", "customer"]
+    );
+    assert_eq!(labels("SELECT b.t| FROM big b"), vec!["total"]);
+    assert_eq!(
+        labels("SELECT customer.| FROM customer"),
+        vec!["id", "name"]
+    );
+    assert_eq!(
+        labels("UPDATE orders SET |"),
+        vec!["id", "amount", "customer"]
+    );
+
+    let completions = {
+        let catalog = Catalog::new().table("t", &["a"]).unwrap();
+        complete("SELECT  FROM t", 7, &catalog)
+    };
+    assert_eq!(completions[2].label, "a");
+    assert_eq!(completions[2].kind, CompletionKind::Column);
+    assert_eq!(completions[2].detail.as_deref(), Some("t"));
+}
+
+#[test]
+fn complete_keywords_valid_next() {
+    assert_eq!(labels("|")[..3], ["SELECT", "INSERT", "UPDATE"]);
+    assert_eq!(labels("sel|"), vec!["SELECT"]);
+    assert_eq!(labels("SELECT * FROM orders WH|"), vec!["WHERE"]);
+    assert_eq!(
+        labels("SELECT * FROM orders o JOIN customer c |")[..3],
+        ["WHERE", "ON", "JOIN"]
+    );
+    assert_eq!(labels("SELECT a FROM t ORDER |"), vec!["BY"]);
+    assert_eq!(labels("SELECT a FROM t ORDER BY a |"), vec!["ASC", "DESC"]);
+    assert_eq!(
+        labels("SELECT * FROM orders WHERE amount IS |"),
+        vec!["NOT", "NULL"]
+    );
+    assert_eq!(
+        labels("SELECT * FROM orders WHERE amount |")[..3],
+        ["AND", "OR", "IS"]
+    );
+    assert_eq!(
+        labels("SELECT * FROM orders WHERE a|"),
+        vec!["amount", "AVG"]
+    );
+    assert_eq!(labels("SELECT * FROM orders WHERE (|")[0], "SELECT");
+    assert_eq!(labels("DELETE FROM orders |"), vec!["WHERE"]);
+    assert_eq!(labels("SELECT 'FR|'"), Vec::<String>::new());
+    assert_eq!(labels("SELECT 1; DROP |")[0], "TABLE");
+}
@@ -0,0 +1,982 @@
+use crate::ast::action::Action;
+use crate::ast::column_name_list::ColumnNameList;
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, LexError, Token, TokenKind};
+use crate::ast::paren::{LPAREN, RPAREN};
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::select_sublist::SelectSublist;
+use crate::ast::set_quantifier::SetQuantifier;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::ops::Range;
+use std::vec::IntoIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment,
+}
+
+/// Whitespace or comment around a token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// Token with its trivia.
+/// `trailing` runs to the end of the line, the newline included;
+/// everything after that is `leading` of the next token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken {
+    pub kind: TokenKind,
+    pub text: String,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+}
+
+impl CstToken {
+    /// Replace the token text, its trivia stays
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+
+    pub fn is_operator(&self, operator: &str) -> bool {
+        self.kind == TokenKind::Operator && self.text == operator
+    }
+
+    pub fn is_keyword(&self, keyword: Keyword) -> bool {
+        self.kind == TokenKind::Keyword(keyword)
+    }
+
+    /// Comments of the leading and trailing trivia
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.leading
+            .iter()
+            .chain(&self.trailing)
+            .filter(|trivia| trivia.kind == TriviaKind::Comment)
+            .map(|trivia| trivia.text.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CstKind {
+    Script,
+    /// tokens up to and including `;`
+    Statement,
+    /// `(` ... `)`
+    Group,
+    /// tokens which fit nowhere, with the reason
+    Error(&'static str),
+    /// empty node where something is expected
+    Missing(&'static str),
+}
+
+/// Problem found by the tolerant parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: &'static str,
+    pub span: Range<usize>,
+}
+
+impl From<LexError> for SyntaxError {
+    fn from(error: LexError) -> Self {
+        SyntaxError {
+            message: error.message,
+            span: error.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstElement {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+impl CstElement {
+    fn first_token_mut(&mut self) -> Option<&mut CstToken> {
+        match self {
+            CstElement::Token(token) => Some(token),
+            CstElement::Node(node) => node.children.first_mut()?.first_token_mut(),
+        }
+    }
+
+    fn last_token_mut(&mut self) -> Option<&mut CstToken> {
+        match self {
+            CstElement::Token(token) => Some(token),
+            CstElement::Node(node) => node.children.last_mut()?.last_token_mut(),
+        }
+    }
+
+    fn is_operator(&self, operator: &str) -> bool {
+        matches!(self, CstElement::Token(token) if token.is_operator(operator))
+    }
+
+    fn write(&self, out: &mut String) {
+        match self {
+            CstElement::Token(token) => {
+                for trivia in &token.leading {
+                    out.push_str(&trivia.text);
+                }
+                out.push_str(&token.text);
+                for trivia in &token.trailing {
+                    out.push_str(&trivia.text);
+                }
+            }
+            CstElement::Node(node) => {
+                for child in &node.children {
+                    child.write(out);
+                }
+            }
+        }
+    }
+}
+
+/// Lossless concrete syntax tree.
+/// Printing it gives back the source byte for byte,
+/// the typed nodes are read from it on demand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode {
+    pub kind: CstKind,
+    pub children: Vec<CstElement>,
+    /// trivia after the last token, only on the script
+    pub trailing: Vec<Trivia>,
+}
+
+impl CstNode {
+    /// Script in strict SQL-92
+    pub fn parse(src: &str) -> Result<CstNode, LexError> {
+        CstNode::parse_with(src, dialect::DEFAULT)
+    }
+
+    pub fn parse_with(src: &str, dialect: &dyn Dialect) -> Result<CstNode, LexError> {
+        let (mut tokens, trailing) = cst_tokens(src, dialect)?;
+        let mut children = vec![];
+        let mut statement = vec![];
+        while let Some(token) = tokens.next() {
+            let end = token.is_operator(";");
+            statement.push(group_or_token(token, &mut tokens));
+            if end {
+                children.push(CstElement::Node(CstNode::new(
+                    CstKind::Statement,
+                    statement,
+                )));
+                statement = vec![];
+            }
+        }
+        if !statement.is_empty() {
+            children.push(CstElement::Node(CstNode::new(
+                CstKind::Statement,
+                statement,
+            )));
+        }
+        Ok(CstNode {
+            kind: CstKind::Script,
+            children,
+            trailing,
+        })
+    }
+
+    /// Script as far as it goes, for editors.
+    /// Never fails: broken input ends up in `Error` nodes, expected
+    /// but absent parts are `Missing` nodes, printing the tree still
+    /// gives back the source.
+    pub fn parse_tolerant(src: &str, dialect: &dyn Dialect) -> (CstNode, Vec<SyntaxError>) {
+        let (tokens, spans, trailing, mut lex_errors) = lex(src, dialect);
+        let mut elements = vec![];
+        for (token, span) in tokens.into_iter().zip(spans) {
+            let element = CstElement::Token(token);
+            elements.push(match lex_errors.iter().position(|e| e.span == span) {
+                Some(i) => error_node(lex_errors.remove(i).message, vec![element]),
+                None => element,
+            });
+        }
+
+        let mut rest = elements.into_iter().peekable();
+        let mut children = vec![];
+        let mut statement = vec![];
+        while let Some(element) = rest.next() {
+            let end = element.is_operator(";");
+            statement.push(if element.is_operator(RPAREN) {
+                error_node("unmatched )", vec![element])
+            } else {
+                tolerant_group(element, &mut rest)
+            });
+            if end {
+                children.push(tolerant_statement(std::mem::take(&mut statement)));
+            }
+        }
+        if !statement.is_empty() {
+            children.push(tolerant_statement(statement));
+        }
+
+        let script = CstNode {
+            kind: CstKind::Script,
+            children,
+            trailing,
+        };
+        // what is left are errors inside comments
+        let mut errors = script.errors();
+        errors.extend(lex_errors.into_iter().map(SyntaxError::from));
+        errors.sort_by_key(|e| e.span.start);
+        (script, errors)
+    }
+
+    /// `Error` and `Missing` nodes with their byte ranges,
+    /// a missing node has an empty range right after the token before
+    pub fn errors(&self) -> Vec<SyntaxError> {
+        let mut errors = vec![];
+        self.collect_errors(&mut 0, &mut 0, &mut errors);
+        errors
+    }
+
+    /// Range of the token text, trivia around it excluded.
+    /// `pos` is where the walk is, `end` where the last token text ended.
+    fn collect_errors(
+        &self,
+        pos: &mut usize,
+        end: &mut usize,
+        errors: &mut Vec<SyntaxError>,
+    ) -> Option<Range<usize>> {
+        let mut range: Option<Range<usize>> = None;
+        for child in &self.children {
+            let child_range = match child {
+                CstElement::Node(node) => node.collect_errors(pos, end, errors),
+                CstElement::Token(token) => {
+                    *pos += token.leading.iter().map(|t| t.text.len()).sum::<usize>();
+                    let start = *pos;
+                    *pos += token.text.len();
+                    *end = *pos;
+                    *pos += token.trailing.iter().map(|t| t.text.len()).sum::<usize>();
+                    Some(start..*end)
+                }
+            };
+            if let Some(child_range) = child_range {
+                range = Some(match range {
+                    Some(range) => range.start..child_range.end,
+                    None => child_range,
+                });
+            }
+        }
+        match self.kind {
+            CstKind::Error(message) => errors.push(SyntaxError {
+                message,
+                span: range.clone().unwrap_or(*end..*end),
+            }),
+            CstKind::Missing(message) => errors.push(SyntaxError {
+                message,
+                span: *end..*end,
+            }),
+            _ => {}
+        }
+        range
+    }
+
+    /// Tokens and groups of a fragment, without statements
+    pub fn parse_fragment(src: &str, dialect: &dyn Dialect) -> Result<Vec<CstElement>, LexError> {
+        let (mut tokens, trailing) = cst_tokens(src, dialect)?;
+        let mut elements = vec![];
+        while let Some(token) = tokens.next() {
+            elements.push(group_or_token(token, &mut tokens));
+        }
+        if let Some(last) = elements.last_mut().and_then(CstElement::last_token_mut) {
+            last.trailing.extend(trailing);
+        }
+        Ok(elements)
+    }
+
+    fn new(kind: CstKind, children: Vec<CstElement>) -> Self {
+        CstNode {
+            kind,
+            children,
+            trailing: vec![],
+        }
+    }
+
+    pub fn statements(&self) -> Vec<&CstNode> {
+        self.nodes(CstKind::Statement)
+    }
+
+    fn nodes(&self, kind: CstKind) -> Vec<&CstNode> {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                CstElement::Node(node) if node.kind == kind => Some(node),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All tokens in document order
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        let mut tokens = vec![];
+        for child in &self.children {
+            match child {
+                CstElement::Token(token) => tokens.push(token),
+                CstElement::Node(node) => tokens.extend(node.tokens()),
+            }
+        }
+        tokens
+    }
+
+    pub fn tokens_mut(&mut self) -> Vec<&mut CstToken> {
+        let mut tokens = vec![];
+        for child in &mut self.children {
+            match child {
+                CstElement::Token(token) => tokens.push(token),
+                CstElement::Node(node) => tokens.extend(node.tokens_mut()),
+            }
+        }
+        tokens
+    }
+
+    /// Replace the child at `index` by the parsed fragment.
+    /// The trivia around the old child moves onto the new one.
+    pub fn replace(
+        &mut self,
+        index: usize,
+        src: &str,
+        dialect: &dyn Dialect,
+    ) -> Result<(), LexError> {
+        let mut elements = CstNode::parse_fragment(src.trim(), dialect)?;
+        let mut old = self.children.remove(index);
+        if elements.is_empty() {
+            self.children.insert(index, old);
+            self.remove(index);
+            return Ok(());
+        }
+
+        if let (Some(old_first), Some(new_first)) =
+            (old.first_token_mut(), elements[0].first_token_mut())
+        {
+            let mut leading = std::mem::take(&mut old_first.leading);
+            leading.append(&mut new_first.leading);
+            new_first.leading = leading;
+        }
+        let last = elements.len() - 1;
+        if let (Some(old_last), Some(new_last)) =
+            (old.last_token_mut(), elements[last].last_token_mut())
+        {
+            new_last.trailing = std::mem::take(&mut old_last.trailing);
+        }
+
+        for (i, element) in elements.into_iter().enumerate() {
+            self.children.insert(index + i, element);
+        }
+        Ok(())
+    }
+
+    /// Remove the child at `index`, its comments stay in the tree:
+    /// before the next child, or after the previous one when it was last
+    pub fn remove(&mut self, index: usize) -> Option<CstElement> {
+        if index >= self.children.len() {
+            return None;
+        }
+        let mut removed = self.children.remove(index);
+
+        let mut comments = vec![];
+        if let Some(first) = removed.first_token_mut() {
+            comments.append(&mut first.leading);
+        }
+        if let Some(last) = removed.last_token_mut() {
+            // the line break after a line comment stays with it
+            let trailing = std::mem::take(&mut last.trailing);
+            let from = trailing
+                .iter()
+                .position(|trivia| trivia.kind == TriviaKind::Comment)
+                .unwrap_or(trailing.len());
+            comments.extend(trailing.into_iter().skip(from));
+        }
+
+        if let Some(next) = self
+            .children
+            .get_mut(index)
+            .and_then(CstElement::first_token_mut)
+        {
+            comments.append(&mut next.leading);
+            next.leading = comments;
+        } else if let Some(prev) = index
+            .checked_sub(1)
+            .and_then(|prev| self.children.get_mut(prev))
+            .and_then(CstElement::last_token_mut)
+        {
+            prev.trailing.append(&mut comments);
+        } else {
+            self.trailing.splice(0..0, comments).for_each(drop);
+        }
+        Some(removed)
+    }
+
+    /// Tokens of this node, not of nested groups
+    fn own_tokens(&self) -> impl Iterator<Item = &CstToken> {
+        self.children.iter().filter_map(|child| match child {
+            CstElement::Token(token) => Some(token),
+            CstElement::Node(_) => None,
+        })
+    }
+
+    /// DISTINCT or ALL right after SELECT
+    pub fn set_quantifier(&self) -> Option<SetQuantifier> {
+        let mut tokens = self.own_tokens();
+        if !tokens.next()?.is_keyword(Keyword::Select) {
+            return None;
+        }
+        match tokens.next()?.kind {
+            TokenKind::Keyword(Keyword::Distinct) => Some(SetQuantifier::DISTINCT),
+            TokenKind::Keyword(Keyword::All) => Some(SetQuantifier::ALL),
+            _ => None,
+        }
+    }
+
+    /// `*` or column references up to FROM, `None` for anything else
+    pub fn select_list(&self) -> Option<SelectList> {
+        let mut children = self.children.iter().skip_while(|child| {
+            matches!(child, CstElement::Token(t) if t.is_keyword(Keyword::Select)
+                || t.is_keyword(Keyword::Distinct)
+                || t.is_keyword(Keyword::All))
+        });
+        let mut tokens = vec![];
+        for child in &mut children {
+            match child {
+                CstElement::Token(t) if t.is_keyword(Keyword::From) || t.is_operator(";") => break,
+                CstElement::Token(t) => tokens.push(t),
+                CstElement::Node(_) => return None,
+            }
+        }
+        if let [token] = tokens.as_slice() {
+            if token.is_operator("*") {
+                return Some(SelectList::ASTERISK);
+            }
+        }
+        let sublists = tokens
+            .split(|t| t.is_operator(","))
+            .map(|column| {
+                let names = column.iter().step_by(2).map(|t| match t.kind {
+                    TokenKind::Identifier | TokenKind::DelimitedIdentifier => {
+                        Identifier::parse(&t.text).ok()
+                    }
+                    // non-reserved key words are names
+                    TokenKind::Keyword(keyword) if !keyword.is_reserved() => {
+                        Identifier::parse(&t.text).ok()
+                    }
+                    _ => None,
+                });
+                let dotted = column.iter().skip(1).step_by(2).all(|t| t.is_operator("."));
+                match names.collect::<Option<Vec<_>>>() {
+                    Some(parts) if dotted && column.len() % 2 == 1 => {
+                        Some(SelectSublist(QualifiedName::new(parts).ok()?))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(SelectList::SUBLISTS(sublists))
+    }
+
+    pub fn select_statement_single_row(&self) -> Option<SelectStatementSingleRow> {
+        Some((Action::SELECT, self.set_quantifier(), self.select_list()?))
+    }
+
+    /// First privilege of a GRANT or REVOKE statement
+    pub fn action(&self) -> Option<Action> {
+        let mut children = self.children.iter().skip_while(|child| {
+            !matches!(child, CstElement::Token(t)
+                if t.is_keyword(Keyword::Grant) || t.is_keyword(Keyword::Revoke))
+        });
+        children.next()?;
+        let privilege = match children.next()? {
+            CstElement::Token(token) => token,
+            CstElement::Node(_) => return None,
+        };
+        let mut columns = || -> Option<ColumnNameList> {
+            match children.next() {
+                Some(CstElement::Node(group)) if group.kind == CstKind::Group => group
+                    .own_tokens()
+                    .filter(|t| !t.is_operator(LPAREN) && !t.is_operator(RPAREN))
+                    .filter(|t| !t.is_operator(","))
+                    .map(|t| Identifier::parse(&t.text).ok())
+                    .collect(),
+                _ => Some(vec![]),
+            }
+        };
+
+        match privilege.kind {
+            TokenKind::Keyword(Keyword::Select) => Some(Action::SELECT),
+            TokenKind::Keyword(Keyword::Delete) => Some(Action::DELETE),
+            TokenKind::Keyword(Keyword::Usage) => Some(Action::USAGE),
+            TokenKind::Keyword(Keyword::Insert) => Some(Action::INSERT(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            TokenKind::Keyword(Keyword::Update) => Some(Action::UPDATE(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            TokenKind::Keyword(Keyword::References) => Some(Action::REFERENCES(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CstNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        for child in &self.children {
+            child.write(&mut out);
+        }
+        for trivia in &self.trailing {
+            out.push_str(&trivia.text);
+        }
+        f.write_str(&out)
+    }
+}
+
+fn trivia(token: &Token) -> Trivia {
+    let kind = match token.kind {
+        TokenKind::Comment => TriviaKind::Comment,
+        _ => TriviaKind::Whitespace,
+    };
+    Trivia {
+        kind,
+        text: token.text.to_string(),
+    }
+}
+
+/// Tokens with trivia attached, and the trivia after the last one
+fn cst_tokens(
+    src: &str,
+    dialect: &dyn Dialect,
+) -> Result<(Peekable<IntoIter<CstToken>>, Vec<Trivia>), LexError> {
+    let (tokens, _, trailing, errors) = lex(src, dialect);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    Ok((tokens.into_iter().peekable(), trailing))
+}
+
+/// Tokens with trivia attached and the span of their text,
+/// the trivia after the last one and the lexer errors
+fn lex(
+    src: &str,
+    dialect: &dyn Dialect,
+) -> (Vec<CstToken>, Vec<Range<usize>>, Vec<Trivia>, Vec<LexError>) {
+    let (tokens, errors) = tokenize_with(src, dialect);
+    let mut out: Vec<CstToken> = vec![];
+    let mut spans = vec![];
+    let mut leading = vec![];
+    let mut same_line = false;
+    for token in &tokens {
+        if !token.kind.is_trivia() {
+            out.push(CstToken {
+                kind: token.kind,
+                text: token.text.to_string(),
+                leading: std::mem::take(&mut leading),
+                trailing: vec![],
+            });
+            spans.push(token.span.clone());
+            same_line = true;
+            continue;
+        }
+        let last = match out.last_mut() {
+            Some(last) if same_line => last,
+            _ => {
+                leading.push(trivia(token));
+                continue;
+            }
+        };
+        match token.text.find('\n') {
+            Some(newline) if token.kind == TokenKind::Whitespace => {
+                let (line, rest) = token.text.split_at(newline + 1);
+                last.trailing.push(Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: line.to_string(),
+                });
+                if !rest.is_empty() {
+                    leading.push(Trivia {
+                        kind: TriviaKind::Whitespace,
+                        text: rest.to_string(),
+                    });
+                }
+                same_line = false;
+            }
+            _ => last.trailing.push(trivia(token)),
+        }
+    }
+    (out, spans, leading, errors)
+}
+
+fn group_or_token(token: CstToken, rest: &mut Peekable<IntoIter<CstToken>>) -> CstElement {
+    if !token.is_operator(LPAREN) {
+        return CstElement::Token(token);
+    }
+    let mut children = vec![CstElement::Token(token)];
+    while let Some(next) = rest.next() {
+        let close = next.is_operator(RPAREN);
+        children.push(group_or_token(next, rest));
+        if close {
+            break;
+        }
+    }
+    CstElement::Node(CstNode::new(CstKind::Group, children))
+}
+
+fn error_node(message: &'static str, children: Vec<CstElement>) -> CstElement {
+    CstElement::Node(CstNode::new(CstKind::Error(message), children))
+}
+
+fn missing_node(message: &'static str) -> CstElement {
+    CstElement::Node(CstNode::new(CstKind::Missing(message), vec![]))
+}
+
+/// Like `group_or_token`, a group left open ends before `;`
+fn tolerant_group(element: CstElement, rest: &mut Peekable<IntoIter<CstElement>>) -> CstElement {
+    if !element.is_operator(LPAREN) {
+        return element;
+    }
+    let mut children = vec![element];
+    loop {
+        match rest.peek() {
+            Some(next) if !next.is_operator(";") => {
+                let next = rest.next().unwrap();
+                let close = next.is_operator(RPAREN);
+                children.push(tolerant_group(next, rest));
+                if close {
+                    break;
+                }
+            }
+            _ => {
+                children.push(missing_node("expected )"));
+                break;
+            }
+        }
+    }
+    insert_missing(&mut children);
+    CstElement::Node(CstNode::new(CstKind::Group, children))
+}
+
+const STATEMENT_KEYWORDS: &[Keyword] = &[
+    Keyword::Select,
+    Keyword::Insert,
+    Keyword::Update,
+    Keyword::Delete,
+    Keyword::Create,
+    Keyword::Drop,
+    Keyword::Alter,
+    Keyword::Grant,
+    Keyword::Revoke,
+    Keyword::Commit,
+    Keyword::Rollback,
+    Keyword::Set,
+    Keyword::Declare,
+    Keyword::Open,
+    Keyword::Close,
+    Keyword::Fetch,
+];
+
+/// Keywords which start a clause, nothing before them is their operand
+const CLAUSE_KEYWORDS: &[Keyword] = &[
+    Keyword::From,
+    Keyword::Where,
+    Keyword::Group,
+    Keyword::Order,
+    Keyword::Having,
+    Keyword::Union,
+    Keyword::Except,
+    Keyword::Intersect,
+    Keyword::Set,
+    Keyword::Values,
+    Keyword::Into,
+    Keyword::And,
+    Keyword::Or,
+    Keyword::Join,
+    Keyword::Inner,
+    Keyword::Left,
+    Keyword::Right,
+    Keyword::Full,
+    Keyword::Cross,
+    Keyword::Natural,
+];
+
+fn starts_statement(element: &CstElement) -> bool {
+    match element {
+        CstElement::Token(token) => STATEMENT_KEYWORDS
+            .iter()
+            .any(|&keyword| token.is_keyword(keyword)),
+        CstElement::Node(node) => node.kind == CstKind::Group,
+    }
+}
+
+/// What has to follow the token. `after` is the element before it;
+/// in a privilege list (`GRANT SELECT, UPDATE ON ...`) the privileges
+/// take no operand, nor does the UPDATE of `FOR UPDATE` or `ON UPDATE`.
+fn expected_after(
+    token: &CstToken,
+    after: Option<&CstElement>,
+    privileges: bool,
+) -> Option<&'static str> {
+    let after_keyword =
+        |keyword| matches!(after, Some(CstElement::Token(t)) if t.is_keyword(keyword));
+    let privilege = matches!(
+        token.kind,
+        TokenKind::Keyword(Keyword::Select)
+            | TokenKind::Keyword(Keyword::Update)
+            | TokenKind::Keyword(Keyword::All)
+    );
+    if privileges && privilege
+        || token.is_keyword(Keyword::Update)
+            && (after_keyword(Keyword::For) || after_keyword(Keyword::On))
+    {
+        return None;
+    }
+    match token.kind {
+        TokenKind::Keyword(Keyword::Select)
+        | TokenKind::Keyword(Keyword::Distinct)
+        | TokenKind::Keyword(Keyword::All) => Some("expected select list"),
+        TokenKind::Keyword(Keyword::From)
+        | TokenKind::Keyword(Keyword::Into)
+        | TokenKind::Keyword(Keyword::Join)
+        | TokenKind::Keyword(Keyword::Table)
+        | TokenKind::Keyword(Keyword::View)
+        | TokenKind::Keyword(Keyword::Update) => Some("expected table name"),
+        TokenKind::Keyword(Keyword::Where)
+        | TokenKind::Keyword(Keyword::Having)
+        | TokenKind::Keyword(Keyword::On)
+        | TokenKind::Keyword(Keyword::And)
+        | TokenKind::Keyword(Keyword::Or)
+        | TokenKind::Keyword(Keyword::Not) => Some("expected condition"),
+        TokenKind::Keyword(Keyword::By) => Some("expected expression"),
+        TokenKind::Operator => match token.text.as_str() {
+            "." => Some("expected name"),
+            "," | "=" | "<>" | "<" | ">" | "<=" | ">=" | "+" | "-" | "/" | "||" => {
+                Some("expected expression")
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the element can't be what the token before expects
+fn ends_operand(element: Option<&CstElement>) -> bool {
+    match element {
+        None => true,
+        Some(CstElement::Token(token)) => {
+            token.is_operator(";")
+                || token.is_operator(RPAREN)
+                || token.is_operator(",")
+                || CLAUSE_KEYWORDS
+                    .iter()
+                    .any(|&keyword| token.is_keyword(keyword))
+        }
+        Some(CstElement::Node(node)) => matches!(node.kind, CstKind::Missing(_)),
+    }
+}
+
+/// `Missing` nodes after tokens left without their operand
+fn insert_missing(children: &mut Vec<CstElement>) {
+    let mut privileges = false;
+    let mut i = 0;
+    while i < children.len() {
+        if let CstElement::Token(token) = &children[i] {
+            if token.is_keyword(Keyword::Grant) || token.is_keyword(Keyword::Revoke) {
+                privileges = true;
+            } else if token.is_keyword(Keyword::On) {
+                privileges = false;
+            }
+            let after = i.checked_sub(1).map(|prev| &children[prev]);
+            if let Some(message) = expected_after(token, after, privileges) {
+                if ends_operand(children.get(i + 1)) {
+                    children.insert(i + 1, missing_node(message));
+                    i += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Statement whose leading tokens start no statement go in an `Error` node
+fn tolerant_statement(mut children: Vec<CstElement>) -> CstElement {
+    let first = &children[0];
+    if !first.is_operator(";") && !starts_statement(first) {
+        let end = children
+            .iter()
+            .position(|c| starts_statement(c) || c.is_operator(";"))
+            .unwrap_or(children.len());
+        let skipped: Vec<CstElement> = children.drain(..end).collect();
+        let reported = skipped
+            .iter()
+            .all(|c| matches!(c, CstElement::Node(node) if matches!(node.kind, CstKind::Error(_))));
+        if reported {
+            children.splice(0..0, skipped);
+        } else {
+            children.insert(0, error_node("expected a statement", skipped));
+        }
+    }
+    insert_missing(&mut children);
+    CstElement::Node(CstNode::new(CstKind::Statement, children))
+}
+
+#[test]
+fn cst_is_lossless() {
+    let src = "  -- header\nSELECT  a , /* x */ (b+1)\n\tFROM t ; -- done\n\nGRANT INSERT (a, \"B\") ON t TO u\n";
+    let cst = CstNode::parse(src).unwrap();
+    assert_eq!(cst.to_string(), src);
+    assert_eq!(cst.statements().len(), 2);
+
+    let select = cst.statements()[0];
+    let tokens = select.tokens();
+    assert_eq!(tokens[0].leading[1].text, "-- header");
+    assert_eq!(tokens[2].trailing[1].text, "/* x */");
+    assert_eq!(
+        tokens.last().unwrap().comments().collect::<Vec<_>>(),
+        vec!["-- done"]
+    );
+    assert!(
+        matches!(select.children[3], CstElement::Node(ref group) if group.kind == CstKind::Group)
+    );
+
+    let empty = CstNode::parse("  \n").unwrap();
+    assert_eq!(empty.to_string(), "  \n");
+    assert!(CstNode::parse("SELECT 'open").is_err());
+}
+
+#[test]
+fn cst_edits_keep_comments() {
+    let src = "SELECT a, -- first\n  b /* second */, c\nFROM t";
+    let mut cst = CstNode::parse(src).unwrap();
+    let statement = match &mut cst.children[0] {
+        CstElement::Node(node) => node,
+        _ => unreachable!(),
+    };
+
+    statement.replace(1, "alpha", dialect::DEFAULT).unwrap();
+    statement.tokens_mut()[3].set_text("beta");
+    assert_eq!(
+        cst.to_string(),
+        "SELECT alpha, -- first\n  beta /* second */, c\nFROM t"
+    );
+
+    let statement = match &mut cst.children[0] {
+        CstElement::Node(node) => node,
+        _ => unreachable!(),
+    };
+    statement.remove(3);
+    statement.remove(3);
+    assert_eq!(
+        cst.to_string(),
+        "SELECT alpha, -- first\n  /* second */c\nFROM t"
+    );
+
+    let mut cst = CstNode::parse("SELECT a FROM t -- keep me\n;\nSELECT b").unwrap();
+    let statement = match &mut cst.children[0] {
+        CstElement::Node(node) => node,
+        _ => unreachable!(),
+    };
+    statement.remove(4);
+    statement.remove(3);
+    assert_eq!(cst.to_string(), "SELECT a FROM -- keep me\nSELECT b");
+
+    let mut cst = CstNode::parse("SELECT a FROM t -- keep me").unwrap();
+    if let CstElement::Node(statement) = &mut cst.children[0] {
+        statement.remove(3);
+    }
+    assert_eq!(cst.to_string(), "SELECT a FROM -- keep me");
+}
+
+#[test]
+fn typed_views_over_cst() {
+    let cst =
+        CstNode::parse("SELECT DISTINCT * FROM t; GRANT UPDATE (a, \"B\") ON t TO u").unwrap();
+    let statements = cst.statements();
+
+    assert_eq!(
+        statements[0].select_statement_single_row(),
+        Some((
+            Action::SELECT,
+            Some(SetQuantifier::DISTINCT),
+            SelectList::ASTERISK
+        ))
+    );
+    assert_eq!(
+        statements[1].action(),
+        Some(Action::UPDATE(
+            LPAREN.to_string(),
+            vec![
+                Identifier::Regular("A".to_string()),
+                Identifier::Delimited("B".to_string())
+            ],
+            RPAREN.to_string(),
+        ))
+    );
+    assert_eq!(statements[1].select_list(), None);
+}
+
+#[test]
+fn tolerant_parse_of_broken_input() {
+    let src = "SELECT a, FROM t WHERE (b = ;\n@ UPDATE SET c = 1;\nSELECT x FROM /* open";
+    let (cst, errors) = CstNode::parse_tolerant(src, dialect::DEFAULT);
+    assert_eq!(cst.to_string(), src);
+    assert_eq!(cst.statements().len(), 3);
+
+    let messages: Vec<(&str, Range<usize>)> =
+        errors.iter().map(|e| (e.message, e.span.clone())).collect();
+    assert_eq!(
+        messages,
+        vec![
+            ("expected expression", 9..9),
+            ("expected expression", 27..27),
+            ("expected )", 27..27),
+            ("unexpected character", 30..31),
+            ("expected table name", 38..38),
+            ("expected table name", 63..63),
+            ("unterminated comment", 64..71),
+        ]
+    );
+
+    let select = cst.statements()[0];
+    assert!(matches!(
+        select.children[3],
+        CstElement::Node(CstNode {
+            kind: CstKind::Missing("expected expression"),
+            ..
+        })
+    ));
+    assert_eq!(select.tokens().last().unwrap().text, ";");
+
+    let (cst, errors) = CstNode::parse_tolerant("x y SELECT 1", dialect::DEFAULT);
+    assert_eq!(errors[0].message, "expected a statement");
+    assert_eq!(errors[0].span, 0..3);
+    assert_eq!(cst.statements()[0].set_quantifier(), None);
+}
+
+#[test]
+fn tolerant_parse_of_valid_input() {
+    let src = "SELECT DISTINCT * FROM t WHERE (a, b) = (1, 2);\nGRANT SELECT ON t TO u";
+    let (cst, errors) = CstNode::parse_tolerant(src, dialect::DEFAULT);
+    assert_eq!(errors, vec![]);
+    assert_eq!(cst, CstNode::parse(src).unwrap());
+
+    for src in [
+        "GRANT SELECT, UPDATE ON t TO u",
+        "REVOKE ALL, INSERT (a) ON t FROM u",
+        "DECLARE c CURSOR FOR SELECT a FROM t FOR UPDATE",
+        "DECLARE c CURSOR FOR SELECT a FROM t FOR UPDATE OF a",
+        "CREATE TABLE t (a INTEGER REFERENCES u (id) ON UPDATE SET NULL)",
+    ] {
+        let (_, errors) = CstNode::parse_tolerant(src, dialect::DEFAULT);
+        assert_eq!(errors, vec![], "{}", src);
+    }
+    let (_, errors) = CstNode::parse_tolerant("GRANT SELECT ON t TO u; SELECT", dialect::DEFAULT);
+    assert_eq!(errors[0].message, "expected select list");
+}
@@ -0,0 +1,540 @@
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize, Token, TokenCursor, TokenKind};
+use crate::ast::lineage::{lineage, Catalog};
+use crate::ast::unparse::ToSql;
+
+/// Column of CREATE TABLE, its constraints other than
+/// NOT NULL are kept as table constraints
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: Identifier,
+    /// data type or domain name, in canonical spelling
+    pub data_type: String,
+    pub default: Option<String>,
+    pub not_null: bool,
+}
+
+/// Table or domain constraint: `PRIMARY KEY (a)`, `CHECK (...)`, ...
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub name: Option<Identifier>,
+    pub definition: String,
+    /// table of a FOREIGN KEY
+    pub references: Option<QualifiedName>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDefinition {
+    pub name: QualifiedName,
+    pub columns: Vec<ColumnDefinition>,
+    pub constraints: Vec<Constraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewDefinition {
+    pub name: QualifiedName,
+    pub columns: Vec<Identifier>,
+    /// query with the check option, in canonical spelling
+    pub query: String,
+    /// tables and views the query reads
+    pub references: Vec<QualifiedName>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainDefinition {
+    pub name: QualifiedName,
+    pub data_type: String,
+    pub default: Option<String>,
+    pub constraints: Vec<Constraint>,
+}
+
+/// Objects created by a DDL script
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub tables: Vec<TableDefinition>,
+    pub views: Vec<ViewDefinition>,
+    pub domains: Vec<DomainDefinition>,
+}
+
+/// Read the CREATE TABLE, VIEW and DOMAIN statements of a script
+pub fn parse_schema(src: &str) -> Result<Schema, &'static str> {
+    let (tokens, errors) = tokenize(src);
+    if let Some(error) = errors.first() {
+        return Err(error.message);
+    }
+    let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+
+    let mut schema = Schema::default();
+    for statement in tokens.split(|t| t.is_operator(";")) {
+        let mut reader = Reader::new(statement);
+        if statement.is_empty() {
+            continue;
+        }
+        if !reader.cursor.eat(Keyword::Create) {
+            return Err("ddl: expected CREATE");
+        }
+        if reader.cursor.eat(Keyword::Table) {
+            schema.tables.push(reader.table()?);
+        } else if reader.cursor.eat(Keyword::View) {
+            schema.views.push(reader.view()?);
+        } else if reader.cursor.eat(Keyword::Domain) {
+            schema.domains.push(reader.domain()?);
+        } else {
+            return Err("ddl: expected TABLE, VIEW or DOMAIN");
+        }
+    }
+    Ok(schema)
+}
+
+impl ColumnDefinition {
+    pub fn to_sql(&self) -> String {
+        let mut sql = format!("{} {}", self.name.to_sql(), self.data_type);
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        if self.not_null {
+            sql.push_str(" NOT NULL");
+        }
+        sql
+    }
+}
+
+impl Constraint {
+    pub fn to_sql(&self) -> String {
+        match &self.name {
+            Some(name) => format!("CONSTRAINT {} {}", name.to_sql(), self.definition),
+            None => self.definition.clone(),
+        }
+    }
+}
+
+impl TableDefinition {
+    pub fn to_sql(&self) -> String {
+        let elements: Vec<String> = self
+            .columns
+            .iter()
+            .map(ColumnDefinition::to_sql)
+            .chain(self.constraints.iter().map(Constraint::to_sql))
+            .collect();
+        format!(
+            "CREATE TABLE {} (\n    {}\n)",
+            self.name.to_sql(),
+            elements.join(",\n    ")
+        )
+    }
+
+    pub fn column(&self, name: &Identifier) -> Option<&ColumnDefinition> {
+        self.columns.iter().find(|column| column.name == *name)
+    }
+}
+
+impl ViewDefinition {
+    pub fn to_sql(&self) -> String {
+        let columns = if self.columns.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<String> = self.columns.iter().map(ToSql::to_sql).collect();
+            format!(" ({})", names.join(", "))
+        };
+        format!(
+            "CREATE VIEW {}{} AS {}",
+            self.name.to_sql(),
+            columns,
+            self.query
+        )
+    }
+}
+
+impl DomainDefinition {
+    pub fn to_sql(&self) -> String {
+        let mut sql = format!("CREATE DOMAIN {} AS {}", self.name.to_sql(), self.data_type);
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        for constraint in &self.constraints {
+            sql.push(' ');
+            sql.push_str(&constraint.to_sql());
+        }
+        sql
+    }
+}
+
+/// Words which end a data type or a default
+const COLUMN_OPTIONS: &[Keyword] = &[
+    Keyword::Default,
+    Keyword::Not,
+    Keyword::Null,
+    Keyword::Constraint,
+    Keyword::Primary,
+    Keyword::Unique,
+    Keyword::References,
+    Keyword::Check,
+    Keyword::Collate,
+];
+
+struct Reader<'a> {
+    cursor: TokenCursor<'a, 'a>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(tokens: &'a [Token<'a>]) -> Self {
+        Reader {
+            cursor: TokenCursor::new(tokens),
+        }
+    }
+
+    fn expect_operator(&mut self, operator: &str) -> Result<(), &'static str> {
+        if self.cursor.eat_operator(operator) {
+            Ok(())
+        } else if operator == "(" {
+            Err("ddl: expected `(`")
+        } else {
+            Err("ddl: expected `)`")
+        }
+    }
+
+    fn identifier(&mut self) -> Result<Identifier, &'static str> {
+        let token = self
+            .cursor
+            .peek()
+            .filter(|t| t.is_name())
+            .ok_or("ddl: expected a name")?;
+        self.cursor.pos += 1;
+        Identifier::parse(token.text)
+    }
+
+    fn name(&mut self) -> Result<QualifiedName, &'static str> {
+        let mut parts = vec![self.identifier()?];
+        while self.cursor.eat_operator(".") {
+            parts.push(self.identifier()?);
+        }
+        QualifiedName::new(parts)
+    }
+
+    /// `(a, b)` when present
+    fn column_list(&mut self) -> Result<Vec<Identifier>, &'static str> {
+        let mut names = vec![];
+        if self.cursor.eat_operator("(") {
+            loop {
+                names.push(self.identifier()?);
+                if !self.cursor.eat_operator(",") {
+                    break;
+                }
+            }
+            self.expect_operator(")")?;
+        }
+        Ok(names)
+    }
+
+    fn until_option(&mut self) -> Result<String, &'static str> {
+        let tokens = self.cursor.until(|t| {
+            t.is_operator(",") || COLUMN_OPTIONS.iter().any(|&keyword| t.is_keyword(keyword))
+        });
+        if tokens.is_empty() {
+            return Err("ddl: expected a data type or value");
+        }
+        Ok(sql_text(tokens))
+    }
+
+    fn table(&mut self) -> Result<TableDefinition, &'static str> {
+        let name = self.name()?;
+        let mut columns = vec![];
+        let mut constraints = vec![];
+        self.expect_operator("(")?;
+        loop {
+            let table_constraint = [
+                Keyword::Constraint,
+                Keyword::Primary,
+                Keyword::Unique,
+                Keyword::Foreign,
+                Keyword::Check,
+            ]
+            .iter()
+            .any(|&keyword| self.cursor.at(keyword));
+            if table_constraint {
+                constraints.push(self.table_constraint()?);
+            } else {
+                let (column, mut more) = self.column()?;
+                columns.push(column);
+                constraints.append(&mut more);
+            }
+            if !self.cursor.eat_operator(",") {
+                break;
+            }
+        }
+        self.expect_operator(")")?;
+        self.end()?;
+        Ok(TableDefinition {
+            name,
+            columns,
+            constraints,
+        })
+    }
+
+    fn table_constraint(&mut self) -> Result<Constraint, &'static str> {
+        let name = if self.cursor.eat(Keyword::Constraint) {
+            Some(self.identifier()?)
+        } else {
+            None
+        };
+        let tokens = self.cursor.until(|t| t.is_operator(","));
+        if tokens.is_empty() {
+            return Err("ddl: expected a constraint");
+        }
+        Ok(Constraint {
+            name,
+            definition: sql_text(tokens),
+            references: referenced_table(tokens),
+        })
+    }
+
+    fn column(&mut self) -> Result<(ColumnDefinition, Vec<Constraint>), &'static str> {
+        let name = self.identifier()?;
+        let mut data_type = self.until_option()?;
+        let mut default = None;
+        let mut not_null = false;
+        let mut constraints = vec![];
+        let mut constraint_name = None;
+        let quoted = name.to_sql();
+
+        loop {
+            let (definition, references) = if self.cursor.eat(Keyword::Default) {
+                default = Some(self.until_option()?);
+                continue;
+            } else if self.cursor.eat(Keyword::Constraint) {
+                constraint_name = Some(self.identifier()?);
+                continue;
+            } else if self.cursor.eat(Keyword::Not) {
+                if !self.cursor.eat(Keyword::Null) {
+                    return Err("ddl: expected NULL after NOT");
+                }
+                not_null = true;
+                constraint_name = None;
+                continue;
+            } else if self.cursor.eat(Keyword::Null) {
+                continue;
+            } else if self.cursor.eat(Keyword::Collate) {
+                data_type = format!("{} COLLATE {}", data_type, self.name()?.to_sql());
+                continue;
+            } else if self.cursor.eat(Keyword::Primary) {
+                if !self.cursor.eat(Keyword::Key) {
+                    return Err("ddl: expected KEY after PRIMARY");
+                }
+                (format!("PRIMARY KEY ({})", quoted), None)
+            } else if self.cursor.eat(Keyword::Unique) {
+                (format!("UNIQUE ({})", quoted), None)
+            } else if self.cursor.at(Keyword::References) {
+                let (references, table) = self.references()?;
+                (
+                    format!("FOREIGN KEY ({}) {}", quoted, references),
+                    Some(table),
+                )
+            } else if self.cursor.eat(Keyword::Check) {
+                (self.check()?, None)
+            } else {
+                break;
+            };
+            constraints.push(Constraint {
+                name: constraint_name.take(),
+                definition,
+                references,
+            });
+        }
+
+        Ok((
+            ColumnDefinition {
+                name,
+                data_type,
+                default,
+                not_null,
+            },
+            constraints,
+        ))
+    }
+
+    /// `REFERENCES t [(columns)] [MATCH ...] [ON DELETE | UPDATE ...]`
+    fn references(&mut self) -> Result<(String, QualifiedName), &'static str> {
+        let start = self.cursor.pos;
+        self.cursor.pos += 1;
+        let table = self.name()?;
+        self.column_list()?;
+        loop {
+            if self.cursor.eat(Keyword::Match) {
+                let _ = self.cursor.eat(Keyword::Full) || self.cursor.eat(Keyword::Partial);
+            } else if self.cursor.eat(Keyword::On) {
+                let _ = self.cursor.eat(Keyword::Delete) || self.cursor.eat(Keyword::Update);
+                if self.cursor.eat(Keyword::Set) {
+                    let _ = self.cursor.eat(Keyword::Null) || self.cursor.eat(Keyword::Default);
+                } else if self.cursor.eat(Keyword::No) {
+                    self.cursor.eat(Keyword::Action);
+                } else {
+                    self.cursor.eat(Keyword::Cascade);
+                }
+            } else {
+                break;
+            }
+        }
+        Ok((sql_text(&self.cursor.tokens[start..self.cursor.pos]), table))
+    }
+
+    /// `CHECK (condition)`, after CHECK
+    fn check(&mut self) -> Result<String, &'static str> {
+        self.expect_operator("(")?;
+        let condition = sql_text(self.cursor.until(|_| false));
+        self.expect_operator(")")?;
+        Ok(format!("CHECK ({})", condition))
+    }
+
+    fn view(&mut self) -> Result<ViewDefinition, &'static str> {
+        let name = self.name()?;
+        let columns = self.column_list()?;
+        if !self.cursor.eat(Keyword::As) {
+            return Err("ddl: expected AS");
+        }
+        let tokens = &self.cursor.tokens[self.cursor.pos..];
+        if tokens.is_empty() {
+            return Err("ddl: expected a query");
+        }
+        self.cursor.pos = self.cursor.tokens.len();
+        let query = sql_text(tokens);
+        let references = lineage(&query, &Catalog::new())
+            .map_err(|error| error.message)?
+            .into_iter()
+            .flat_map(|statement| statement.reads)
+            .collect();
+        Ok(ViewDefinition {
+            name,
+            columns,
+            query,
+            references,
+        })
+    }
+
+    fn domain(&mut self) -> Result<DomainDefinition, &'static str> {
+        let name = self.name()?;
+        self.cursor.eat(Keyword::As);
+        let data_type = self.until_option()?;
+        let mut default = None;
+        let mut constraints = vec![];
+        loop {
+            if self.cursor.eat(Keyword::Default) {
+                default = Some(self.until_option()?);
+            } else if self.cursor.at(Keyword::Constraint) || self.cursor.at(Keyword::Check) {
+                let name = if self.cursor.eat(Keyword::Constraint) {
+                    Some(self.identifier()?)
+                } else {
+                    None
+                };
+                if !self.cursor.eat(Keyword::Check) {
+                    return Err("ddl: expected CHECK");
+                }
+                constraints.push(Constraint {
+                    name,
+                    definition: self.check()?,
+                    references: None,
+                });
+            } else {
+                break;
+            }
+        }
+        self.end()?;
+        Ok(DomainDefinition {
+            name,
+            data_type,
+            default,
+            constraints,
+        })
+    }
+
+    fn end(&self) -> Result<(), &'static str> {
+        if self.cursor.pos < self.cursor.tokens.len() {
+            return Err("ddl: unexpected text after the definition");
+        }
+        Ok(())
+    }
+}
+
+/// Table after REFERENCES
+fn referenced_table(tokens: &[Token]) -> Option<QualifiedName> {
+    let start = tokens
+        .iter()
+        .position(|t| t.is_keyword(Keyword::References))?;
+    Reader::new(&tokens[start + 1..]).name().ok()
+}
+
+/// Canonical spelling: reserved words in upper case, other key words
+/// as written since they may be names,
+/// single spaces, none inside parentheses or around dots
+pub fn sql_text(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    let mut prev: Option<&Token> = None;
+    for token in tokens {
+        let glued = token.is_operator(",")
+            || token.is_operator(")")
+            || token.is_operator(".")
+            || prev.is_none_or(|p| p.is_operator("(") || p.is_operator("."));
+        if !glued {
+            text.push(' ');
+        }
+        match token.kind {
+            TokenKind::Keyword(keyword) if keyword.is_reserved() => text.push_str(keyword.as_str()),
+            _ => text.push_str(token.text),
+        }
+        prev = Some(token);
+    }
+    text
+}
+
+#[test]
+fn parse_ddl_script() {
+    let schema = parse_schema(
+        "CREATE DOMAIN money AS numeric(10, 2) DEFAULT 0 CHECK (VALUE >= 0);\n\
+         CREATE TABLE customer (id integer PRIMARY KEY, name varchar(40) NOT NULL);\n\
+         CREATE TABLE orders (\n\
+           id integer NOT NULL,\n\
+           customer integer CONSTRAINT fk_customer REFERENCES customer (id),\n\
+           amount money DEFAULT 1 CHECK (amount < 1000) NOT NULL,\n\
+           CONSTRAINT pk_orders PRIMARY KEY (id)\n\
+         );\n\
+         CREATE VIEW big (id) AS SELECT id FROM orders WHERE amount > 100 WITH CHECK OPTION",
+    )
+    .unwrap();
+
+    let domain = &schema.domains[0];
+    assert_eq!(domain.data_type, "NUMERIC (10, 2)");
+    assert_eq!(domain.default.as_deref(), Some("0"));
+    assert_eq!(domain.constraints[0].definition, "CHECK (VALUE >= 0)");
+
+    let orders = &schema.tables[1];
+    assert_eq!(
+        orders.columns[2].to_sql(),
+        "amount money DEFAULT 1 NOT NULL"
+    );
+    let constraints: Vec<String> = orders.constraints.iter().map(Constraint::to_sql).collect();
+    assert_eq!(
+        constraints,
+        vec![
+            "CONSTRAINT fk_customer FOREIGN KEY (customer) REFERENCES customer (id)",
+            "CHECK (amount < 1000)",
+            "CONSTRAINT pk_orders PRIMARY KEY (id)",
+        ]
+    );
+    assert_eq!(
+        orders.constraints[0].references,
+        Some(QualifiedName::parse("customer").unwrap())
+    );
+
+    let view = &schema.views[0];
+    assert_eq!(
+        view.to_sql(),
+        "CREATE VIEW big (id) AS SELECT id FROM orders WHERE amount > 100 WITH CHECK OPTION"
+    );
+    assert_eq!(
+        view.references,
+        vec![QualifiedName::parse("orders").unwrap()]
+    );
+
+    assert!(parse_schema("DROP TABLE t").is_err());
+    assert!(parse_schema("CREATE TABLE t (a integer").is_err());
+}
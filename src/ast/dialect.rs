@@ -0,0 +1,291 @@
+/// Vendor extension a dialect may accept on top of SQL-92
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// `` `name` `` as delimited identifier
+    BacktickIdentifiers,
+    /// `LIMIT n` and `OFFSET n` after a query
+    LimitOffset,
+    /// `expr::type`
+    DoubleColonCast,
+    /// case-insensitive `ILIKE`
+    ILike,
+    /// `AUTO_INCREMENT` or `AUTOINCREMENT` column attribute
+    AutoIncrement,
+    /// `SERIAL` column types
+    Serial,
+    /// `FETCH FIRST n ROWS ONLY`, with `OFFSET n ROWS`
+    FetchFirst,
+    /// `CONCAT(a, b, ...)` function
+    ConcatFunction,
+}
+
+/// Data types which are spelled differently across dialects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    TinyInt,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Text,
+    Binary,
+    Timestamp,
+    Boolean,
+}
+
+impl DataType {
+    /// Type of any known spelling, case-insensitive.
+    /// `DOUBLE PRECISION` is recognized by its first word.
+    pub fn parse(name: &str) -> Option<DataType> {
+        let data_type = match name.to_uppercase().as_str() {
+            "TINYINT" | "INT1" => DataType::TinyInt,
+            "SMALLINT" | "INT2" => DataType::SmallInt,
+            "INTEGER" | "INT" | "INT4" => DataType::Integer,
+            "BIGINT" | "INT8" => DataType::BigInt,
+            "REAL" | "FLOAT4" => DataType::Real,
+            "DOUBLE" | "FLOAT8" => DataType::Double,
+            "TEXT" => DataType::Text,
+            "BYTEA" | "BLOB" => DataType::Binary,
+            "TIMESTAMP" | "DATETIME" => DataType::Timestamp,
+            "BOOLEAN" | "BOOL" => DataType::Boolean,
+            _ => return None,
+        };
+        Some(data_type)
+    }
+}
+
+/// Lexical and syntactic rules of a SQL flavour.
+/// The lexer and the parser ask the dialect instead of hard-coding them.
+pub trait Dialect {
+    fn name(&self) -> &'static str;
+
+    /// Characters which open and close a delimited identifier
+    fn identifier_quotes(&self) -> &'static [char] {
+        &['"']
+    }
+
+    /// Characters which open and close a character string literal
+    fn string_quotes(&self) -> &'static [char] {
+        &['\'']
+    }
+
+    fn supports(&self, _feature: Feature) -> bool {
+        false
+    }
+
+    /// Whether `||` concatenates strings
+    fn has_concat_operator(&self) -> bool {
+        true
+    }
+
+    /// Spelling of the auto-increment column attribute
+    fn auto_increment_keyword(&self) -> &'static str {
+        "AUTO_INCREMENT"
+    }
+
+    /// Data type of a type name as this dialect reads it
+    fn parse_type(&self, name: &str) -> Option<DataType> {
+        DataType::parse(name)
+    }
+
+    /// Spelling of the data type, `None` if there is no such type
+    fn type_name(&self, data_type: DataType) -> Option<&'static str> {
+        match data_type {
+            DataType::SmallInt => Some("SMALLINT"),
+            DataType::Integer => Some("INTEGER"),
+            DataType::Real => Some("REAL"),
+            DataType::Double => Some("DOUBLE PRECISION"),
+            DataType::Timestamp => Some("TIMESTAMP"),
+            DataType::TinyInt
+            | DataType::BigInt
+            | DataType::Text
+            | DataType::Binary
+            | DataType::Boolean => None,
+        }
+    }
+}
+
+/// Strict SQL-92, no extensions
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sql92;
+
+impl Dialect for Sql92 {
+    fn name(&self) -> &'static str {
+        "sql92"
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgreSql;
+
+impl Dialect for PostgreSql {
+    fn name(&self) -> &'static str {
+        "postgresql"
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        matches!(
+            feature,
+            Feature::LimitOffset
+                | Feature::DoubleColonCast
+                | Feature::ILike
+                | Feature::Serial
+                | Feature::FetchFirst
+                | Feature::ConcatFunction
+        )
+    }
+
+    fn type_name(&self, data_type: DataType) -> Option<&'static str> {
+        match data_type {
+            DataType::BigInt => Some("BIGINT"),
+            DataType::Text => Some("TEXT"),
+            DataType::Binary => Some("BYTEA"),
+            DataType::Boolean => Some("BOOLEAN"),
+            _ => Sql92.type_name(data_type),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MySql;
+
+impl Dialect for MySql {
+    fn name(&self) -> &'static str {
+        "mysql"
+    }
+
+    fn identifier_quotes(&self) -> &'static [char] {
+        &['`']
+    }
+
+    fn string_quotes(&self) -> &'static [char] {
+        &['\'', '"']
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        matches!(
+            feature,
+            Feature::BacktickIdentifiers
+                | Feature::LimitOffset
+                | Feature::AutoIncrement
+                | Feature::Serial
+                | Feature::ConcatFunction
+        )
+    }
+
+    /// `||` is a logical OR
+    fn has_concat_operator(&self) -> bool {
+        false
+    }
+
+    /// `REAL` is a synonym of `DOUBLE`
+    fn parse_type(&self, name: &str) -> Option<DataType> {
+        match DataType::parse(name) {
+            Some(DataType::Real) if name.eq_ignore_ascii_case("REAL") => Some(DataType::Double),
+            data_type => data_type,
+        }
+    }
+
+    fn type_name(&self, data_type: DataType) -> Option<&'static str> {
+        let name = match data_type {
+            DataType::TinyInt => "TINYINT",
+            DataType::SmallInt => "SMALLINT",
+            DataType::Integer => "INT",
+            DataType::BigInt => "BIGINT",
+            DataType::Real => "FLOAT",
+            DataType::Double => "DOUBLE",
+            DataType::Text => "TEXT",
+            DataType::Binary => "BLOB",
+            DataType::Timestamp => "DATETIME",
+            DataType::Boolean => "BOOLEAN",
+        };
+        Some(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn identifier_quotes(&self) -> &'static [char] {
+        &['"', '`']
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        matches!(
+            feature,
+            Feature::BacktickIdentifiers | Feature::LimitOffset | Feature::AutoIncrement
+        )
+    }
+
+    fn auto_increment_keyword(&self) -> &'static str {
+        "AUTOINCREMENT"
+    }
+
+    /// SQLite only has storage classes
+    fn type_name(&self, data_type: DataType) -> Option<&'static str> {
+        let name = match data_type {
+            DataType::TinyInt
+            | DataType::SmallInt
+            | DataType::Integer
+            | DataType::BigInt
+            | DataType::Boolean => "INTEGER",
+            DataType::Real | DataType::Double => "REAL",
+            DataType::Text | DataType::Timestamp => "TEXT",
+            DataType::Binary => "BLOB",
+        };
+        Some(name)
+    }
+}
+
+/// Strict SQL-92 unless asked otherwise
+pub const DEFAULT: &dyn Dialect = &Sql92;
+
+/// Built-in dialect by its name, case-insensitive
+pub fn by_name(name: &str) -> Option<&'static dyn Dialect> {
+    let dialect: &'static dyn Dialect = match name.to_lowercase().as_str() {
+        "sql92" | "ansi" => &Sql92,
+        "postgresql" | "postgres" => &PostgreSql,
+        "mysql" => &MySql,
+        "sqlite" => &Sqlite,
+        _ => return None,
+    };
+    Some(dialect)
+}
+
+#[test]
+fn dialects_by_name() {
+    assert_eq!(DEFAULT.name(), "sql92");
+    assert_eq!(by_name("Postgres").unwrap().name(), "postgresql");
+    assert!(by_name("oracle").is_none());
+
+    assert!(!DEFAULT.supports(Feature::LimitOffset));
+    assert!(by_name("mysql")
+        .unwrap()
+        .supports(Feature::BacktickIdentifiers));
+    assert_eq!(by_name("sqlite").unwrap().identifier_quotes(), &['"', '`']);
+}
+
+#[test]
+fn dialect_type_names() {
+    assert_eq!(DataType::parse("int4"), Some(DataType::Integer));
+    assert_eq!(DataType::parse("varchar"), None);
+    assert_eq!(DataType::parse("real"), Some(DataType::Real));
+    assert_eq!(MySql.parse_type("real"), Some(DataType::Double));
+
+    assert_eq!(Sql92.type_name(DataType::Integer), Some("INTEGER"));
+    assert_eq!(Sql92.type_name(DataType::Text), None);
+    assert_eq!(
+        PostgreSql.type_name(DataType::Double),
+        Some("DOUBLE PRECISION")
+    );
+    assert_eq!(MySql.type_name(DataType::Timestamp), Some("DATETIME"));
+    assert_eq!(Sqlite.type_name(DataType::Boolean), Some("INTEGER"));
+    assert_eq!(PostgreSql.type_name(DataType::Real), Some("REAL"));
+    assert_eq!(PostgreSql.type_name(DataType::TinyInt), None);
+}
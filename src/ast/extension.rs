@@ -0,0 +1,199 @@
+use crate::ast::dialect::{Dialect, Feature};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{Token, TokenKind};
+use std::ops::Range;
+
+/// Typed node of a vendor extension accepted by a dialect
+#[derive(Debug, Clone, PartialEq)]
+pub enum Extension {
+    /// `` `name` ``, holds the name without quotes
+    BacktickIdentifier(String),
+    /// `LIMIT count`
+    Limit(String),
+    /// `OFFSET count`, optionally followed by `ROWS`
+    Offset(String),
+    /// `FETCH FIRST count ROWS ONLY`
+    FetchFirst(String),
+    /// `::type_name`
+    Cast(String),
+    ILike,
+    AutoIncrement,
+    /// `SERIAL`, `BIGSERIAL` or `SMALLSERIAL`
+    Serial(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionNode {
+    pub extension: Extension,
+    pub span: Range<usize>,
+}
+
+/// Identifiers which start an extension, matched without folding a copy
+const WORDS: [&str; 8] = [
+    "LIMIT",
+    "OFFSET",
+    "ILIKE",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "SERIAL",
+    "BIGSERIAL",
+    "SMALLSERIAL",
+];
+
+/// Extensions used by the tokens, only those the dialect supports.
+/// In strict SQL-92 this is always empty.
+pub fn find_extensions(tokens: &[Token], dialect: &dyn Dialect) -> Vec<ExtensionNode> {
+    let tokens: Vec<&Token> = tokens.iter().filter(|t| !t.kind.is_trivia()).collect();
+    let mut nodes = vec![];
+
+    let upper_at = |i: usize| tokens.get(i).map(|t: &&Token| t.uppercase());
+    let is_row = |i: usize| matches!(upper_at(i).as_deref(), Some("ROW") | Some("ROWS"));
+
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1);
+        let qualified = i > 0 && tokens[i - 1].is_operator(".");
+        let mut push = |extension, end: usize| {
+            nodes.push(ExtensionNode {
+                extension,
+                span: token.span.start..end,
+            })
+        };
+
+        match token.kind {
+            TokenKind::DelimitedIdentifier
+                if token.text.starts_with('`')
+                    && dialect.supports(Feature::BacktickIdentifiers) =>
+            {
+                // unterminated at the end of the source, without closing quote
+                let body = &token.text[1..];
+                let body = body.strip_suffix('`').unwrap_or(body);
+                push(
+                    Extension::BacktickIdentifier(body.replace("``", "`")),
+                    token.span.end,
+                );
+            }
+            TokenKind::Operator if token.text == "::" => {
+                if let Some(ty) =
+                    next.filter(|t| matches!(t.kind, TokenKind::Identifier | TokenKind::Keyword(_)))
+                {
+                    push(Extension::Cast(ty.text.to_string()), ty.span.end);
+                }
+            }
+            TokenKind::Keyword(Keyword::Fetch) if dialect.supports(Feature::FetchFirst) => {
+                let first = matches!(upper_at(i + 1).as_deref(), Some("FIRST") | Some("NEXT"));
+                let only = tokens.get(i + 4).filter(|t| t.is_keyword(Keyword::Only));
+                match (first, tokens.get(i + 2), is_row(i + 3), only) {
+                    (true, Some(count), true, Some(only))
+                        if matches!(count.kind, TokenKind::Number | TokenKind::Parameter) =>
+                    {
+                        push(Extension::FetchFirst(count.text.to_string()), only.span.end)
+                    }
+                    _ => {}
+                }
+            }
+            TokenKind::Identifier if !qualified => {
+                let upper = WORDS
+                    .iter()
+                    .find(|word| word.eq_ignore_ascii_case(token.text))
+                    .map_or("", |word| word);
+                let count =
+                    next.filter(|t| matches!(t.kind, TokenKind::Number | TokenKind::Parameter));
+                match (upper, count) {
+                    ("LIMIT", Some(count)) if dialect.supports(Feature::LimitOffset) => {
+                        push(Extension::Limit(count.text.to_string()), count.span.end)
+                    }
+                    ("OFFSET", Some(count))
+                        if dialect.supports(Feature::LimitOffset)
+                            || dialect.supports(Feature::FetchFirst) =>
+                    {
+                        let end = if is_row(i + 2) {
+                            tokens[i + 2].span.end
+                        } else {
+                            count.span.end
+                        };
+                        push(Extension::Offset(count.text.to_string()), end)
+                    }
+                    ("ILIKE", _) if dialect.supports(Feature::ILike) => {
+                        push(Extension::ILike, token.span.end)
+                    }
+                    ("AUTO_INCREMENT", _) | ("AUTOINCREMENT", _)
+                        if dialect.supports(Feature::AutoIncrement) =>
+                    {
+                        push(Extension::AutoIncrement, token.span.end)
+                    }
+                    ("SERIAL", _) | ("BIGSERIAL", _) | ("SMALLSERIAL", _)
+                        if dialect.supports(Feature::Serial) =>
+                    {
+                        push(Extension::Serial(upper.to_string()), token.span.end)
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    nodes
+}
+
+#[test]
+fn extensions_of_dialects() {
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92};
+    use crate::ast::lexer::tokenize_with;
+
+    let src = "SELECT a::text FROM t WHERE b ILIKE 'x%' LIMIT 10 OFFSET 20";
+    let (tokens, _) = tokenize_with(src, &PostgreSql);
+    let nodes = find_extensions(&tokens, &PostgreSql);
+    let extensions: Vec<&Extension> = nodes.iter().map(|n| &n.extension).collect();
+    assert_eq!(
+        extensions,
+        vec![
+            &Extension::Cast("text".to_string()),
+            &Extension::ILike,
+            &Extension::Limit("10".to_string()),
+            &Extension::Offset("20".to_string()),
+        ]
+    );
+    assert_eq!(&src[nodes[0].span.clone()], "::text");
+    assert_eq!(&src[nodes[2].span.clone()], "LIMIT 10");
+
+    let src = "CREATE TABLE `t` (id INT AUTO_INCREMENT)";
+    let (tokens, _) = tokenize_with(src, &MySql);
+    let nodes = find_extensions(&tokens, &MySql);
+    assert_eq!(
+        nodes[0].extension,
+        Extension::BacktickIdentifier("t".to_string())
+    );
+    assert_eq!(nodes[1].extension, Extension::AutoIncrement);
+
+    let (tokens, _) = tokenize_with(src, &Sql92);
+    assert!(find_extensions(&tokens, &Sql92).is_empty());
+
+    let src = "SELECT a FROM t OFFSET 5 ROWS FETCH NEXT 1 ROW ONLY";
+    let (tokens, _) = tokenize_with(src, &PostgreSql);
+    let nodes = find_extensions(&tokens, &PostgreSql);
+    assert_eq!(nodes[0].extension, Extension::Offset("5".to_string()));
+    assert_eq!(&src[nodes[0].span.clone()], "OFFSET 5 ROWS");
+    assert_eq!(nodes[1].extension, Extension::FetchFirst("1".to_string()));
+    assert_eq!(&src[nodes[1].span.clone()], "FETCH NEXT 1 ROW ONLY");
+}
+
+#[test]
+fn unterminated_backtick_identifier() {
+    use crate::ast::dialect::MySql;
+    use crate::ast::lexer::tokenize_with;
+
+    for (src, name) in [
+        ("SELECT `\u{e9}", "\u{e9}"),
+        ("SELECT `ab", "ab"),
+        ("SELECT `", ""),
+    ] {
+        let (tokens, errors) = tokenize_with(src, &MySql);
+        assert_eq!(errors.len(), 1);
+        let nodes = find_extensions(&tokens, &MySql);
+        assert_eq!(
+            nodes[0].extension,
+            Extension::BacktickIdentifier(name.to_string())
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::identifier::Identifier;
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, LexError, Token, TokenKind};
+use crate::util::fnv::fnv1a;
+
+/// Marker which replaces literals and parameters
+pub const PLACEHOLDER: &str = "?";
+
+/// Statement text with the literals taken out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalized {
+    pub text: String,
+    pub fingerprint: u64,
+}
+
+/// Normalize a statement in strict SQL-92
+pub fn normalize(src: &str) -> Result<Normalized, LexError> {
+    normalize_with(src, dialect::DEFAULT)
+}
+
+/// Statements which differ only in literals, IN-list lengths,
+/// identifier case, whitespace and comments normalize to the same text.
+/// Keywords are written in upper case, regular identifiers in their
+/// canonical upper-case form, delimited identifiers as they are.
+pub fn normalize_with(src: &str, dialect: &dyn Dialect) -> Result<Normalized, LexError> {
+    let (tokens, errors) = tokenize_with(src, dialect);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    let mut tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+    while tokens.last().is_some_and(|t| t.is_operator(";")) {
+        tokens.pop();
+    }
+
+    // words with whether a space goes before them
+    let mut words: Vec<(String, bool)> = vec![];
+    let mut after_identifier = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let signed = (token.is_operator("-") || token.is_operator("+"))
+            && tokens
+                .get(i + 1)
+                .is_some_and(|t| t.kind == TokenKind::Number)
+            && (i == 0 || starts_operand(&tokens[i - 1]));
+        if signed {
+            i += 1;
+        }
+        i += 1;
+
+        let word = match token.kind {
+            _ if signed => PLACEHOLDER.to_string(),
+            kind if kind.is_literal() => PLACEHOLDER.to_string(),
+            TokenKind::Parameter => PLACEHOLDER.to_string(),
+            TokenKind::Keyword(keyword) => keyword.as_str().to_string(),
+            TokenKind::Identifier => token.text.to_uppercase(),
+            TokenKind::DelimitedIdentifier => Identifier::parse(token.text)
+                .map_or_else(|_| token.text.to_string(), |name| name.to_string()),
+            _ => token.text.to_string(),
+        };
+
+        // `IN (?, ?, ?)` collapses to `IN (?)`
+        if word == PLACEHOLDER && in_list_tail(&words) {
+            words.pop();
+            continue;
+        }
+
+        let prev = words.last().map(|(word, _)| word.as_str());
+        let glued = matches!(word.as_str(), "," | ")" | ".")
+            || matches!(prev, None | Some("(") | Some("."))
+            || word == "(" && after_identifier;
+        after_identifier = matches!(
+            token.kind,
+            TokenKind::Identifier | TokenKind::DelimitedIdentifier
+        );
+        words.push((word, !glued));
+    }
+
+    let mut text = String::new();
+    for (word, space) in words {
+        if space {
+            text.push(' ');
+        }
+        text.push_str(&word);
+    }
+    Ok(Normalized {
+        fingerprint: fingerprint_of(&text),
+        text,
+    })
+}
+
+/// Whether a sign after this token belongs to a number
+fn starts_operand(token: &Token) -> bool {
+    match token.kind {
+        TokenKind::Operator => !token.is_operator(")"),
+        TokenKind::Keyword(Keyword::Null) => false,
+        TokenKind::Keyword(_) => true,
+        _ => false,
+    }
+}
+
+/// The words end in `IN (?, ?, ... ,`
+fn in_list_tail(words: &[(String, bool)]) -> bool {
+    let mut rev = words.iter().rev().map(|(word, _)| word.as_str()).peekable();
+    if rev.peek() != Some(&",") {
+        return false;
+    }
+    loop {
+        match (rev.next(), rev.next()) {
+            (Some(","), Some(PLACEHOLDER)) => {}
+            (Some("("), Some("IN")) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// 64-bit FNV-1a of the normalized text, stable across runs and platforms
+pub fn fingerprint_of(text: &str) -> u64 {
+    fnv1a(text.as_bytes())
+}
+
+#[test]
+fn normalize_literals_and_layout() {
+    let a =
+        normalize("select a,  B from t -- list\nwhere id in (1, 2, 3) and name = 'x';").unwrap();
+    let b = normalize("SELECT A, b FROM T WHERE ID IN (-7) AND NAME = :name").unwrap();
+    assert_eq!(a.text, "SELECT A, B FROM T WHERE ID IN (?) AND NAME = ?");
+    assert_eq!(a, b);
+
+    let c = normalize("SELECT \"a\" FROM t WHERE x - 1 > COUNT(*)").unwrap();
+    assert_eq!(c.text, "SELECT \"a\" FROM T WHERE X - ? > COUNT (*)");
+    assert_ne!(c.fingerprint, a.fingerprint);
+
+    let d =
+        normalize("SELECT a FROM t WHERE id IN (1, b, 2) AND f(x) = DATE '2020-01-01'").unwrap();
+    assert_eq!(
+        d.text,
+        "SELECT A FROM T WHERE ID IN (?, B, ?) AND F(X) = DATE ?"
+    );
+
+    assert!(normalize("SELECT 'open").is_err());
+}
+
+#[test]
+fn fingerprint_is_stable() {
+    assert_eq!(fingerprint_of(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(fingerprint_of("a"), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(
+        normalize("SELECT 1").unwrap().fingerprint,
+        fingerprint_of("SELECT ?")
+    );
+}
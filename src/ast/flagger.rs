@@ -0,0 +1,423 @@
+use crate::ast::action::Action;
+use crate::ast::cst::CstNode;
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize, Token, TokenKind};
+use crate::ast::visit::{walk_action, Visit};
+use std::ops::Range;
+
+/// SQL-92 conformance levels, ordered from the smallest subset.
+/// `Extension` is anything outside of Full SQL-92.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Entry,
+    Intermediate,
+    Full,
+    Extension,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Entry => "entry",
+            Level::Intermediate => "intermediate",
+            Level::Full => "full",
+            Level::Extension => "extension",
+        }
+    }
+}
+
+/// One feature which raises the level of a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flag {
+    pub level: Level,
+    pub feature: &'static str,
+    /// byte range in the source, `None` for typed nodes without position
+    pub span: Option<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementLevel {
+    pub span: Range<usize>,
+    pub level: Level,
+    pub flags: Vec<Flag>,
+}
+
+/// Minimal level of the flags, Entry when there are none
+pub fn level_of(flags: &[Flag]) -> Level {
+    flags
+        .iter()
+        .map(|flag| flag.level)
+        .max()
+        .unwrap_or(Level::Entry)
+}
+
+/// Single keyword features, after the leveling rules of SQL-92
+const KEYWORD_FEATURES: &[(Keyword, Level, &str)] = &[
+    (Keyword::Join, Level::Intermediate, "joined table"),
+    (Keyword::Natural, Level::Intermediate, "natural join"),
+    (Keyword::Cast, Level::Intermediate, "CAST specification"),
+    (Keyword::Case, Level::Intermediate, "CASE expression"),
+    (Keyword::Nullif, Level::Intermediate, "NULLIF"),
+    (Keyword::Coalesce, Level::Intermediate, "COALESCE"),
+    (Keyword::Except, Level::Intermediate, "EXCEPT"),
+    (Keyword::Corresponding, Level::Intermediate, "CORRESPONDING"),
+    (Keyword::Intersect, Level::Full, "INTERSECT"),
+    (
+        Keyword::Date,
+        Level::Intermediate,
+        "datetime type or literal",
+    ),
+    (
+        Keyword::Time,
+        Level::Intermediate,
+        "datetime type or literal",
+    ),
+    (
+        Keyword::Timestamp,
+        Level::Intermediate,
+        "datetime type or literal",
+    ),
+    (
+        Keyword::Interval,
+        Level::Intermediate,
+        "interval type or literal",
+    ),
+    (
+        Keyword::CurrentDate,
+        Level::Intermediate,
+        "datetime value function",
+    ),
+    (
+        Keyword::CurrentTime,
+        Level::Intermediate,
+        "datetime value function",
+    ),
+    (
+        Keyword::CurrentTimestamp,
+        Level::Intermediate,
+        "datetime value function",
+    ),
+    (Keyword::Extract, Level::Intermediate, "EXTRACT"),
+    (Keyword::Varchar, Level::Intermediate, "CHARACTER VARYING"),
+    (Keyword::Varying, Level::Intermediate, "CHARACTER VARYING"),
+    (Keyword::National, Level::Intermediate, "national character"),
+    (Keyword::Nchar, Level::Intermediate, "national character"),
+    (Keyword::Bit, Level::Full, "BIT type"),
+    (Keyword::BitLength, Level::Full, "BIT_LENGTH"),
+    (
+        Keyword::Substring,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (
+        Keyword::Upper,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (
+        Keyword::Lower,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (
+        Keyword::Trim,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (
+        Keyword::Translate,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (
+        Keyword::Convert,
+        Level::Intermediate,
+        "character value function",
+    ),
+    (Keyword::Position, Level::Intermediate, "POSITION"),
+    (Keyword::CharLength, Level::Intermediate, "CHAR_LENGTH"),
+    (Keyword::CharacterLength, Level::Intermediate, "CHAR_LENGTH"),
+    (Keyword::OctetLength, Level::Intermediate, "OCTET_LENGTH"),
+    (Keyword::CurrentUser, Level::Intermediate, "CURRENT_USER"),
+    (Keyword::SessionUser, Level::Intermediate, "SESSION_USER"),
+    (Keyword::SystemUser, Level::Intermediate, "SYSTEM_USER"),
+    (Keyword::Alter, Level::Intermediate, "schema manipulation"),
+    (Keyword::Drop, Level::Intermediate, "schema manipulation"),
+    (Keyword::Domain, Level::Intermediate, "domain definition"),
+    (Keyword::Assertion, Level::Full, "assertion definition"),
+    (Keyword::Temporary, Level::Full, "temporary table"),
+    (Keyword::Collate, Level::Full, "COLLATE clause"),
+    (Keyword::Translation, Level::Full, "translation definition"),
+];
+
+/// Common vendor words which are plain identifiers for SQL-92
+const VENDOR_WORDS: &[&str] = &["ILIKE", "AUTO_INCREMENT", "SERIAL", "RETURNING"];
+/// Vendor row-limiting words, flagged when followed by a count
+const VENDOR_LIMITS: &[&str] = &["LIMIT", "OFFSET", "TOP"];
+
+/// Entry SQL limit on identifier length (5.2 leveling rules)
+const ENTRY_IDENTIFIER_LEN: usize = 18;
+
+/// Flag features found in the significant tokens of one statement
+pub fn flag_tokens(tokens: &[Token]) -> Vec<Flag> {
+    let tokens: Vec<&Token> = tokens.iter().filter(|t| !t.kind.is_trivia()).collect();
+    let mut flags = vec![];
+    let mut flag = |level, feature, span: &Range<usize>| {
+        flags.push(Flag {
+            level,
+            feature,
+            span: Some(span.clone()),
+        })
+    };
+
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1);
+        let prev = if i > 0 { tokens.get(i - 1) } else { None };
+        let next_is = |keyword| next.is_some_and(|t| t.is_keyword(keyword));
+
+        match token.kind {
+            TokenKind::Keyword(Keyword::Cross) if next_is(Keyword::Join) => {
+                flag(Level::Full, "cross join", &token.span)
+            }
+            TokenKind::Keyword(Keyword::Union) if next_is(Keyword::Join) => {
+                flag(Level::Full, "union join", &token.span)
+            }
+            TokenKind::Keyword(keyword) => {
+                if let Some((_, level, feature)) =
+                    KEYWORD_FEATURES.iter().find(|(k, _, _)| *k == keyword)
+                {
+                    flag(*level, feature, &token.span);
+                }
+            }
+            TokenKind::Identifier => {
+                let upper = token.text.to_uppercase();
+                let qualified = prev.is_some_and(|t| t.is_operator("."));
+                if token.text.chars().count() > ENTRY_IDENTIFIER_LEN {
+                    flag(
+                        Level::Intermediate,
+                        "identifier longer than 18",
+                        &token.span,
+                    );
+                }
+                if token.text.ends_with('_') {
+                    flag(Level::Intermediate, "identifier ending in '_'", &token.span);
+                }
+                if !qualified && VENDOR_WORDS.contains(&upper.as_str()) {
+                    flag(Level::Extension, "vendor keyword", &token.span);
+                }
+                let counted = next
+                    .is_some_and(|t| matches!(t.kind, TokenKind::Number | TokenKind::Parameter));
+                if !qualified && counted && VENDOR_LIMITS.contains(&upper.as_str()) {
+                    flag(Level::Extension, "row limit clause", &token.span);
+                }
+            }
+            TokenKind::DelimitedIdentifier if !token.text.starts_with('"') => flag(
+                Level::Extension,
+                "non-standard quoted identifier",
+                &token.span,
+            ),
+            TokenKind::DelimitedIdentifier => {
+                flag(Level::Intermediate, "delimited identifier", &token.span)
+            }
+            TokenKind::NationalString => flag(
+                Level::Intermediate,
+                "national character literal",
+                &token.span,
+            ),
+            TokenKind::BitString | TokenKind::HexString => {
+                flag(Level::Full, "bit string literal", &token.span)
+            }
+            TokenKind::Operator if token.text == "||" => {
+                flag(Level::Intermediate, "concatenation", &token.span)
+            }
+            // strict lexing reads `a::b` as `:` followed by the parameter `:b`
+            TokenKind::Operator
+                if token.text == "::"
+                    || token.text == ":" && next.is_some_and(|t| t.text.starts_with(':')) =>
+            {
+                flag(Level::Extension, "'::' cast", &token.span)
+            }
+            TokenKind::Unknown => flag(Level::Extension, "non-standard character", &token.span),
+            _ => {}
+        }
+    }
+
+    flags
+}
+
+/// Level of every `;`-separated statement of the source
+pub fn flag_source(src: &str) -> Vec<StatementLevel> {
+    let (tokens, _) = tokenize(src);
+    let mut statements = vec![];
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_operator(";") || i + 1 == tokens.len() {
+            let end = if token.is_operator(";") { i } else { i + 1 };
+            let statement = &tokens[start..end];
+            start = i + 1;
+
+            let mut comments: Vec<Flag> = statement
+                .iter()
+                .filter(|t| t.kind == TokenKind::Comment && t.text.starts_with("/*"))
+                .map(|t| Flag {
+                    level: Level::Extension,
+                    feature: "bracketed comment",
+                    span: Some(t.span.clone()),
+                })
+                .collect();
+            let significant: Vec<&Token> =
+                statement.iter().filter(|t| !t.kind.is_trivia()).collect();
+            let (first, last) = match (significant.first(), significant.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => continue,
+            };
+
+            let span = first.span.start..last.span.end;
+            let mut flags = flag_tokens(statement);
+            flags.append(&mut comments);
+            // what only the typed nodes tell, tokens flag the rest with spans
+            if let Ok(cst) = CstNode::parse(&src[span.clone()]) {
+                for typed in cst.statements().iter().flat_map(|s| flag_typed(s)) {
+                    if !flags.iter().any(|flag| flag.feature == typed.feature) {
+                        flags.push(typed);
+                    }
+                }
+            }
+            flags.sort_by_key(|flag| flag.span.as_ref().map(|span| span.start));
+            statements.push(StatementLevel {
+                span,
+                level: level_of(&flags),
+                flags,
+            });
+        }
+    }
+
+    statements
+}
+
+/// Flags of the typed nodes, collected with `Visit`
+#[derive(Default)]
+pub struct TypedFlagger {
+    pub flags: Vec<Flag>,
+}
+
+impl Visit for TypedFlagger {
+    fn visit_action(&mut self, action: &Action) {
+        if let Action::USAGE = action {
+            // USAGE applies to domains and character sets,
+            // neither of them exists in Entry SQL
+            self.flags.push(Flag {
+                level: Level::Intermediate,
+                feature: "USAGE privilege",
+                span: None,
+            });
+        }
+        walk_action(self, action)
+    }
+
+    fn visit_column_name(&mut self, name: &crate::ast::identifier::Identifier) {
+        if name.is_delimited() {
+            self.flags.push(Flag {
+                level: Level::Intermediate,
+                feature: "delimited identifier",
+                span: None,
+            });
+        }
+    }
+}
+
+/// Flags of the typed views of a statement which has them
+fn flag_typed(statement: &CstNode) -> Vec<Flag> {
+    let mut flagger = TypedFlagger::default();
+    if let Some(action) = statement.action() {
+        flagger.visit_action(&action);
+    }
+    if let Some(stmt) = statement.select_statement_single_row() {
+        flagger.visit_select_statement_single_row(&stmt);
+    }
+    flagger.flags
+}
+
+#[test]
+fn entry_level_statement() {
+    let statements = flag_source("SELECT a, b FROM t WHERE a = 1");
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].level, Level::Entry);
+    assert!(statements[0].flags.is_empty());
+}
+
+#[test]
+fn statement_levels_with_spans() {
+    let src = "SELECT CAST(a AS INTEGER) FROM t JOIN u ON t.id = u.id;\n\
+               SELECT * FROM t CROSS JOIN u;\n\
+               SELECT * FROM t LIMIT 10 /* page */;\n\
+               DELETE FROM t";
+    let statements = flag_source(src);
+
+    let levels: Vec<Level> = statements.iter().map(|s| s.level).collect();
+    assert_eq!(
+        levels,
+        vec![
+            Level::Intermediate,
+            Level::Full,
+            Level::Extension,
+            Level::Entry
+        ]
+    );
+
+    let cast = &statements[0].flags[0];
+    assert_eq!(cast.feature, "CAST specification");
+    assert_eq!(&src[cast.span.clone().unwrap()], "CAST");
+    assert_eq!(
+        &src[statements[1].span.clone()],
+        "SELECT * FROM t CROSS JOIN u"
+    );
+
+    let features: Vec<&str> = statements[2].flags.iter().map(|f| f.feature).collect();
+    assert_eq!(features, vec!["row limit clause", "bracketed comment"]);
+}
+
+#[test]
+fn identifier_rules() {
+    let flags = flag_tokens(&tokenize("SELECT very_long_column_name_x, a_ FROM t.limit").0);
+    let features: Vec<&str> = flags.iter().map(|f| f.feature).collect();
+    assert_eq!(
+        features,
+        vec!["identifier longer than 18", "identifier ending in '_'"]
+    );
+}
+
+#[test]
+fn typed_flags() {
+    use crate::ast::identifier::Identifier;
+    use crate::ast::select_list::SelectList;
+    use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+
+    let stmt: SelectStatementSingleRow = (
+        Action::INSERT(
+            "(".to_string(),
+            vec![Identifier::Delimited("a".to_string())],
+            ")".to_string(),
+        ),
+        None,
+        SelectList::ASTERISK,
+    );
+    let mut flagger = TypedFlagger::default();
+    flagger.visit_select_statement_single_row(&stmt);
+    assert_eq!(level_of(&flagger.flags), Level::Intermediate);
+    assert_eq!(flagger.flags[0].feature, "delimited identifier");
+
+    let statements =
+        flag_source("GRANT USAGE ON DOMAIN d TO PUBLIC; GRANT INSERT (\"a\") ON t TO u");
+    assert_eq!(statements[0].level, Level::Intermediate);
+    assert!(statements[0].flags.contains(&Flag {
+        level: Level::Intermediate,
+        feature: "USAGE privilege",
+        span: None,
+    }));
+    // the delimited identifier is flagged once, by its token
+    let features: Vec<&str> = statements[1].flags.iter().map(|f| f.feature).collect();
+    assert_eq!(features, vec!["delimited identifier"]);
+    assert!(statements[1].flags[0].span.is_some());
+}
@@ -0,0 +1,535 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::extension::{find_extensions, Extension};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, LexError, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// keep the spelling of the source
+    Preserve,
+}
+
+/// Where the comma goes when a list is broken into lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommaStyle {
+    Trailing,
+    Leading,
+}
+
+#[derive(Clone, Copy)]
+pub struct FormatOptions {
+    pub keyword_case: KeywordCase,
+    /// spaces per indentation level
+    pub indent: usize,
+    /// preferred maximal line width
+    pub width: usize,
+    pub comma: CommaStyle,
+    pub dialect: &'static dyn Dialect,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            keyword_case: KeywordCase::Upper,
+            indent: 4,
+            width: 80,
+            comma: CommaStyle::Trailing,
+            dialect: dialect::DEFAULT,
+        }
+    }
+}
+
+/// Significant token or comment, with what the layout needs to know
+#[derive(Debug, Clone)]
+struct Tok {
+    text: String,
+    kind: TokenKind,
+    /// no whitespace before it in the source
+    glued: bool,
+    /// comment which starts its own line in the source
+    own_line: bool,
+    /// starts a row limit clause of the dialect
+    row_limit: bool,
+}
+
+impl Tok {
+    fn is_op(&self, op: &str) -> bool {
+        self.kind == TokenKind::Operator && self.text == op
+    }
+
+    fn is_keyword(&self, keyword: Keyword) -> bool {
+        self.kind == TokenKind::Keyword(keyword)
+    }
+
+    fn is_comment(&self) -> bool {
+        self.kind == TokenKind::Comment
+    }
+
+    fn is_line_comment(&self) -> bool {
+        self.is_comment() && self.text.starts_with("--")
+    }
+}
+
+/// Rewrite the script in canonical style.
+/// Comments are kept, statements are separated by a blank line.
+pub fn format(src: &str, options: &FormatOptions) -> Result<String, LexError> {
+    let (tokens, errors) = tokenize_with(src, options.dialect);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    let extensions = find_extensions(&tokens, options.dialect);
+
+    let mut toks: Vec<Tok> = vec![];
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind == TokenKind::Whitespace {
+            continue;
+        }
+        let before = if i > 0 { Some(&tokens[i - 1]) } else { None };
+        let extension = extensions
+            .iter()
+            .find(|node| node.span.start == token.span.start)
+            .map(|node| &node.extension);
+        // non-reserved keywords are also valid names, they keep their case
+        let keyword = matches!(token.kind, TokenKind::Keyword(k) if k.is_reserved())
+            || extension.is_some_and(|e| !matches!(e, Extension::BacktickIdentifier(_)));
+        let text = match options.keyword_case {
+            KeywordCase::Upper if keyword => token.text.to_uppercase(),
+            KeywordCase::Lower if keyword => token.text.to_lowercase(),
+            _ => token.text.trim_end().to_string(),
+        };
+        toks.push(Tok {
+            text,
+            kind: token.kind,
+            glued: before.is_some_and(|t| t.kind != TokenKind::Whitespace),
+            own_line: before
+                .is_none_or(|t| t.kind == TokenKind::Whitespace && t.text.contains('\n')),
+            row_limit: matches!(
+                extension,
+                Some(Extension::Limit(_)) | Some(Extension::Offset(_))
+            ),
+        });
+    }
+
+    let formatter = Formatter { options };
+    let mut statements = vec![];
+    for statement in split_statements(&toks) {
+        statements.push(formatter.statement(statement));
+    }
+    Ok(statements.join("\n"))
+}
+
+/// Statements end after `;` and the comments on the same line
+fn split_statements(toks: &[Tok]) -> Vec<&[Tok]> {
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut i = 0;
+    while i < toks.len() {
+        if toks[i].is_op(";") {
+            i += 1;
+            while i < toks.len() && toks[i].is_comment() && !toks[i].own_line {
+                i += 1;
+            }
+            statements.push(&toks[start..i]);
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    if start < toks.len() {
+        statements.push(&toks[start..]);
+    }
+    statements
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClauseKind {
+    /// items separated by commas: SELECT, FROM, GROUP BY, ...
+    List,
+    /// conditions joined by AND/OR: WHERE, HAVING, joins
+    Condition,
+    Inline,
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+}
+
+impl<'a> Formatter<'a> {
+    fn indent(&self, level: usize) -> String {
+        " ".repeat(self.options.indent * level)
+    }
+
+    fn fits(&self, line: &str, level: usize) -> bool {
+        !line.contains('\n')
+            && self.options.indent * level + line.chars().count() <= self.options.width
+    }
+
+    fn statement(&self, toks: &[Tok]) -> String {
+        let mut out = String::new();
+        for clause in split_clauses(toks) {
+            self.clause(clause, &mut out);
+        }
+        out
+    }
+
+    fn clause(&self, toks: &[Tok], out: &mut String) {
+        // comments on their own lines before the clause
+        let leading = toks
+            .iter()
+            .take_while(|t| t.is_comment() && t.own_line)
+            .count();
+        for comment in &toks[..leading] {
+            out.push_str(&comment.text);
+            out.push('\n');
+        }
+        let toks = &toks[leading..];
+        if toks.is_empty() {
+            return;
+        }
+
+        let line = self.join(toks, 1);
+        if self.fits(&line, 0) {
+            out.push_str(&line);
+            out.push('\n');
+            return;
+        }
+        let head = head_len(toks).min(toks.len());
+        match clause_kind(&toks[0]) {
+            ClauseKind::List if head < toks.len() => self.list(toks, head, out),
+            ClauseKind::Condition => self.condition(toks, out),
+            _ => self.inline(toks, out),
+        }
+    }
+
+    /// Head on its own line, one item per line
+    fn list(&self, toks: &[Tok], head: usize, out: &mut String) {
+        out.push_str(&self.join(&toks[..head], 0));
+        out.push('\n');
+        let items = split_top_level(&toks[head..], |t, _| t.is_op(","));
+        self.items(&items, 1, out);
+    }
+
+    fn items(&self, items: &[&[Tok]], level: usize, out: &mut String) {
+        let indent = self.indent(level);
+        for (i, item) in items.iter().enumerate() {
+            let last = i + 1 == items.len();
+            // a trailing comma must not end up inside a line comment
+            let body = item
+                .iter()
+                .rposition(|t| !t.is_line_comment())
+                .map_or(0, |p| p + 1);
+            let (item, comments) = item.split_at(body);
+            let mut line = indent.clone();
+            if self.options.comma == CommaStyle::Leading && i > 0 {
+                line.push_str(", ");
+            }
+            line.push_str(&self.join(item, level));
+            if self.options.comma == CommaStyle::Trailing && !last {
+                line.push(',');
+            }
+            for comment in comments {
+                line.push(' ');
+                line.push_str(&comment.text);
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+
+    /// First condition next to the head, the others indented
+    fn condition(&self, toks: &[Tok], out: &mut String) {
+        let mut between = false;
+        let parts = split_top_level(toks, |t, i| {
+            if t.is_keyword(Keyword::Between) {
+                between = true;
+            }
+            let split =
+                i > 0 && (t.is_keyword(Keyword::Or) || t.is_keyword(Keyword::And) && !between);
+            if t.is_keyword(Keyword::And) {
+                between = false;
+            }
+            split
+        });
+        let indent = self.indent(1);
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                out.push_str(&indent);
+            }
+            out.push_str(&self.join(part, 1));
+            out.push('\n');
+        }
+    }
+
+    /// Break the first parenthesized list which has top-level commas
+    fn inline(&self, toks: &[Tok], out: &mut String) {
+        let mut depth = 0;
+        let mut open = None;
+        for (i, tok) in toks.iter().enumerate() {
+            if tok.is_op("(") {
+                depth += 1;
+                if depth == 1 {
+                    open = Some(i);
+                }
+            } else if tok.is_op(")") {
+                depth -= 1;
+                if depth == 0 {
+                    let open = open.unwrap_or(0);
+                    let items = split_top_level(&toks[open + 1..i], |t, _| t.is_op(","));
+                    if items.len() > 1 {
+                        out.push_str(&self.join(&toks[..=open], 0));
+                        out.push('\n');
+                        self.items(&items, 1, out);
+                        out.push_str(&self.join(&toks[i..], 0));
+                        out.push('\n');
+                        return;
+                    }
+                }
+            }
+        }
+        out.push_str(&self.join(toks, 1));
+        out.push('\n');
+    }
+
+    /// Tokens on one line, except after line comments
+    fn join(&self, toks: &[Tok], level: usize) -> String {
+        let mut line = String::new();
+        let mut newline = false;
+        for (i, tok) in toks.iter().enumerate() {
+            if i > 0 {
+                if newline || tok.is_comment() && tok.own_line {
+                    line.push('\n');
+                    line.push_str(&self.indent(level));
+                } else if space_between(i.checked_sub(2).map(|p| &toks[p]), &toks[i - 1], tok) {
+                    line.push(' ');
+                }
+            }
+            line.push_str(&tok.text);
+            newline = tok.is_line_comment();
+        }
+        line
+    }
+}
+
+fn space_between(before: Option<&Tok>, prev: &Tok, next: &Tok) -> bool {
+    if [",", ")", ".", ";", "]", "::"]
+        .iter()
+        .any(|op| next.is_op(op))
+    {
+        return false;
+    }
+    if ["(", ".", "::", "["].iter().any(|op| prev.is_op(op)) {
+        return false;
+    }
+    // function call, written without space in the source
+    if next.is_op("(")
+        && next.glued
+        && matches!(
+            prev.kind,
+            TokenKind::Identifier | TokenKind::DelimitedIdentifier | TokenKind::Keyword(_)
+        )
+    {
+        return false;
+    }
+    // unary sign
+    let unary = before.is_none_or(|b| {
+        b.kind == TokenKind::Operator && !b.is_op(")") || matches!(b.kind, TokenKind::Keyword(_))
+    });
+    !((prev.is_op("-") || prev.is_op("+")) && next.glued && unary)
+}
+
+/// Split the tokens of a statement into clauses, at top level only
+fn split_clauses(toks: &[Tok]) -> Vec<&[Tok]> {
+    let mut clauses = vec![];
+    let mut start = 0;
+    let mut depth = 0;
+    for i in 0..toks.len() {
+        if toks[i].is_op("(") {
+            depth += 1;
+        } else if toks[i].is_op(")") {
+            depth -= 1;
+        }
+        if depth != 0 || i == 0 || !starts_clause(toks, i) {
+            continue;
+        }
+        // comments on their own lines go with the next clause
+        let mut end = i;
+        while end > start && toks[end - 1].is_comment() && toks[end - 1].own_line {
+            end -= 1;
+        }
+        if end > start {
+            clauses.push(&toks[start..end]);
+            start = end;
+        }
+    }
+    clauses.push(&toks[start..]);
+    clauses
+}
+
+const JOIN_WORDS: [Keyword; 7] = [
+    Keyword::Left,
+    Keyword::Right,
+    Keyword::Full,
+    Keyword::Inner,
+    Keyword::Cross,
+    Keyword::Natural,
+    Keyword::Outer,
+];
+
+fn previous_significant(toks: &[Tok], i: usize) -> Option<&Tok> {
+    toks[..i].iter().rev().find(|t| !t.is_comment())
+}
+
+fn starts_clause(toks: &[Tok], i: usize) -> bool {
+    let prev = previous_significant(toks, i);
+    let prev_is = |keyword| prev.is_some_and(|p| p.is_keyword(keyword));
+    let tok = &toks[i];
+    if tok.row_limit {
+        return true;
+    }
+    let keyword = match tok.kind {
+        TokenKind::Keyword(keyword) => keyword,
+        _ => return false,
+    };
+    match keyword {
+        Keyword::Select
+        | Keyword::Where
+        | Keyword::Group
+        | Keyword::Having
+        | Keyword::Order
+        | Keyword::Except
+        | Keyword::Intersect
+        | Keyword::Values
+        | Keyword::Set
+        | Keyword::Fetch => true,
+        Keyword::Union => !toks.get(i + 1).is_some_and(|t| t.is_keyword(Keyword::Join)),
+        Keyword::From => !prev_is(Keyword::Delete),
+        Keyword::Join => !JOIN_WORDS.iter().any(|w| prev_is(*w)) && !prev_is(Keyword::Union),
+        Keyword::Left | Keyword::Right | Keyword::Full | Keyword::Inner | Keyword::Cross => {
+            !prev_is(Keyword::Natural)
+        }
+        Keyword::Natural => true,
+        _ => false,
+    }
+}
+
+/// Count of keywords which make the head of a clause
+fn head_len(toks: &[Tok]) -> usize {
+    let next_is = |i: usize, keywords: &[Keyword]| {
+        toks.get(i)
+            .is_some_and(|t| keywords.iter().any(|k| t.is_keyword(*k)))
+    };
+    match toks[0].kind {
+        TokenKind::Keyword(Keyword::Group) | TokenKind::Keyword(Keyword::Order) => 2,
+        TokenKind::Keyword(Keyword::Select) if next_is(1, &[Keyword::Distinct, Keyword::All]) => 2,
+        _ => 1,
+    }
+}
+
+fn clause_kind(tok: &Tok) -> ClauseKind {
+    match tok.kind {
+        TokenKind::Keyword(Keyword::Select)
+        | TokenKind::Keyword(Keyword::From)
+        | TokenKind::Keyword(Keyword::Group)
+        | TokenKind::Keyword(Keyword::Order)
+        | TokenKind::Keyword(Keyword::Set)
+        | TokenKind::Keyword(Keyword::Values) => ClauseKind::List,
+        TokenKind::Keyword(Keyword::Where) | TokenKind::Keyword(Keyword::Having) => {
+            ClauseKind::Condition
+        }
+        TokenKind::Keyword(keyword)
+            if keyword == Keyword::Join || JOIN_WORDS.contains(&keyword) =>
+        {
+            ClauseKind::Condition
+        }
+        _ => ClauseKind::Inline,
+    }
+}
+
+/// Split where `at` is true outside of parentheses.
+/// Separating commas are dropped, other separators start the next part.
+fn split_top_level(toks: &[Tok], mut at: impl FnMut(&Tok, usize) -> bool) -> Vec<&[Tok]> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut depth = 0;
+    for (i, tok) in toks.iter().enumerate() {
+        if tok.is_op("(") {
+            depth += 1;
+        } else if tok.is_op(")") {
+            depth -= 1;
+        } else if depth == 0 && at(tok, i) {
+            parts.push(&toks[start..i]);
+            start = if tok.is_op(",") { i + 1 } else { i };
+        }
+    }
+    parts.push(&toks[start..]);
+    parts
+}
+
+#[test]
+fn format_short_and_long_statements() {
+    let options = FormatOptions {
+        width: 30,
+        ..FormatOptions::default()
+    };
+    let sql = format(
+        "select a,b from t where x=1;select alpha_column, beta_column, count(*) from t \
+         where a = 1 and b between 1 and 2 or c = -1 group by alpha_column, beta_column",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT a, b\nFROM t\nWHERE x = 1;\n\
+         \n\
+         SELECT\n    \
+             alpha_column,\n    \
+             beta_column,\n    \
+             COUNT(*)\n\
+         FROM t\n\
+         WHERE a = 1\n    \
+             AND b BETWEEN 1 AND 2\n    \
+             OR c = -1\n\
+         GROUP BY\n    \
+             alpha_column,\n    \
+             beta_column\n"
+    );
+    assert_eq!(format(&sql, &options).unwrap(), sql);
+}
+
+#[test]
+fn format_keeps_comments() {
+    let src = "-- head\nSELECT a, -- first\n b /* mid */ FROM t; -- end\nselect 1";
+    let options = FormatOptions {
+        width: 20,
+        keyword_case: KeywordCase::Lower,
+        comma: CommaStyle::Leading,
+        ..FormatOptions::default()
+    };
+    let sql = format(src, &options).unwrap();
+    assert_eq!(
+        sql,
+        "-- head\nselect\n    a\n    , -- first\n    b /* mid */\nfrom t; -- end\n\nselect 1\n"
+    );
+    assert_eq!(format(&sql, &options).unwrap(), sql);
+}
+
+#[test]
+fn format_breaks_parenthesized_lists() {
+    let options = FormatOptions {
+        width: 30,
+        ..FormatOptions::default()
+    };
+    let sql = format(
+        "create table t (id integer, label varchar(20), created timestamp)",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        sql,
+        "CREATE TABLE t (\n    id INTEGER,\n    label VARCHAR(20),\n    created TIMESTAMP\n)\n"
+    );
+    assert_eq!(format(&sql, &options).unwrap(), sql);
+    assert!(format("SELECT 'open", &options).is_err());
+}
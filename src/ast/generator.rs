@@ -0,0 +1,456 @@
+use crate::ast::action::Action;
+use crate::ast::builder::{
+    select, table, Column, Condition, Operand, Query, SelectFrom, Table, Value,
+};
+use crate::ast::ddl::{ColumnDefinition, Schema, TableDefinition};
+use crate::ast::paren::{LPAREN, RPAREN};
+use crate::ast::unparse::ToSql;
+
+/// SplitMix64, reproducible from its seed
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, 0 when `n` is 0
+    pub fn below(&mut self, n: usize) -> usize {
+        match n {
+            0 => 0,
+            n => (self.next_u64() % n as u64) as usize,
+        }
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Size and feature controls
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    /// most columns of a select list, with 0 it is always `*`
+    pub max_columns: usize,
+    /// most tables joined to the first one
+    pub max_joins: usize,
+    /// most levels of AND, OR and NOT in a condition
+    pub max_depth: usize,
+    /// most values of an IN list, with 0 there are none
+    pub max_in_list: usize,
+    pub distinct: bool,
+    pub left_joins: bool,
+    pub order_by: bool,
+    pub like: bool,
+    /// IS NULL and IS NOT NULL
+    pub nulls: bool,
+    /// GRANT statements besides queries
+    pub grants: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            max_columns: 4,
+            max_joins: 2,
+            max_depth: 2,
+            max_in_list: 3,
+            distinct: true,
+            left_joins: true,
+            order_by: true,
+            like: true,
+            nulls: true,
+            grants: true,
+        }
+    }
+}
+
+/// What can be compared with what
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeClass {
+    /// exact or approximate number, with the largest integer it holds
+    Numeric(i64),
+    /// character string of at most that many characters
+    Character(usize),
+    /// compared only with columns of the same declared type
+    Other,
+}
+
+/// Column of a table of the FROM clause
+struct Scoped<'a> {
+    table: Table,
+    column: &'a ColumnDefinition,
+    class: TypeClass,
+}
+
+/// Random statements against the tables of a schema.
+/// Comparisons pair columns and values of compatible types, the
+/// same seed and options give the same statements.
+pub struct Generator<'a> {
+    schema: &'a Schema,
+    options: GeneratorOptions,
+    rng: Rng,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(schema: &'a Schema, seed: u64) -> Self {
+        Generator {
+            schema,
+            options: GeneratorOptions::default(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn with_options(mut self, options: GeneratorOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Query or GRANT, `None` when the schema has no table with columns
+    pub fn statement(&mut self) -> Option<Query> {
+        if self.options.grants && self.rng.chance(20) {
+            let (action, table) = self.action()?;
+            return Some(Query {
+                sql: format!(
+                    "GRANT {} ON {} TO PUBLIC",
+                    action.to_sql(),
+                    table.name.to_sql()
+                ),
+                params: vec![],
+            });
+        }
+        self.select()?.build().ok()
+    }
+
+    /// Privilege on a table, with columns of that table
+    pub fn action(&mut self) -> Option<(Action, &'a TableDefinition)> {
+        let table = self.table()?;
+        let mut columns = vec![];
+        for column in &table.columns {
+            if self.rng.chance(40) {
+                columns.push(column.name.clone());
+            }
+        }
+        let action = match self.rng.below(6) {
+            0 => Action::SELECT,
+            1 => Action::DELETE,
+            2 => Action::USAGE,
+            3 => Action::INSERT(LPAREN.to_string(), columns, RPAREN.to_string()),
+            4 => Action::UPDATE(LPAREN.to_string(), columns, RPAREN.to_string()),
+            _ => Action::REFERENCES(LPAREN.to_string(), columns, RPAREN.to_string()),
+        };
+        Some((action, table))
+    }
+
+    /// SELECT with joins, a WHERE condition and ORDER BY
+    pub fn select(&mut self) -> Option<SelectFrom> {
+        let first = self.table()?;
+        let from = table(&first.name.to_sql()).alias("t0");
+        let mut scope = self.scoped(&from, first);
+
+        let mut joins = vec![];
+        for i in 1..=self.rng.below(self.options.max_joins + 1) {
+            let joined = self.table()?;
+            let alias = table(&joined.name.to_sql()).alias(&format!("t{}", i));
+            let new = self.scoped(&alias, joined);
+            let pairs: Vec<(usize, usize)> = (0..scope.len())
+                .flat_map(|a| (0..new.len()).map(move |b| (a, b)))
+                .filter(|&(a, b)| comparable(&scope[a], &new[b]))
+                .collect();
+            if pairs.is_empty() {
+                continue;
+            }
+            let (a, b) = *self.rng.pick(&pairs);
+            let on = scope[a].column().eq(new[b].column());
+            let left = self.options.left_joins && self.rng.chance(30);
+            joins.push((alias, on, left));
+            scope.extend(new);
+        }
+
+        let mut query = select();
+        if self.options.distinct {
+            match self.rng.below(10) {
+                0 | 1 => query = query.distinct(),
+                2 => query = query.all(),
+                _ => {}
+            }
+        }
+        let mut selected = vec![];
+        if self.options.max_columns > 0 && self.rng.chance(70) {
+            for _ in 0..=self.rng.below(self.options.max_columns) {
+                let column = self.rng.below(scope.len());
+                query = query.column(scope[column].column());
+                selected.push(column);
+            }
+        }
+
+        let mut query = query.from(from);
+        for (alias, on, left) in joins {
+            query = match left {
+                true => query.left_join(alias, on),
+                false => query.join(alias, on),
+            };
+        }
+        if self.rng.chance(70) {
+            let depth = self.options.max_depth;
+            query = query.filter(self.condition(&scope, depth));
+        }
+        if self.options.order_by && self.rng.chance(40) {
+            // sort keys are columns of the result
+            let keys = match selected.is_empty() {
+                true => (0..scope.len()).collect(),
+                false => selected,
+            };
+            for _ in 0..=self.rng.below(2) {
+                let column = scope[*self.rng.pick(&keys)].column();
+                query = match self.rng.chance(50) {
+                    true => query.order_by_desc(column),
+                    false => query.order_by(column),
+                };
+            }
+        }
+        Some(query)
+    }
+
+    fn table(&mut self) -> Option<&'a TableDefinition> {
+        let tables: Vec<&TableDefinition> = self
+            .schema
+            .tables
+            .iter()
+            .filter(|t| !t.columns.is_empty())
+            .collect();
+        if tables.is_empty() {
+            return None;
+        }
+        Some(*self.rng.pick(&tables))
+    }
+
+    fn scoped(&self, table: &Table, definition: &'a TableDefinition) -> Vec<Scoped<'a>> {
+        definition
+            .columns
+            .iter()
+            .map(|column| Scoped {
+                table: table.clone(),
+                column,
+                class: self.class(&column.data_type),
+            })
+            .collect()
+    }
+
+    /// Class of a data type, domains by their own type
+    fn class(&self, data_type: &str) -> TypeClass {
+        if let Some(domain) = self
+            .schema
+            .domains
+            .iter()
+            .find(|d| d.name.to_sql().eq_ignore_ascii_case(data_type))
+        {
+            return self.class(&domain.data_type);
+        }
+        let mut words = data_type
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty());
+        let name = words.next().unwrap_or("").to_uppercase();
+        let sizes: Vec<i64> = words.filter_map(|w| w.parse().ok()).collect();
+        match name.as_str() {
+            "SMALLINT" | "INTEGER" | "INT" | "FLOAT" | "REAL" | "DOUBLE" => TypeClass::Numeric(999),
+            "NUMERIC" | "DECIMAL" | "DEC" => {
+                let digits = match sizes.as_slice() {
+                    [precision, scale] => precision - scale,
+                    [precision] => *precision,
+                    _ => 3,
+                };
+                TypeClass::Numeric(10i64.saturating_pow(digits.clamp(0, 3) as u32) - 1)
+            }
+            "CHAR" | "CHARACTER" | "VARCHAR" | "NATIONAL" | "NCHAR" => {
+                TypeClass::Character(sizes.first().map_or(1, |&n| n as usize))
+            }
+            _ => TypeClass::Other,
+        }
+    }
+
+    fn condition(&mut self, scope: &[Scoped], depth: usize) -> Condition {
+        if depth > 0 && self.rng.chance(40) {
+            let left = self.condition(scope, depth - 1);
+            return match self.rng.below(3) {
+                0 => left.and(self.condition(scope, depth - 1)),
+                1 => left.or(self.condition(scope, depth - 1)),
+                _ => left.not(),
+            };
+        }
+
+        let subject = &scope[self.rng.below(scope.len())];
+        let column = subject.column();
+        if subject.class == TypeClass::Other || self.options.nulls && self.rng.chance(15) {
+            let others: Vec<&Scoped> = scope
+                .iter()
+                .filter(|other| comparable(subject, other))
+                .collect();
+            return match self.rng.chance(50) && self.options.nulls || others.is_empty() {
+                true if self.rng.chance(50) => column.is_null(),
+                true => column.is_not_null(),
+                false => column.eq(self.rng.pick(&others).column()),
+            };
+        }
+
+        match self.rng.below(4) {
+            0 if self.options.max_in_list > 0 => {
+                let count = 1 + self.rng.below(self.options.max_in_list);
+                let values: Vec<Value> = (0..count).map(|_| self.value(subject.class)).collect();
+                column.in_list(values)
+            }
+            1 if self.options.like && matches!(subject.class, TypeClass::Character(_)) => {
+                let pattern = match self.value(subject.class) {
+                    Value::Text(text) => format!("{}%", text),
+                    _ => "%".to_string(),
+                };
+                column.like(pattern)
+            }
+            2 => {
+                let others: Vec<&Scoped> = scope
+                    .iter()
+                    .filter(|other| comparable(subject, other))
+                    .collect();
+                let other = self.rng.pick(&others).column();
+                self.compare(column, other)
+            }
+            _ => {
+                let value = self.value(subject.class);
+                self.compare(column, value)
+            }
+        }
+    }
+
+    fn compare(&mut self, column: Column, operand: impl Into<Operand>) -> Condition {
+        match self.rng.below(6) {
+            0 => column.eq(operand),
+            1 => column.ne(operand),
+            2 => column.lt(operand),
+            3 => column.le(operand),
+            4 => column.gt(operand),
+            _ => column.ge(operand),
+        }
+    }
+
+    fn value(&mut self, class: TypeClass) -> Value {
+        match class {
+            TypeClass::Numeric(max) => Value::Integer(self.rng.below(max as usize + 1) as i64),
+            TypeClass::Character(length) => {
+                let length = 1 + self.rng.below(length.min(8));
+                let text = (0..length)
+                    .map(|_| (b'a' + self.rng.below(26) as u8) as char)
+                    .collect();
+                Value::Text(text)
+            }
+            TypeClass::Other => Value::Null,
+        }
+    }
+}
+
+impl<'a> Scoped<'a> {
+    fn column(&self) -> Column {
+        self.table.col(&self.column.name.to_sql())
+    }
+}
+
+fn comparable(a: &Scoped, b: &Scoped) -> bool {
+    match (a.class, b.class) {
+        (TypeClass::Numeric(_), TypeClass::Numeric(_)) => true,
+        (TypeClass::Character(_), TypeClass::Character(_)) => true,
+        (TypeClass::Other, TypeClass::Other) => a.column.data_type == b.column.data_type,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+const SCHEMA: &str = "
+    CREATE DOMAIN money AS NUMERIC(10, 2);
+    CREATE TABLE customer (id INTEGER PRIMARY KEY, name VARCHAR(40), \"Since\" DATE);
+    CREATE TABLE orders (id INTEGER, customer INTEGER REFERENCES customer (id),
+        amount money, status CHAR(4), placed DATE);
+    CREATE TABLE \"order\" (\"select\" SMALLINT, note CHARACTER VARYING(100))";
+
+#[test]
+fn generated_statements_round_trip() {
+    use crate::ast::cst::CstNode;
+    use crate::ast::ddl::parse_schema;
+    use crate::ast::dialect;
+    use crate::ast::flagger::{flag_source, Level};
+
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut generator = Generator::new(&schema, 42);
+    let mut joins = 0;
+    for _ in 0..300 {
+        let query = generator.select().unwrap();
+        let sql = query.build().unwrap().sql;
+        joins += sql.matches(" JOIN ").count();
+        let tree = CstNode::parse(&sql).unwrap();
+        assert_eq!(
+            CstNode::parse_tolerant(&sql, dialect::DEFAULT).1,
+            vec![],
+            "{}",
+            sql
+        );
+        assert_eq!(tree.to_string(), sql);
+        assert!(flag_source(&sql)[0].level < Level::Extension, "{}", sql);
+        assert_eq!(
+            tree.statements()[0].select_statement_single_row(),
+            Some(query.statement())
+        );
+
+        let (action, table) = generator.action().unwrap();
+        let grant = format!(
+            "GRANT {} ON {} TO PUBLIC",
+            action.to_sql(),
+            table.name.to_sql()
+        );
+        let tree = CstNode::parse(&grant).unwrap();
+        assert_eq!(tree.statements()[0].action(), Some(action), "{}", grant);
+    }
+    assert!(joins > 0);
+}
+
+#[test]
+fn generator_options_and_seeds() {
+    use crate::ast::ddl::parse_schema;
+
+    let schema = parse_schema(SCHEMA).unwrap();
+    let statements = |seed| {
+        let mut generator = Generator::new(&schema, seed);
+        (0..20)
+            .map(|_| generator.statement().unwrap().sql)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(statements(7), statements(7));
+    assert_ne!(statements(7), statements(8));
+
+    let options = GeneratorOptions {
+        max_columns: 0,
+        max_joins: 0,
+        grants: false,
+        ..GeneratorOptions::default()
+    };
+    let mut generator = Generator::new(&schema, 1).with_options(options);
+    for _ in 0..50 {
+        let sql = generator.statement().unwrap().sql;
+        assert!(sql.starts_with("SELECT"), "{}", sql);
+        assert!(sql.contains("* FROM"), "{}", sql);
+        assert!(!sql.contains("JOIN"), "{}", sql);
+    }
+
+    let empty = parse_schema("CREATE VIEW v AS SELECT 1 FROM t").unwrap();
+    assert_eq!(Generator::new(&empty, 0).statement(), None);
+}
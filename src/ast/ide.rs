@@ -0,0 +1,285 @@
+use crate::ast::cst::{CstNode, SyntaxError};
+use crate::ast::ddl::sql_text;
+use crate::ast::dialect::Dialect;
+use crate::ast::identifier::Identifier;
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, Token, TokenCursor};
+use std::ops::Range;
+
+/// Longest statement summary in the outline
+const SUMMARY_LEN: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Table,
+    View,
+    Column,
+    Statement,
+}
+
+/// Entry of the document outline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// whole definition
+    pub span: Range<usize>,
+    /// the name, or the first token of a statement
+    pub selection: Range<usize>,
+    pub children: Vec<Symbol>,
+}
+
+/// Syntax errors of a half-written document
+pub fn diagnostics(src: &str, dialect: &dyn Dialect) -> Vec<SyntaxError> {
+    CstNode::parse_tolerant(src, dialect).1
+}
+
+/// Reserved words of `reserved_word` starting with `prefix`, in any case
+pub fn keywords(prefix: &str) -> Vec<&'static str> {
+    let prefix = prefix.to_uppercase();
+    let mut words: Vec<&'static str> = Keyword::ALL
+        .iter()
+        .filter(|keyword| keyword.is_reserved())
+        .map(|keyword| keyword.as_str())
+        .filter(|word| word.starts_with(&prefix))
+        .collect();
+    words.sort_unstable();
+    words
+}
+
+/// One symbol per statement, tables and views with their columns
+pub fn symbols(src: &str, dialect: &dyn Dialect) -> Vec<Symbol> {
+    let tokens = significant(src, dialect);
+    let mut symbols = vec![];
+    for statement in statements(&tokens) {
+        let span = statement[0].span.start..statement[statement.len() - 1].span.end;
+        let symbol = match definition_of(statement) {
+            Some(table) => Symbol {
+                name: table.name.to_string(),
+                kind: table.kind,
+                span,
+                selection: table.name_span,
+                children: table
+                    .columns
+                    .into_iter()
+                    .map(|column| Symbol {
+                        name: column.name.to_string(),
+                        kind: SymbolKind::Column,
+                        span: column.span,
+                        selection: column.name_span,
+                        children: vec![],
+                    })
+                    .collect(),
+            },
+            None => Symbol {
+                name: summary(statement),
+                kind: SymbolKind::Statement,
+                span,
+                selection: statement[0].span.clone(),
+                children: vec![],
+            },
+        };
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+/// Where the name at `offset` is defined: a table reference goes to
+/// its CREATE TABLE or CREATE VIEW, a column reference to the column
+/// definition. Qualifiers and correlation names are followed, an
+/// unqualified column is looked up in the tables of its statement first.
+pub fn definition(src: &str, offset: usize, dialect: &dyn Dialect) -> Option<Range<usize>> {
+    let tokens = significant(src, dialect);
+    let at = tokens
+        .iter()
+        .position(|t| t.span.start <= offset && offset <= t.span.end && t.is_name())?;
+    let name = Identifier::parse(tokens[at].text).ok()?;
+    let statement = statements(&tokens).into_iter().find(|s| {
+        s[0].span.start <= tokens[at].span.start && tokens[at].span.end <= s[s.len() - 1].span.end
+    })?;
+    let definitions: Vec<Definition> = statements(&tokens)
+        .into_iter()
+        .filter_map(definition_of)
+        .collect();
+
+    let table = |name: &Identifier| definitions.iter().find(|d| d.name == *name);
+    // table name or correlation name of the statement
+    let relation = |name: &Identifier| {
+        table(name).or_else(|| {
+            (1..statement.len())
+                .filter(|&i| Identifier::parse(statement[i].text).ok().as_ref() == Some(name))
+                .find_map(|i| {
+                    let before = match &statement[i - 1] {
+                        t if t.is_keyword(Keyword::As) && i >= 2 => &statement[i - 2],
+                        t => t,
+                    };
+                    table(&Identifier::parse(before.text).ok()?)
+                })
+        })
+    };
+    let column = |definition: &Definition| {
+        definition
+            .columns
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.name_span.clone())
+    };
+
+    if tokens.get(at + 1).is_some_and(|t| t.is_operator(".")) {
+        return relation(&name).map(|d| d.name_span.clone());
+    }
+    if at >= 2 && tokens[at - 1].is_operator(".") {
+        let qualifier = Identifier::parse(tokens[at - 2].text).ok()?;
+        return relation(&qualifier).and_then(column);
+    }
+    let used: Vec<&Definition> = definitions
+        .iter()
+        .filter(|d| {
+            statement
+                .iter()
+                .any(|t| Identifier::parse(t.text).ok().as_ref() == Some(&d.name))
+        })
+        .collect();
+    used.iter()
+        .find_map(|d| column(d))
+        .or_else(|| table(&name).map(|d| d.name_span.clone()))
+        .or_else(|| definitions.iter().find_map(column))
+}
+
+/// CREATE TABLE or CREATE VIEW with its column names
+struct Definition {
+    name: Identifier,
+    kind: SymbolKind,
+    name_span: Range<usize>,
+    columns: Vec<Column>,
+}
+
+struct Column {
+    name: Identifier,
+    span: Range<usize>,
+    name_span: Range<usize>,
+}
+
+fn definition_of(statement: &[Token]) -> Option<Definition> {
+    let mut cursor = TokenCursor::new(statement);
+    if !cursor.eat(Keyword::Create) {
+        return None;
+    }
+    let kind = if cursor.eat(Keyword::Table) {
+        SymbolKind::Table
+    } else if cursor.eat(Keyword::View) {
+        SymbolKind::View
+    } else {
+        return None;
+    };
+    // last part of a qualified name
+    let mut name_token = cursor.peek().filter(|t| t.is_name())?;
+    cursor.pos += 1;
+    while cursor.eat_operator(".") {
+        name_token = cursor.peek().filter(|t| t.is_name())?;
+        cursor.pos += 1;
+    }
+    let mut definition = Definition {
+        name: Identifier::parse(name_token.text).ok()?,
+        kind,
+        name_span: name_token.span.clone(),
+        columns: vec![],
+    };
+    if !cursor.eat_operator("(") {
+        return Some(definition);
+    }
+
+    // elements of the parenthesized list which start with a name
+    loop {
+        let element = cursor.until(|t| t.is_operator(","));
+        if let Some(first) = element.first().filter(|t| t.is_name()) {
+            definition.columns.push(Column {
+                name: Identifier::parse(first.text).ok()?,
+                span: first.span.start..element[element.len() - 1].span.end,
+                name_span: first.span.clone(),
+            });
+        }
+        if !cursor.eat_operator(",") {
+            break;
+        }
+    }
+    Some(definition)
+}
+
+fn significant<'a>(src: &'a str, dialect: &'a dyn Dialect) -> Vec<Token<'a>> {
+    let (tokens, _) = tokenize_with(src, dialect);
+    tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect()
+}
+
+/// Tokens of each statement, without the `;`
+fn statements<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t [Token<'a>]> {
+    tokens
+        .split(|t| t.is_operator(";"))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn summary(statement: &[Token]) -> String {
+    let text = sql_text(statement);
+    match text.char_indices().nth(SUMMARY_LEN) {
+        Some((end, _)) => format!("{}...", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+const SCRIPT: &str = "CREATE TABLE customer (id INTEGER PRIMARY KEY, name VARCHAR(40));
+CREATE TABLE orders (id INTEGER, customer INTEGER, amount NUMERIC(10, 2), CHECK (amount > 0));
+SELECT o.amount, name FROM orders AS o JOIN customer ON customer.id = o.customer WHERE amount > 100";
+
+#[test]
+fn outline_of_script() {
+    use crate::ast::dialect;
+
+    let symbols = symbols(SCRIPT, dialect::DEFAULT);
+    assert_eq!(symbols.len(), 3);
+    assert_eq!(symbols[0].name, "customer");
+    assert_eq!(symbols[0].kind, SymbolKind::Table);
+    assert_eq!(&SCRIPT[symbols[0].selection.clone()], "customer");
+    let columns: Vec<&str> = symbols[1]
+        .children
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(columns, vec!["id", "customer", "amount"]);
+    assert_eq!(
+        &SCRIPT[symbols[1].children[2].span.clone()],
+        "amount NUMERIC(10, 2)"
+    );
+    assert_eq!(symbols[2].kind, SymbolKind::Statement);
+    assert_eq!(
+        symbols[2].name,
+        "SELECT o.amount, name FROM orders AS o J..."
+    );
+
+    assert_eq!(keywords("wh"), vec!["WHEN", "WHENEVER", "WHERE"]);
+    assert_eq!(keywords("ad"), vec!["ADD"]);
+}
+
+#[test]
+fn definition_of_references() {
+    use crate::ast::dialect;
+
+    let select = SCRIPT.rfind("SELECT").unwrap();
+    let goto = |needle: &str, skip: usize| {
+        let at = select + SCRIPT[select..].match_indices(needle).nth(skip).unwrap().0;
+        definition(SCRIPT, at + needle.len() - 1, dialect::DEFAULT)
+            .map(|span| (&SCRIPT[span.clone()], span.start))
+    };
+    let orders = SCRIPT.find("orders").unwrap();
+    let amount = SCRIPT.find("amount").unwrap();
+    let customer_name = SCRIPT.find("name").unwrap();
+    let customer_id = SCRIPT.find("id").unwrap();
+
+    assert_eq!(goto("o.amount", 0), Some(("amount", amount)));
+    assert_eq!(goto("o", 0), Some(("orders", orders)));
+    assert_eq!(goto("name", 0), Some(("name", customer_name)));
+    assert_eq!(goto("customer.id", 0), Some(("id", customer_id)));
+    assert_eq!(goto("orders", 0), Some(("orders", orders)));
+    assert_eq!(goto("amount", 1), Some(("amount", amount)));
+}
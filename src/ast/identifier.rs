@@ -0,0 +1,218 @@
+use crate::ast::keyword::is_reserved;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// SQL-92 identifier (5.2 <token> and <separator>).
+///
+/// A regular identifier keeps its spelling but compares by its
+/// upper-case form; a delimited identifier keeps and compares
+/// its exact body. So `foo`, `FOO` and `"FOO"` are equivalent,
+/// while `"foo"` is a different name.
+#[derive(Debug, Clone)]
+pub enum Identifier {
+    Regular(String),
+    Delimited(String),
+}
+
+impl Identifier {
+    /// Read identifier from its SQL spelling: `name` or `"na""me"`.
+    /// A reserved word is only a name when delimited.
+    pub fn parse(src: &str) -> Result<Self, &'static str> {
+        if let Some(quoted) = src.strip_prefix('"') {
+            let body = quoted
+                .strip_suffix('"')
+                .ok_or("identifier: unterminated delimited identifier")?;
+            if body.is_empty() {
+                return Err("identifier: empty delimited identifier");
+            }
+            if body.replace("\"\"", "").contains('"') {
+                return Err("identifier: unescaped quote in delimited identifier");
+            }
+            return Ok(Identifier::Delimited(body.replace("\"\"", "\"")));
+        }
+
+        let mut chars = src.chars();
+        match chars.next() {
+            Some(c) if c.is_alphabetic() => {}
+            _ => return Err("identifier: regular identifier must start with a letter"),
+        }
+        if !chars.all(|c| c.is_alphanumeric() || c == '_') {
+            return Err("identifier: invalid character in regular identifier");
+        }
+        if is_reserved(src) {
+            return Err("identifier: reserved word must be delimited");
+        }
+        Ok(Identifier::Regular(src.to_string()))
+    }
+
+    /// Name as written, without quotes
+    pub fn value(&self) -> &str {
+        match self {
+            Identifier::Regular(value) | Identifier::Delimited(value) => value,
+        }
+    }
+
+    pub fn is_delimited(&self) -> bool {
+        matches!(self, Identifier::Delimited(_))
+    }
+
+    /// Form used for comparison: upper case for regular identifiers
+    pub fn canonical(&self) -> Cow<'_, str> {
+        match self {
+            Identifier::Regular(value) => Cow::Owned(value.to_uppercase()),
+            Identifier::Delimited(value) => Cow::Borrowed(value),
+        }
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for Identifier {}
+
+impl Hash for Identifier {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Identifier::Regular(value) => f.write_str(value),
+            Identifier::Delimited(value) => write!(f, "\"{}\"", value.replace('"', "\"\"")),
+        }
+    }
+}
+
+/// Dotted name: `catalog.schema.object` or `correlation.column`.
+/// Parts are counted from the right, so `object` is always the last one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QualifiedName {
+    parts: Vec<Identifier>,
+}
+
+impl QualifiedName {
+    pub fn new(parts: Vec<Identifier>) -> Result<Self, &'static str> {
+        if parts.is_empty() {
+            return Err("qualified name: no parts");
+        }
+        Ok(QualifiedName { parts })
+    }
+
+    /// Split on dots outside of delimited identifiers
+    pub fn parse(src: &str) -> Result<Self, &'static str> {
+        let mut parts = vec![];
+        let mut start = 0;
+        let mut in_quotes = false;
+        for (i, c) in src.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                '.' if !in_quotes => {
+                    parts.push(Identifier::parse(&src[start..i])?);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(Identifier::parse(&src[start..])?);
+        QualifiedName::new(parts)
+    }
+
+    pub fn parts(&self) -> &[Identifier] {
+        &self.parts
+    }
+
+    pub fn object(&self) -> &Identifier {
+        self.parts.last().unwrap()
+    }
+
+    pub fn schema(&self) -> Option<&Identifier> {
+        self.nth_from_right(1)
+    }
+
+    pub fn catalog(&self) -> Option<&Identifier> {
+        self.nth_from_right(2)
+    }
+
+    /// Everything but the last part, e.g. the correlation of a column
+    pub fn qualifier(&self) -> Option<QualifiedName> {
+        let (_, qualifier) = self.parts.split_last()?;
+        QualifiedName::new(qualifier.to_vec()).ok()
+    }
+
+    fn nth_from_right(&self, n: usize) -> Option<&Identifier> {
+        let len = self.parts.len();
+        if n >= len {
+            return None;
+        }
+        self.parts.get(len - 1 - n)
+    }
+}
+
+impl From<Identifier> for QualifiedName {
+    fn from(identifier: Identifier) -> Self {
+        QualifiedName {
+            parts: vec![identifier],
+        }
+    }
+}
+
+impl Display for QualifiedName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn identifier_equivalence() {
+    let id = |s| Identifier::parse(s).unwrap();
+
+    assert_eq!(id("foo"), id("FOO"));
+    assert_eq!(id("foo"), id("\"FOO\""));
+    assert_ne!(id("foo"), id("\"foo\""));
+    assert_ne!(id("\"Foo\""), id("\"FOO\""));
+    assert_eq!(id("\"a\"\"b\"").value(), "a\"b");
+    assert_eq!(id("\"a\"\"b\"").to_string(), "\"a\"\"b\"");
+    assert_eq!(id("Foo").to_string(), "Foo");
+}
+
+#[test]
+fn invalid_identifiers() {
+    assert!(Identifier::parse("").is_err());
+    assert!(Identifier::parse("1abc").is_err());
+    assert!(Identifier::parse("a-b").is_err());
+    assert!(Identifier::parse("\"\"").is_err());
+    assert!(Identifier::parse("\"open").is_err());
+    assert!(Identifier::parse("\"a\"b\"").is_err());
+    assert!(Identifier::parse("order").is_err());
+    assert!(Identifier::parse("\"ORDER\"").is_ok());
+    assert!(Identifier::parse("name").is_ok());
+}
+
+#[test]
+fn qualified_names() {
+    let name = QualifiedName::parse("cat.\"My.Schema\".t").unwrap();
+
+    assert_eq!(name.parts().len(), 3);
+    assert_eq!(name.catalog().unwrap().value(), "cat");
+    assert_eq!(name.schema().unwrap().value(), "My.Schema");
+    assert_eq!(name.object().value(), "t");
+    assert_eq!(name.to_string(), "cat.\"My.Schema\".t");
+    assert_eq!(name, QualifiedName::parse("CAT.\"My.Schema\".T").unwrap());
+
+    let column = QualifiedName::parse("e.salary").unwrap();
+    assert_eq!(column.qualifier().unwrap().to_string(), "e");
+    assert!(column.catalog().is_none());
+    assert!(QualifiedName::parse("a..b").is_err());
+}
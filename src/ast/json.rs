@@ -0,0 +1,310 @@
+use crate::ast::action::Action;
+use crate::ast::ast_node::AstNode;
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::paren::{LPAREN, RPAREN};
+use crate::ast::reserved_word as word;
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::select_sublist::SelectSublist;
+use crate::ast::set_quantifier::SetQuantifier;
+use crate::util::json::{self, JsonValue};
+
+/// Version of the JSON AST encoding.
+/// Bump it on any change of the layout below.
+pub const AST_JSON_VERSION: u32 = 1;
+
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str>;
+}
+
+/// Encode node into versioned document:
+/// `{"version":1,"ast":<node>}`
+pub fn encode<T: ToJson>(node: &T) -> String {
+    JsonValue::Object(vec![
+        (
+            "version".to_string(),
+            JsonValue::Number(AST_JSON_VERSION as f64),
+        ),
+        ("ast".to_string(), node.to_json()),
+    ])
+    .to_string()
+}
+
+/// Decode node from document produced by `encode`
+pub fn decode<T: FromJson>(src: &str) -> Result<T, &'static str> {
+    let document = json::parse(src)?;
+    let version = document
+        .get("version")
+        .and_then(JsonValue::as_f64)
+        .ok_or("ast json: missing version")?;
+    if version != AST_JSON_VERSION as f64 {
+        return Err("ast json: unsupported version");
+    }
+    let ast = document.get("ast").ok_or("ast json: missing ast")?;
+    T::from_json(ast)
+}
+
+fn kind_of(value: &JsonValue) -> Result<&str, &'static str> {
+    value
+        .get("kind")
+        .and_then(JsonValue::as_str)
+        .ok_or("ast json: missing kind")
+}
+
+fn tagged(kind: &str, mut fields: Vec<(String, JsonValue)>) -> JsonValue {
+    fields.insert(0, ("kind".to_string(), JsonValue::from(kind)));
+    JsonValue::Object(fields)
+}
+
+impl ToJson for AstNode {
+    fn to_json(&self) -> JsonValue {
+        tagged(
+            "ast_node",
+            vec![
+                ("name".to_string(), JsonValue::from(self.name.as_str())),
+                (
+                    "children".to_string(),
+                    JsonValue::Array(self.children().iter().map(|c| c.to_json()).collect()),
+                ),
+            ],
+        )
+    }
+}
+
+impl FromJson for AstNode {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str> {
+        if kind_of(value)? != "ast_node" {
+            return Err("ast json: expect ast_node");
+        }
+        let name = value
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .ok_or("ast json: missing name")?;
+        let children = value
+            .get("children")
+            .and_then(JsonValue::as_array)
+            .ok_or("ast json: missing children")?;
+
+        let mut node = AstNode::new(String::new());
+        // keep the stored spelling as is, `new` would fold it
+        node.name = name.to_string();
+        for child in children {
+            node.add(AstNode::from_json(child)?);
+        }
+        Ok(node)
+    }
+}
+
+impl ToJson for Action {
+    fn to_json(&self) -> JsonValue {
+        let (action, columns) = match self {
+            Action::SELECT => (word::SELECT, None),
+            Action::DELETE => (word::DELETE, None),
+            Action::USAGE => (word::USAGE, None),
+            Action::INSERT(_, list, _) => (word::INSERT, Some(list)),
+            Action::UPDATE(_, list, _) => (word::UPDATE, Some(list)),
+            Action::REFERENCES(_, list, _) => (word::REFERENCES, Some(list)),
+        };
+        let columns = match columns {
+            // identifiers are stored in their SQL spelling, quotes included
+            Some(list) => JsonValue::Array(list.iter().map(|c| c.to_string().into()).collect()),
+            None => JsonValue::Null,
+        };
+        tagged(
+            "action",
+            vec![
+                ("action".to_string(), JsonValue::from(action)),
+                ("columns".to_string(), columns),
+            ],
+        )
+    }
+}
+
+impl FromJson for Action {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str> {
+        if kind_of(value)? != "action" {
+            return Err("ast json: expect action");
+        }
+        let action = value
+            .get("action")
+            .and_then(JsonValue::as_str)
+            .ok_or("ast json: missing action")?;
+        let columns = || -> Result<Vec<Identifier>, &'static str> {
+            value
+                .get("columns")
+                .and_then(JsonValue::as_array)
+                .ok_or("ast json: missing columns")?
+                .iter()
+                .map(|c| Identifier::parse(c.as_str().ok_or("ast json: invalid column")?))
+                .collect()
+        };
+
+        match action {
+            word::SELECT => Ok(Action::SELECT),
+            word::DELETE => Ok(Action::DELETE),
+            word::USAGE => Ok(Action::USAGE),
+            word::INSERT => Ok(Action::INSERT(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            word::UPDATE => Ok(Action::UPDATE(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            word::REFERENCES => Ok(Action::REFERENCES(
+                LPAREN.to_string(),
+                columns()?,
+                RPAREN.to_string(),
+            )),
+            _ => Err("ast json: unknown action"),
+        }
+    }
+}
+
+impl ToJson for SetQuantifier {
+    fn to_json(&self) -> JsonValue {
+        match self {
+            SetQuantifier::DISTINCT => JsonValue::from(word::DISTINCT),
+            SetQuantifier::ALL => JsonValue::from(word::ALL),
+        }
+    }
+}
+
+impl FromJson for SetQuantifier {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str> {
+        match value.as_str() {
+            Some(word::DISTINCT) => Ok(SetQuantifier::DISTINCT),
+            Some(word::ALL) => Ok(SetQuantifier::ALL),
+            _ => Err("ast json: unknown set quantifier"),
+        }
+    }
+}
+
+impl ToJson for SelectList {
+    fn to_json(&self) -> JsonValue {
+        match self {
+            SelectList::ASTERISK => JsonValue::from("*"),
+            // column references in their SQL spelling
+            SelectList::SUBLISTS(sublists) => {
+                JsonValue::Array(sublists.iter().map(|s| s.0.to_string().into()).collect())
+            }
+        }
+    }
+}
+
+impl FromJson for SelectList {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str> {
+        if let Some(sublists) = value.as_array() {
+            return sublists
+                .iter()
+                .map(|s| {
+                    let name = s.as_str().ok_or("ast json: invalid select sublist")?;
+                    Ok(SelectSublist(QualifiedName::parse(name)?))
+                })
+                .collect::<Result<_, _>>()
+                .map(SelectList::SUBLISTS);
+        }
+        match value.as_str() {
+            Some("*") => Ok(SelectList::ASTERISK),
+            _ => Err("ast json: unknown select list"),
+        }
+    }
+}
+
+impl ToJson for SelectStatementSingleRow {
+    fn to_json(&self) -> JsonValue {
+        let (action, quantifier, list) = self;
+        tagged(
+            "select_statement_single_row",
+            vec![
+                ("action".to_string(), action.to_json()),
+                (
+                    "set_quantifier".to_string(),
+                    quantifier.as_ref().map_or(JsonValue::Null, ToJson::to_json),
+                ),
+                ("select_list".to_string(), list.to_json()),
+            ],
+        )
+    }
+}
+
+impl FromJson for SelectStatementSingleRow {
+    fn from_json(value: &JsonValue) -> Result<Self, &'static str> {
+        if kind_of(value)? != "select_statement_single_row" {
+            return Err("ast json: expect select_statement_single_row");
+        }
+        let field = |name| value.get(name).ok_or("ast json: missing field");
+        let quantifier = field("set_quantifier")?;
+        let quantifier = if quantifier.is_null() {
+            None
+        } else {
+            Some(SetQuantifier::from_json(quantifier)?)
+        };
+
+        Ok((
+            Action::from_json(field("action")?)?,
+            quantifier,
+            SelectList::from_json(field("select_list")?)?,
+        ))
+    }
+}
+
+#[test]
+fn ast_node_json_round_trip() {
+    let mut update_node = AstNode::from("UPDATE");
+    let mut lp_node = AstNode::from("(");
+    lp_node.add(AstNode::from("bob"));
+    update_node.add(lp_node).add(AstNode::from(")"));
+
+    let encoded = encode(&update_node);
+    assert_eq!(
+        encoded,
+        r#"{"version":1,"ast":{"kind":"ast_node","name":"UPDATE","children":[{"kind":"ast_node","name":"(","children":[{"kind":"ast_node","name":"bob","children":[]}]},{"kind":"ast_node","name":")","children":[]}]}}"#
+    );
+
+    let decoded: AstNode = decode(&encoded).unwrap();
+    assert_eq!(encode(&decoded), encoded);
+}
+
+#[test]
+fn select_statement_json_round_trip() {
+    let stmt: SelectStatementSingleRow = (
+        Action::REFERENCES(
+            LPAREN.to_string(),
+            vec![
+                Identifier::Regular("a".to_string()),
+                Identifier::Delimited("B c".to_string()),
+            ],
+            RPAREN.to_string(),
+        ),
+        Some(SetQuantifier::DISTINCT),
+        SelectList::ASTERISK,
+    );
+
+    let decoded: SelectStatementSingleRow = decode(&encode(&stmt)).unwrap();
+    assert_eq!(decoded, stmt);
+
+    let stmt: SelectStatementSingleRow = (
+        Action::SELECT,
+        None,
+        SelectList::SUBLISTS(vec![
+            SelectSublist(QualifiedName::parse("t.a").unwrap()),
+            SelectSublist(QualifiedName::parse("\"B c\"").unwrap()),
+        ]),
+    );
+    let decoded: SelectStatementSingleRow = decode(&encode(&stmt)).unwrap();
+    assert_eq!(decoded, stmt);
+}
+
+#[test]
+fn json_decode_rejects_other_version() {
+    let res: Result<AstNode, _> =
+        decode(r#"{"version":2,"ast":{"kind":"ast_node","name":"a","children":[]}}"#);
+    assert_eq!(res.err(), Some("ast json: unsupported version"));
+}
@@ -0,0 +1,425 @@
+use crate::ast::identifier::Identifier;
+use crate::ast::reserved_word as word;
+use crate::util::fnv::FnvHasher;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use std::sync::OnceLock;
+
+/// Keyword classes of SQL-92 (5.2 <key word>)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordClass {
+    /// can't be used as a regular identifier
+    Reserved,
+    /// keyword only in some contexts, still a valid identifier
+    NonReserved,
+}
+
+macro_rules! keywords {
+    ($($variant:ident => $text:expr, $class:ident;)*) => {
+        /// SQL-92 key word
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Keyword {
+            $($variant,)*
+        }
+
+        impl Keyword {
+            pub const ALL: &'static [Keyword] = &[$(Keyword::$variant,)*];
+
+            /// Upper-case spelling
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Keyword::$variant => $text,)*
+                }
+            }
+
+            pub fn class(self) -> KeywordClass {
+                match self {
+                    $(Keyword::$variant => KeywordClass::$class,)*
+                }
+            }
+        }
+    };
+}
+
+keywords! {
+    Absolute => word::ABSOLUTE, Reserved;
+    Action => word::ACTION, Reserved;
+    Ada => "ADA", NonReserved;
+    Add => word::ADD, Reserved;
+    All => word::ALL, Reserved;
+    Allocate => word::ALLOCATE, Reserved;
+    Alter => word::ALTER, Reserved;
+    And => word::AND, Reserved;
+    Any => word::ANY, Reserved;
+    Are => word::ARE, Reserved;
+    As => word::AS, Reserved;
+    Asc => word::ASC, Reserved;
+    Assertion => word::ASSERTION, Reserved;
+    At => word::AT, Reserved;
+    Authorization => word::AUTHORIZATION, Reserved;
+    Avg => word::AVG, Reserved;
+    Begin => word::BEGIN, Reserved;
+    Between => word::BETWEEN, Reserved;
+    Bit => word::BIT, Reserved;
+    BitLength => word::BIT_LENGTH, Reserved;
+    Both => word::BOTH, Reserved;
+    By => word::BY, Reserved;
+    C => "C", NonReserved;
+    Cascade => word::CASCADE, Reserved;
+    Cascaded => word::CASCADED, Reserved;
+    Case => word::CASE, Reserved;
+    Cast => word::CAST, Reserved;
+    Catalog => word::CATALOG, Reserved;
+    CatalogName => "CATALOG_NAME", NonReserved;
+    Char => word::CHAR, Reserved;
+    Character => word::CHARACTER, Reserved;
+    CharacterLength => word::CHARACTER_LENGTH, Reserved;
+    CharacterSetCatalog => "CHARACTER_SET_CATALOG", NonReserved;
+    CharacterSetName => "CHARACTER_SET_NAME", NonReserved;
+    CharacterSetSchema => "CHARACTER_SET_SCHEMA", NonReserved;
+    CharLength => word::CHAR_LENGTH, Reserved;
+    Check => word::CHECK, Reserved;
+    ClassOrigin => "CLASS_ORIGIN", NonReserved;
+    Close => word::CLOSE, Reserved;
+    Coalesce => word::COALESCE, Reserved;
+    Cobol => "COBOL", NonReserved;
+    Collate => word::COLLATE, Reserved;
+    Collation => word::COLLATION, Reserved;
+    CollationCatalog => "COLLATION_CATALOG", NonReserved;
+    CollationName => "COLLATION_NAME", NonReserved;
+    CollationSchema => "COLLATION_SCHEMA", NonReserved;
+    Column => word::COLUMN, Reserved;
+    ColumnName => "COLUMN_NAME", NonReserved;
+    CommandFunction => "COMMAND_FUNCTION", NonReserved;
+    Commit => word::COMMIT, Reserved;
+    Committed => "COMMITTED", NonReserved;
+    ConditionNumber => "CONDITION_NUMBER", NonReserved;
+    Connect => word::CONNECT, Reserved;
+    Connection => word::CONNECTION, Reserved;
+    ConnectionName => "CONNECTION_NAME", NonReserved;
+    Constraint => word::CONSTRAINT, Reserved;
+    Constraints => word::CONSTRAINTS, Reserved;
+    ConstraintCatalog => "CONSTRAINT_CATALOG", NonReserved;
+    ConstraintName => "CONSTRAINT_NAME", NonReserved;
+    ConstraintSchema => "CONSTRAINT_SCHEMA", NonReserved;
+    Continue => word::CONTINUE, Reserved;
+    Convert => word::CONVERT, Reserved;
+    Corresponding => word::CORRESPONDING, Reserved;
+    Count => word::COUNT, Reserved;
+    Create => word::CREATE, Reserved;
+    Cross => word::CROSS, Reserved;
+    Current => word::CURRENT, Reserved;
+    CurrentDate => word::CURRENT_DATE, Reserved;
+    CurrentTime => word::CURRENT_TIME, Reserved;
+    CurrentTimestamp => word::CURRENT_TIMESTAMP, Reserved;
+    CurrentUser => word::CURRENT_USER, Reserved;
+    Cursor => word::CURSOR, Reserved;
+    CursorName => "CURSOR_NAME", NonReserved;
+    Data => "DATA", NonReserved;
+    Date => word::DATE, Reserved;
+    DatetimeIntervalCode => "DATETIME_INTERVAL_CODE", NonReserved;
+    DatetimeIntervalPrecision => "DATETIME_INTERVAL_PRECISION", NonReserved;
+    Day => word::DAY, Reserved;
+    Deallocate => word::DEALLOCATE, Reserved;
+    Dec => word::DEC, Reserved;
+    Decimal => word::DECIMAL, Reserved;
+    Declare => word::DECLARE, Reserved;
+    Default => word::DEFAULT, Reserved;
+    Deferrable => word::DEFERRABLE, Reserved;
+    Deferred => word::DEFERRED, Reserved;
+    Delete => word::DELETE, Reserved;
+    Desc => word::DESC, Reserved;
+    Describe => word::DESCRIBE, Reserved;
+    Descriptor => word::DESCRIPTOR, Reserved;
+    Diagnostics => word::DIAGNOSTICS, Reserved;
+    Disconnect => word::DISCONNECT, Reserved;
+    Distinct => word::DISTINCT, Reserved;
+    Domain => word::DOMAIN, Reserved;
+    Double => word::DOUBLE, Reserved;
+    Drop => word::DROP, Reserved;
+    DynamicFunction => "DYNAMIC_FUNCTION", NonReserved;
+    Else => word::ELSE, Reserved;
+    End => word::END, Reserved;
+    EndExec => word::END_EXEC, Reserved;
+    Escape => word::ESCAPE, Reserved;
+    Except => word::EXCEPT, Reserved;
+    Exception => word::EXCEPTION, Reserved;
+    Exec => word::EXEC, Reserved;
+    Execute => word::EXECUTE, Reserved;
+    Exists => word::EXISTS, Reserved;
+    External => word::EXTERNAL, Reserved;
+    Extract => word::EXTRACT, Reserved;
+    False => word::FALSE, Reserved;
+    Fetch => word::FETCH, Reserved;
+    First => word::FIRST, Reserved;
+    Float => word::FLOAT, Reserved;
+    For => word::FOR, Reserved;
+    Foreign => word::FOREIGN, Reserved;
+    Fortran => "FORTRAN", NonReserved;
+    Found => word::FOUND, Reserved;
+    From => word::FROM, Reserved;
+    Full => word::FULL, Reserved;
+    Get => word::GET, Reserved;
+    Global => word::GLOBAL, Reserved;
+    Go => word::GO, Reserved;
+    Goto => word::GOTO, Reserved;
+    Grant => word::GRANT, Reserved;
+    Group => word::GROUP, Reserved;
+    Having => word::HAVING, Reserved;
+    Hour => word::HOUR, Reserved;
+    Identity => word::IDENTITY, Reserved;
+    Immediate => word::IMMEDIATE, Reserved;
+    In => word::IN, Reserved;
+    Indicator => word::INDICATOR, Reserved;
+    Initially => word::INITIALLY, Reserved;
+    Inner => word::INNER, Reserved;
+    Input => word::INPUT, Reserved;
+    Insensitive => word::INSENSITIVE, Reserved;
+    Insert => word::INSERT, Reserved;
+    Int => word::INT, Reserved;
+    Integer => word::INTEGER, Reserved;
+    Intersect => word::INTERSECT, Reserved;
+    Interval => word::INTERVAL, Reserved;
+    Into => word::INTO, Reserved;
+    Is => word::IS, Reserved;
+    Isolation => word::ISOLATION, Reserved;
+    Join => word::JOIN, Reserved;
+    Key => word::KEY, Reserved;
+    Language => word::LANGUAGE, Reserved;
+    Last => word::LAST, Reserved;
+    Leading => word::LEADING, Reserved;
+    Left => word::LEFT, Reserved;
+    Length => "LENGTH", NonReserved;
+    Level => word::LEVEL, Reserved;
+    Like => word::LIKE, Reserved;
+    Local => word::LOCAL, Reserved;
+    Lower => word::LOWER, Reserved;
+    Match => word::MATCH, Reserved;
+    Max => word::MAX, Reserved;
+    MessageLength => "MESSAGE_LENGTH", NonReserved;
+    MessageOctetLength => "MESSAGE_OCTET_LENGTH", NonReserved;
+    MessageText => "MESSAGE_TEXT", NonReserved;
+    Min => word::MIN, Reserved;
+    Minute => word::MINUTE, Reserved;
+    Module => word::MODULE, Reserved;
+    Month => word::MONTH, Reserved;
+    More => "MORE", NonReserved;
+    Mumps => "MUMPS", NonReserved;
+    Name => "NAME", NonReserved;
+    Names => word::NAMES, Reserved;
+    National => word::NATIONAL, Reserved;
+    Natural => word::NATURAL, Reserved;
+    Nchar => word::NCHAR, Reserved;
+    Next => word::NEXT, Reserved;
+    No => word::NO, Reserved;
+    Not => word::NOT, Reserved;
+    Null => word::NULL, Reserved;
+    Nullable => "NULLABLE", NonReserved;
+    Nullif => word::NULLIF, Reserved;
+    Number => "NUMBER", NonReserved;
+    Numeric => word::NUMERIC, Reserved;
+    OctetLength => word::OCTET_LENGTH, Reserved;
+    Of => word::OF, Reserved;
+    On => word::ON, Reserved;
+    Only => word::ONLY, Reserved;
+    Open => word::OPEN, Reserved;
+    Option => word::OPTION, Reserved;
+    Or => word::OR, Reserved;
+    Order => word::ORDER, Reserved;
+    Outer => word::OUTER, Reserved;
+    Output => word::OUTPUT, Reserved;
+    Overlaps => word::OVERLAPS, Reserved;
+    Pad => word::PAD, Reserved;
+    Partial => word::PARTIAL, Reserved;
+    Pascal => "PASCAL", NonReserved;
+    Pli => "PLI", NonReserved;
+    Position => word::POSITION, Reserved;
+    Precision => word::PRECISION, Reserved;
+    Prepare => word::PREPARE, Reserved;
+    Preserve => word::PRESERVE, Reserved;
+    Primary => word::PRIMARY, Reserved;
+    Prior => word::PRIOR, Reserved;
+    Privileges => word::PRIVILEGES, Reserved;
+    Procedure => word::PROCEDURE, Reserved;
+    Public => word::PUBLIC, Reserved;
+    Read => word::READ, Reserved;
+    Real => word::REAL, Reserved;
+    References => word::REFERENCES, Reserved;
+    Relative => word::RELATIVE, Reserved;
+    Repeatable => "REPEATABLE", NonReserved;
+    Restrict => word::RESTRICT, Reserved;
+    ReturnedLength => "RETURNED_LENGTH", NonReserved;
+    ReturnedOctetLength => "RETURNED_OCTET_LENGTH", NonReserved;
+    ReturnedSqlstate => "RETURNED_SQLSTATE", NonReserved;
+    Revoke => word::REVOKE, Reserved;
+    Right => word::RIGHT, Reserved;
+    Rollback => word::ROLLBACK, Reserved;
+    Rows => word::ROWS, Reserved;
+    RowCount => "ROW_COUNT", NonReserved;
+    Scale => "SCALE", NonReserved;
+    Schema => word::SCHEMA, Reserved;
+    SchemaName => "SCHEMA_NAME", NonReserved;
+    Scroll => word::SCROLL, Reserved;
+    Second => word::SECOND, Reserved;
+    Section => word::SECTION, Reserved;
+    Select => word::SELECT, Reserved;
+    Serializable => "SERIALIZABLE", NonReserved;
+    ServerName => "SERVER_NAME", NonReserved;
+    Session => word::SESSION, Reserved;
+    SessionUser => word::SESSION_USER, Reserved;
+    Set => word::SET, Reserved;
+    Size => word::SIZE, Reserved;
+    Smallint => word::SMALLINT, Reserved;
+    Some => word::SOME, Reserved;
+    Space => word::SPACE, Reserved;
+    Sql => word::SQL, Reserved;
+    Sqlcode => word::SQLCODE, Reserved;
+    Sqlerror => word::SQLERROR, Reserved;
+    Sqlstate => word::SQLSTATE, Reserved;
+    SubclassOrigin => "SUBCLASS_ORIGIN", NonReserved;
+    Substring => word::SUBSTRING, Reserved;
+    Sum => word::SUM, Reserved;
+    SystemUser => word::SYSTEM_USER, Reserved;
+    Table => word::TABLE, Reserved;
+    TableName => "TABLE_NAME", NonReserved;
+    Temporary => word::TEMPORARY, Reserved;
+    Then => word::THEN, Reserved;
+    Time => word::TIME, Reserved;
+    Timestamp => word::TIMESTAMP, Reserved;
+    TimezoneHour => word::TIMEZONE_HOUR, Reserved;
+    TimezoneMinute => word::TIMEZONE_MINUTE, Reserved;
+    To => word::TO, Reserved;
+    Trailing => word::TRAILING, Reserved;
+    Transaction => word::TRANSACTION, Reserved;
+    Translate => word::TRANSLATE, Reserved;
+    Translation => word::TRANSLATION, Reserved;
+    Trim => word::TRIM, Reserved;
+    True => word::TRUE, Reserved;
+    Type => "TYPE", NonReserved;
+    Uncommitted => "UNCOMMITTED", NonReserved;
+    Union => word::UNION, Reserved;
+    Unique => word::UNIQUE, Reserved;
+    Unknown => word::UNKNOWN, Reserved;
+    Unnamed => "UNNAMED", NonReserved;
+    Update => word::UPDATE, Reserved;
+    Upper => word::UPPER, Reserved;
+    Usage => word::USAGE, Reserved;
+    User => word::USER, Reserved;
+    Using => word::USING, Reserved;
+    Value => word::VALUE, Reserved;
+    Values => word::VALUES, Reserved;
+    Varchar => word::VARCHAR, Reserved;
+    Varying => word::VARYING, Reserved;
+    View => word::VIEW, Reserved;
+    When => word::WHEN, Reserved;
+    Whenever => word::WHENEVER, Reserved;
+    Where => word::WHERE, Reserved;
+    With => word::WITH, Reserved;
+    Work => word::WORK, Reserved;
+    Write => word::WRITE, Reserved;
+    Year => word::YEAR, Reserved;
+    Zone => word::ZONE, Reserved;
+}
+
+/// Longest keyword, longer words are rejected without hashing
+const MAX_KEYWORD_LEN: usize = 27;
+
+type KeywordTable = HashMap<&'static str, Keyword, BuildHasherDefault<FnvHasher>>;
+
+fn keyword_table() -> &'static KeywordTable {
+    static TABLE: OnceLock<KeywordTable> = OnceLock::new();
+    TABLE.get_or_init(|| Keyword::ALL.iter().map(|k| (k.as_str(), *k)).collect())
+}
+
+impl Keyword {
+    /// Case-insensitive lookup
+    pub fn lookup(word: &str) -> Option<Keyword> {
+        let bytes = word.as_bytes();
+        if bytes.len() > MAX_KEYWORD_LEN {
+            return None;
+        }
+        let mut upper = [0u8; MAX_KEYWORD_LEN];
+        let upper = &mut upper[..bytes.len()];
+        upper.copy_from_slice(bytes);
+        upper.make_ascii_uppercase();
+        let upper = std::str::from_utf8(upper).ok()?;
+        keyword_table().get(upper).copied()
+    }
+
+    pub fn is_reserved(self) -> bool {
+        self.class() == KeywordClass::Reserved
+    }
+}
+
+pub fn is_keyword(word: &str) -> bool {
+    Keyword::lookup(word).is_some()
+}
+
+pub fn is_reserved(word: &str) -> bool {
+    Keyword::lookup(word).is_some_and(Keyword::is_reserved)
+}
+
+/// Whether the name must be written as a delimited identifier
+/// to keep its exact meaning: it is not a valid regular identifier,
+/// it is a reserved word, or it isn't in the folded upper case.
+pub fn needs_quoting(name: &str) -> bool {
+    match Identifier::parse(name) {
+        Ok(Identifier::Regular(value)) => is_reserved(&value) || value != value.to_uppercase(),
+        _ => true,
+    }
+}
+
+impl Identifier {
+    /// Shortest spelling with the same meaning:
+    /// delimited only when a regular identifier won't do
+    pub fn to_minimal_sql(&self) -> String {
+        match self {
+            Identifier::Delimited(value) if !needs_quoting(value) => value.clone(),
+            Identifier::Regular(value) if is_reserved(value) => {
+                Identifier::Delimited(value.to_uppercase()).to_string()
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+#[test]
+fn keyword_lookup_ignores_case() {
+    assert_eq!(Keyword::lookup("select"), Some(Keyword::Select));
+    assert_eq!(Keyword::lookup("End-Exec"), Some(Keyword::EndExec));
+    assert_eq!(Keyword::lookup("Serializable"), Some(Keyword::Serializable));
+    assert_eq!(Keyword::lookup("selectx"), None);
+    assert_eq!(Keyword::lookup("sélect"), None);
+    assert_eq!(Keyword::Select.as_str(), "SELECT");
+    assert!(Keyword::ALL
+        .iter()
+        .all(|&k| Keyword::lookup(k.as_str()) == Some(k)));
+}
+
+#[test]
+fn keyword_classes() {
+    assert_eq!(Keyword::ALL.iter().filter(|k| k.is_reserved()).count(), 227);
+    assert!(Keyword::ALL
+        .iter()
+        .all(|k| k.as_str().len() <= MAX_KEYWORD_LEN));
+    assert!(is_reserved("table"));
+    assert!(!is_reserved("name"));
+    assert!(is_keyword("name"));
+    assert!(!is_keyword("salary"));
+}
+
+#[test]
+fn quoting() {
+    assert!(!needs_quoting("SALARY"));
+    assert!(needs_quoting("salary"));
+    assert!(needs_quoting("ORDER"));
+    assert!(needs_quoting("two words"));
+    assert!(!needs_quoting("NAME"));
+
+    let id = |s| Identifier::parse(s).unwrap();
+    assert_eq!(id("\"SALARY\"").to_minimal_sql(), "SALARY");
+    assert_eq!(id("\"Salary\"").to_minimal_sql(), "\"Salary\"");
+    let order = Identifier::Regular("order".to_string());
+    assert_eq!(order.to_minimal_sql(), "\"ORDER\"");
+    assert_eq!(id("salary").to_minimal_sql(), "salary");
+}
@@ -0,0 +1,576 @@
+use crate::ast::dialect::{self, Dialect, Feature};
+use crate::ast::keyword::Keyword;
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Lexical class of a token (SQL-92 5.2 <token> and <separator>)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword(Keyword),
+    /// regular identifier which is not a keyword
+    Identifier,
+    /// `"..."`, or another quote of the dialect
+    DelimitedIdentifier,
+    /// `'...'`
+    String,
+    /// `N'...'`
+    NationalString,
+    /// `B'...'`
+    BitString,
+    /// `X'...'`
+    HexString,
+    /// exact or approximate numeric literal
+    Number,
+    /// `?` or `:name`
+    Parameter,
+    /// operators and punctuation: `(`, `,`, `<=`, `||`, ...
+    Operator,
+    Whitespace,
+    /// `-- ...` or `/* ... */`
+    Comment,
+    /// character that starts no token
+    Unknown,
+}
+
+impl TokenKind {
+    /// Whitespace and comments
+    pub fn is_trivia(self) -> bool {
+        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
+    }
+
+    pub fn is_literal(self) -> bool {
+        matches!(
+            self,
+            TokenKind::String
+                | TokenKind::NationalString
+                | TokenKind::BitString
+                | TokenKind::HexString
+                | TokenKind::Number
+        )
+    }
+}
+
+/// Token borrowing its text from the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+impl<'a> Token<'a> {
+    pub fn is_keyword(&self, keyword: Keyword) -> bool {
+        self.kind == TokenKind::Keyword(keyword)
+    }
+
+    pub fn is_operator(&self, operator: &str) -> bool {
+        self.kind == TokenKind::Operator && self.text == operator
+    }
+
+    /// Identifier, delimited identifier or non-reserved key word
+    pub fn is_name(&self) -> bool {
+        match self.kind {
+            TokenKind::Identifier | TokenKind::DelimitedIdentifier => true,
+            TokenKind::Keyword(keyword) => !keyword.is_reserved(),
+            _ => false,
+        }
+    }
+
+    /// Text in upper case, borrowed when it has no lower-case letter
+    pub fn uppercase(&self) -> Cow<'a, str> {
+        if self.text.chars().any(char::is_lowercase) {
+            Cow::Owned(self.text.to_uppercase())
+        } else {
+            Cow::Borrowed(self.text)
+        }
+    }
+
+    /// Text inside the quotes of a delimited identifier or a string,
+    /// escapes kept. An unterminated one has no closing quote to strip.
+    pub fn unquoted(&self) -> &'a str {
+        let mut chars = self.text.chars();
+        let quote = chars.next().unwrap_or('"');
+        let body = chars.as_str();
+        body.strip_suffix(quote).unwrap_or(body)
+    }
+
+    /// Form names compare by: key words and regular identifiers in
+    /// upper case, delimited identifiers without quotes and escapes.
+    /// Only copies when folding or unescaping changes the text.
+    pub fn canonical(&self) -> Cow<'a, str> {
+        match self.kind {
+            TokenKind::Keyword(_) | TokenKind::Identifier => self.uppercase(),
+            TokenKind::DelimitedIdentifier => {
+                let quote = &self.text[..1];
+                let body = self.unquoted();
+                if body.contains(quote) {
+                    Cow::Owned(body.replace(&quote.repeat(2), quote))
+                } else {
+                    Cow::Borrowed(body)
+                }
+            }
+            _ => Cow::Borrowed(self.text),
+        }
+    }
+}
+
+/// Position in the significant tokens of a statement, shared by the
+/// recursive-descent readers
+pub struct TokenCursor<'t, 'a> {
+    pub tokens: &'t [Token<'a>],
+    pub pos: usize,
+}
+
+impl<'t, 'a> TokenCursor<'t, 'a> {
+    pub fn new(tokens: &'t [Token<'a>]) -> Self {
+        TokenCursor { tokens, pos: 0 }
+    }
+
+    pub fn peek(&self) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    pub fn at(&self, keyword: Keyword) -> bool {
+        self.peek().is_some_and(|t| t.is_keyword(keyword))
+    }
+
+    pub fn at_operator(&self, operator: &str) -> bool {
+        self.peek().is_some_and(|t| t.is_operator(operator))
+    }
+
+    pub fn eat(&mut self, keyword: Keyword) -> bool {
+        let found = self.at(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub fn eat_operator(&mut self, operator: &str) -> bool {
+        let found = self.at_operator(operator);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Index of the first token from `from` at this depth matching
+    /// `stop`, or of the `)` closing the enclosing parentheses
+    pub fn end_of(&self, from: usize, stop: impl Fn(&Token) -> bool) -> usize {
+        let mut depth = 0;
+        let mut i = from;
+        while let Some(token) = self.tokens.get(i) {
+            if depth == 0 && (stop(token) || token.is_operator(")")) {
+                break;
+            }
+            if token.is_operator("(") {
+                depth += 1;
+            } else if token.is_operator(")") {
+                depth -= 1;
+            }
+            i += 1;
+        }
+        i
+    }
+
+    /// Tokens up to `end_of` the current position, moving past them
+    pub fn until(&mut self, stop: impl Fn(&Token) -> bool) -> &'t [Token<'a>] {
+        let start = self.pos;
+        self.pos = self.end_of(start, stop);
+        &self.tokens[start..self.pos]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub message: &'static str,
+    pub span: Range<usize>,
+}
+
+/// Lossless tokenizer: every byte of the source ends up in exactly one
+/// token, whitespace and comments included, so the tokens concatenate
+/// back into the source. Problems are collected in `errors`.
+pub struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    dialect: &'a dyn Dialect,
+    pub errors: Vec<LexError>,
+}
+
+/// Tokens and errors of the whole source
+pub fn tokenize(src: &str) -> (Vec<Token<'_>>, Vec<LexError>) {
+    tokenize_with(src, dialect::DEFAULT)
+}
+
+pub fn tokenize_with<'a>(
+    src: &'a str,
+    dialect: &'a dyn Dialect,
+) -> (Vec<Token<'a>>, Vec<LexError>) {
+    let mut lexer = Lexer::with_dialect(src, dialect);
+    let tokens = lexer.by_ref().collect();
+    (tokens, lexer.errors)
+}
+
+const OPERATORS_2: [&str; 4] = ["<>", "<=", ">=", "||"];
+const OPERATORS_1: &str = "()[],.;:+-*/%=<>&|^?";
+
+impl<'a> Lexer<'a> {
+    /// Strict SQL-92 lexer
+    pub fn new(src: &'a str) -> Self {
+        Lexer::with_dialect(src, dialect::DEFAULT)
+    }
+
+    pub fn with_dialect(src: &'a str, dialect: &'a dyn Dialect) -> Self {
+        Lexer {
+            src,
+            pos: 0,
+            dialect,
+            errors: vec![],
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    /// Char `n` ahead; ASCII text needs no decoding
+    fn peek_at(&self, n: usize) -> Option<char> {
+        let bytes = &self.src.as_bytes()[self.pos..];
+        match bytes.get(..=n) {
+            Some(ahead) if ahead.is_ascii() => Some(ahead[n] as char),
+            _ => self.rest().chars().nth(n),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn bump_while(&mut self, f: impl Fn(char) -> bool) {
+        let rest = self.rest();
+        // an ASCII byte is a whole char, so most runs need no decoding
+        let ascii = rest
+            .bytes()
+            .position(|b| !b.is_ascii() || !f(b as char))
+            .unwrap_or(rest.len());
+        let rest = &rest[ascii..];
+        self.pos += ascii
+            + rest
+                .char_indices()
+                .find(|&(_, c)| !f(c))
+                .map_or(rest.len(), |(i, _)| i);
+    }
+
+    fn error(&mut self, message: &'static str, start: usize) {
+        self.errors.push(LexError {
+            message,
+            span: start..self.pos,
+        });
+    }
+
+    /// Quoted run with doubled quote as escape, opening quote at `pos`
+    fn quoted(&mut self, quote: char, start: usize, message: &'static str) {
+        self.bump();
+        loop {
+            match self.rest().find(quote) {
+                None => {
+                    self.pos = self.src.len();
+                    self.error(message, start);
+                    return;
+                }
+                Some(end) => {
+                    self.pos += end + quote.len_utf8();
+                    if self.peek() != Some(quote) {
+                        return;
+                    }
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) {
+        self.bump_while(|c| c.is_ascii_digit());
+        if self.peek() == Some('.') {
+            self.bump();
+            self.bump_while(|c| c.is_ascii_digit());
+        }
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            let exponent = match self.peek_at(1) {
+                Some('+') | Some('-') => self.peek_at(2),
+                other => other,
+            };
+            if exponent.is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+                if matches!(self.peek(), Some('+') | Some('-')) {
+                    self.bump();
+                }
+                self.bump_while(|c| c.is_ascii_digit());
+            }
+        }
+    }
+
+    fn next_kind(&mut self, start: usize) -> TokenKind {
+        let c = self.peek().unwrap();
+        let next = self.peek_at(1);
+
+        if c.is_whitespace() {
+            self.bump_while(char::is_whitespace);
+            return TokenKind::Whitespace;
+        }
+        // words first, they are most of a script
+        let string_prefix = next == Some('\'') && matches!(c, 'n' | 'N' | 'b' | 'B' | 'x' | 'X');
+        if c.is_alphabetic() && !string_prefix {
+            self.bump_while(|c| c.is_alphanumeric() || c == '_');
+            return match Keyword::lookup(&self.src[start..self.pos]) {
+                Some(keyword) => TokenKind::Keyword(keyword),
+                None => TokenKind::Identifier,
+            };
+        }
+        if c == '-' && next == Some('-') {
+            self.bump_while(|c| c != '\n');
+            return TokenKind::Comment;
+        }
+        if c == '/' && next == Some('*') {
+            match self.rest()[2..].find("*/") {
+                Some(end) => self.pos += 2 + end + 2,
+                None => {
+                    self.pos = self.src.len();
+                    self.error("unterminated comment", start);
+                }
+            }
+            return TokenKind::Comment;
+        }
+        if string_prefix {
+            let kind = match c {
+                'n' | 'N' => TokenKind::NationalString,
+                'b' | 'B' => TokenKind::BitString,
+                _ => TokenKind::HexString,
+            };
+            self.bump();
+            self.quoted('\'', start, "unterminated string literal");
+            return kind;
+        }
+        if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) {
+            self.number();
+            return TokenKind::Number;
+        }
+        if self.dialect.string_quotes().contains(&c) {
+            self.quoted(c, start, "unterminated string literal");
+            return TokenKind::String;
+        }
+        if self.dialect.identifier_quotes().contains(&c) {
+            self.quoted(c, start, "unterminated delimited identifier");
+            return TokenKind::DelimitedIdentifier;
+        }
+        if c == ':' && next == Some(':') && self.dialect.supports(Feature::DoubleColonCast) {
+            self.pos += 2;
+            return TokenKind::Operator;
+        }
+        match c {
+            '?' => {
+                self.bump();
+                return TokenKind::Parameter;
+            }
+            ':' if next.is_some_and(char::is_alphabetic) => {
+                self.bump();
+                self.bump_while(|c| c.is_alphanumeric() || c == '_');
+                return TokenKind::Parameter;
+            }
+            _ => {}
+        }
+        if OPERATORS_2.iter().any(|op| self.rest().starts_with(op)) {
+            self.pos += 2;
+            return TokenKind::Operator;
+        }
+        if OPERATORS_1.contains(c) {
+            self.bump();
+            return TokenKind::Operator;
+        }
+
+        self.bump();
+        self.error("unexpected character", start);
+        TokenKind::Unknown
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.src.len() {
+            return None;
+        }
+        let start = self.pos;
+        let kind = self.next_kind(start);
+        Some(Token {
+            kind,
+            text: &self.src[start..self.pos],
+            span: start..self.pos,
+        })
+    }
+}
+
+#[test]
+fn lexer_is_lossless() {
+    let src = "SELECT a.\"Col\"\"x\", 'it''s', N'n', X'0F', 1.5e-3 -- note\n/* c */ FROM t WHERE b <> ? AND d = :v;";
+    let (tokens, errors) = tokenize(src);
+
+    assert!(errors.is_empty());
+    assert_eq!(tokens.iter().map(|t| t.text).collect::<String>(), src);
+
+    let kinds: Vec<TokenKind> = tokens
+        .iter()
+        .filter(|t| !t.kind.is_trivia())
+        .map(|t| t.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Keyword(Keyword::Select),
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::DelimitedIdentifier,
+            TokenKind::Operator,
+            TokenKind::String,
+            TokenKind::Operator,
+            TokenKind::NationalString,
+            TokenKind::Operator,
+            TokenKind::HexString,
+            TokenKind::Operator,
+            TokenKind::Number,
+            TokenKind::Keyword(Keyword::From),
+            TokenKind::Identifier,
+            TokenKind::Keyword(Keyword::Where),
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::Parameter,
+            TokenKind::Keyword(Keyword::And),
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::Parameter,
+            TokenKind::Operator,
+        ]
+    );
+}
+
+#[test]
+fn lexer_reports_errors() {
+    let (tokens, errors) = tokenize("SELECT 'open");
+    assert_eq!(tokens.last().unwrap().kind, TokenKind::String);
+    assert_eq!(errors[0].message, "unterminated string literal");
+    assert_eq!(errors[0].span, 7..12);
+
+    let (tokens, errors) = tokenize("a ` b /* x");
+    assert_eq!(tokens[2].kind, TokenKind::Unknown);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(tokens.last().unwrap().kind, TokenKind::Comment);
+}
+
+#[test]
+fn lexer_follows_dialect() {
+    use crate::ast::dialect::{MySql, PostgreSql};
+
+    let texts = |src, dialect| {
+        let (tokens, errors) = tokenize_with(src, dialect);
+        assert!(errors.is_empty());
+        tokens
+            .into_iter()
+            .filter(|t| !t.kind.is_trivia())
+            .map(|t| (t.kind, t.text))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        texts("`a b` \"s\"", &MySql),
+        vec![
+            (TokenKind::DelimitedIdentifier, "`a b`"),
+            (TokenKind::String, "\"s\"")
+        ]
+    );
+    assert_eq!(
+        texts("a::int", &PostgreSql),
+        vec![
+            (TokenKind::Identifier, "a"),
+            (TokenKind::Operator, "::"),
+            (TokenKind::Keyword(Keyword::Int), "int")
+        ]
+    );
+    assert_eq!(tokenize("`a`").1.len(), 2);
+}
+
+#[test]
+fn lexer_numbers_and_names() {
+    let (tokens, _) = tokenize("1e 2E+5 .5 x1 nation");
+    let texts: Vec<&str> = tokens
+        .iter()
+        .filter(|t| !t.kind.is_trivia())
+        .map(|t| t.text)
+        .collect();
+    assert_eq!(texts, vec!["1", "e", "2E+5", ".5", "x1", "nation"]);
+}
+
+#[test]
+fn canonical_names_borrow() {
+    use crate::ast::dialect::MySql;
+
+    let names = |src, dialect| {
+        let (tokens, _) = tokenize_with(src, dialect);
+        tokens
+            .iter()
+            .filter(|t| !t.kind.is_trivia())
+            .map(|t| match t.canonical() {
+                Cow::Borrowed(name) => (name.to_string(), true),
+                Cow::Owned(name) => (name, false),
+            })
+            .collect::<Vec<_>>()
+    };
+    let owned = |name: &str, borrowed| (name.to_string(), borrowed);
+
+    assert_eq!(
+        names("NAME name \"a\"\"b\" \"x\" 'v'", dialect::DEFAULT),
+        vec![
+            owned("NAME", true),
+            owned("NAME", false),
+            owned("a\"b", false),
+            owned("x", true),
+            owned("'v'", true)
+        ]
+    );
+    assert_eq!(names("`c``d`", &MySql), vec![owned("c`d", false)]);
+    assert_eq!(names("\"ab", dialect::DEFAULT), vec![owned("ab", true)]);
+    assert_eq!(
+        names("\"\u{e9}", dialect::DEFAULT),
+        vec![owned("\u{e9}", true)]
+    );
+    assert_eq!(names("\"", dialect::DEFAULT), vec![owned("", true)]);
+}
+
+#[test]
+fn cursor_stops_at_depth_zero() {
+    let (tokens, _) = tokenize("name (a, b), \"c\" ORDER");
+    let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+    let mut cursor = TokenCursor::new(&tokens);
+
+    assert!(cursor.peek().is_some_and(|t| t.is_name()));
+    assert_eq!(
+        cursor
+            .until(|t| t.is_operator(","))
+            .iter()
+            .map(|t| t.text)
+            .collect::<Vec<_>>(),
+        vec!["name", "(", "a", ",", "b", ")"]
+    );
+    assert!(cursor.eat_operator(","));
+    assert!(cursor.peek().is_some_and(|t| t.is_name()));
+    cursor.pos += 1;
+    assert!(!cursor.peek().is_some_and(|t| t.is_name()));
+    assert!(cursor.eat(Keyword::Order));
+    assert_eq!(cursor.peek(), None);
+}
@@ -0,0 +1,863 @@
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize, LexError, Token, TokenCursor, TokenKind};
+
+/// Column of a table, or of the statement result when `table` is `None`.
+/// `*` of a table without known columns stays `"*"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnRef {
+    pub table: Option<QualifiedName>,
+    pub column: Identifier,
+}
+
+/// Source columns which feed an output or target column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLineage {
+    pub target: ColumnRef,
+    pub sources: Vec<ColumnRef>,
+}
+
+/// What one statement reads and writes.
+/// Views of the catalog are resolved to their base tables.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lineage {
+    pub reads: Vec<QualifiedName>,
+    pub writes: Vec<QualifiedName>,
+    pub columns: Vec<ColumnLineage>,
+}
+
+/// Known tables and views, used to resolve unqualified
+/// columns, `*` and the columns of views
+#[derive(Debug, Default)]
+pub struct Catalog {
+    tables: Vec<(QualifiedName, Vec<Identifier>)>,
+    views: Vec<(QualifiedName, String)>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    pub fn table(mut self, name: &str, columns: &[&str]) -> Result<Self, &'static str> {
+        let columns = columns
+            .iter()
+            .map(|column| Identifier::parse(column))
+            .collect::<Result<_, _>>()?;
+        self.tables.push((QualifiedName::parse(name)?, columns));
+        Ok(self)
+    }
+
+    /// View by the text of its query
+    pub fn view(mut self, name: &str, query: &str) -> Result<Self, &'static str> {
+        self.views
+            .push((QualifiedName::parse(name)?, query.to_string()));
+        Ok(self)
+    }
+
+    /// Names of the tables, then of the views
+    pub fn names(&self) -> Vec<&QualifiedName> {
+        let tables = self.tables.iter().map(|(name, _)| name);
+        tables
+            .chain(self.views.iter().map(|(name, _)| name))
+            .collect()
+    }
+
+    /// Columns of a table, or the output columns of a view
+    pub fn relation_columns(&self, relation: &QualifiedName) -> Option<Vec<Identifier>> {
+        if let Some(columns) = self.columns(relation) {
+            return Some(columns.clone());
+        }
+        let query = lineage(self.view_query(relation)?, self).ok()?;
+        let outputs = query.into_iter().next()?.columns;
+        Some(outputs.into_iter().map(|c| c.target.column).collect())
+    }
+
+    fn columns(&self, table: &QualifiedName) -> Option<&Vec<Identifier>> {
+        self.tables
+            .iter()
+            .find(|(name, _)| same_object(name, table))
+            .map(|(_, columns)| columns)
+    }
+
+    fn view_query(&self, view: &QualifiedName) -> Option<&str> {
+        self.views
+            .iter()
+            .find(|(name, _)| same_object(name, view))
+            .map(|(_, query)| query.as_str())
+    }
+}
+
+/// `s.t` and `t` name the same object, `s.t` and `u.t` don't
+fn same_object(a: &QualifiedName, b: &QualifiedName) -> bool {
+    a.object() == b.object()
+        && match (a.schema(), b.schema()) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+}
+
+/// Lineage of every `;`-separated statement
+pub fn lineage(src: &str, catalog: &Catalog) -> Result<Vec<Lineage>, LexError> {
+    let (tokens, errors) = tokenize(src);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+    Ok(tokens
+        .split(|t| t.is_operator(";"))
+        .filter(|statement| !statement.is_empty())
+        .map(|statement| Analyzer::new(src, statement, catalog, 0).statement())
+        .collect())
+}
+
+/// Views nested deeper are not resolved
+const MAX_VIEW_DEPTH: usize = 8;
+
+/// Output column of a query with its sources
+type Output = (Identifier, Vec<ColumnRef>);
+
+/// Table, view or derived table in a FROM clause
+struct Relation {
+    name: Identifier,
+    table: Option<QualifiedName>,
+    /// `None` for a table the catalog doesn't know
+    columns: Option<Vec<Output>>,
+}
+
+impl Relation {
+    fn has(&self, column: &Identifier) -> Option<bool> {
+        let columns = self.columns.as_ref()?;
+        Some(columns.iter().any(|(name, _)| name == column))
+    }
+
+    fn sources(&self, column: &Identifier) -> Vec<ColumnRef> {
+        let known = self
+            .columns
+            .iter()
+            .flatten()
+            .find(|(name, _)| name == column);
+        match (known, &self.table) {
+            (Some((_, sources)), _) => sources.clone(),
+            (None, Some(table)) => vec![ColumnRef {
+                table: Some(table.clone()),
+                column: column.clone(),
+            }],
+            (None, None) => vec![],
+        }
+    }
+
+    /// Columns of `*`
+    fn star(&self) -> Vec<Output> {
+        match (&self.columns, &self.table) {
+            (Some(columns), _) => columns.clone(),
+            (None, table) => {
+                let star = Identifier::Delimited("*".to_string());
+                let source = ColumnRef {
+                    table: table.clone(),
+                    column: star.clone(),
+                };
+                vec![(star, vec![source])]
+            }
+        }
+    }
+}
+
+/// Recursive descent over the significant tokens of a statement
+struct Analyzer<'a> {
+    src: &'a str,
+    cursor: TokenCursor<'a, 'a>,
+    catalog: &'a Catalog,
+    /// relations of the enclosing queries, innermost last
+    scopes: Vec<Vec<Relation>>,
+    reads: Vec<QualifiedName>,
+    views: usize,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(src: &'a str, tokens: &'a [Token<'a>], catalog: &'a Catalog, views: usize) -> Self {
+        Analyzer {
+            src,
+            cursor: TokenCursor::new(tokens),
+            catalog,
+            scopes: vec![],
+            reads: vec![],
+            views,
+        }
+    }
+
+    fn statement(mut self) -> Lineage {
+        let mut writes = vec![];
+        let mut columns = vec![];
+        let target = |table: &QualifiedName, column: Identifier| ColumnRef {
+            table: Some(table.clone()),
+            column,
+        };
+
+        if self.cursor.at(Keyword::Select) || self.cursor.at_operator("(") {
+            for (name, sources) in self.query() {
+                columns.push(ColumnLineage {
+                    target: ColumnRef {
+                        table: None,
+                        column: name,
+                    },
+                    sources,
+                });
+            }
+        } else if self.cursor.eat(Keyword::Insert) {
+            self.cursor.eat(Keyword::Into);
+            if let Some(table) = self.name() {
+                let names = self.column_list();
+                let outputs = if self.cursor.eat(Keyword::Values) {
+                    self.values()
+                } else {
+                    self.query()
+                };
+                let names = names
+                    .or_else(|| self.catalog.columns(&table).cloned())
+                    .unwrap_or_else(|| outputs.iter().map(|(name, _)| name.clone()).collect());
+                for (name, (_, sources)) in names.into_iter().zip(outputs) {
+                    columns.push(ColumnLineage {
+                        target: target(&table, name),
+                        sources,
+                    });
+                }
+                writes.push(table);
+            }
+        } else if self.cursor.eat(Keyword::Update) {
+            if let Some(table) = self.name() {
+                self.scopes.push(vec![]);
+                let alias = self.alias();
+                self.push_relation(table.clone(), alias, None);
+                // the target is read only where its columns are
+                self.reads.clear();
+                self.cursor.eat(Keyword::Set);
+                while let Some(name) = self.name() {
+                    self.cursor.eat_operator("=");
+                    let end = self.cursor.end_of(self.cursor.pos, |t| {
+                        t.is_operator(",") || t.is_keyword(Keyword::Where)
+                    });
+                    let sources = self.expression(self.cursor.pos, end);
+                    self.cursor.pos = end;
+                    columns.push(ColumnLineage {
+                        target: target(&table, name.object().clone()),
+                        sources,
+                    });
+                    if !self.cursor.eat_operator(",") {
+                        break;
+                    }
+                }
+                if self.cursor.eat(Keyword::Where) {
+                    self.condition();
+                }
+                writes.push(table);
+            }
+        } else if self.cursor.eat(Keyword::Delete) {
+            self.cursor.eat(Keyword::From);
+            if let Some(table) = self.name() {
+                self.scopes.push(vec![]);
+                let alias = self.alias();
+                self.push_relation(table.clone(), alias, None);
+                // the target is read only where its columns are
+                self.reads.clear();
+                if self.cursor.eat(Keyword::Where) {
+                    self.condition();
+                }
+                writes.push(table);
+            }
+        } else if self.cursor.eat(Keyword::Create) && self.cursor.eat(Keyword::View) {
+            if let Some(view) = self.name() {
+                let names = self.column_list();
+                self.cursor.eat(Keyword::As);
+                let outputs = self.query();
+                let names =
+                    names.unwrap_or_else(|| outputs.iter().map(|(name, _)| name.clone()).collect());
+                for (name, (_, sources)) in names.into_iter().zip(outputs) {
+                    columns.push(ColumnLineage {
+                        target: target(&view, name),
+                        sources,
+                    });
+                }
+                writes.push(view);
+            }
+        }
+
+        Lineage {
+            reads: self.reads,
+            writes,
+            columns,
+        }
+    }
+
+    /// Query expression: terms joined by UNION, EXCEPT or INTERSECT
+    fn query(&mut self) -> Vec<Output> {
+        let mut outputs = self.query_term();
+        while self.cursor.eat(Keyword::Union)
+            || self.cursor.eat(Keyword::Except)
+            || self.cursor.eat(Keyword::Intersect)
+        {
+            let _ = self.cursor.eat(Keyword::All) || self.cursor.eat(Keyword::Distinct);
+            if self.cursor.eat(Keyword::Corresponding) && self.cursor.eat(Keyword::By) {
+                self.column_list();
+            }
+            for ((_, sources), (_, more)) in outputs.iter_mut().zip(self.query_term()) {
+                extend_unique(sources, more);
+            }
+        }
+        if self.cursor.eat(Keyword::Order) {
+            self.cursor.pos = self.cursor.end_of(self.cursor.pos, |_| false);
+        }
+        outputs
+    }
+
+    fn query_term(&mut self) -> Vec<Output> {
+        if self.cursor.eat_operator("(") {
+            let outputs = self.query();
+            self.cursor.eat_operator(")");
+            return outputs;
+        }
+        if !self.cursor.eat(Keyword::Select) {
+            return vec![];
+        }
+        let _ = self.cursor.eat(Keyword::Distinct) || self.cursor.eat(Keyword::All);
+
+        // select items are resolved once FROM has built the scope
+        let mut items = vec![];
+        loop {
+            let end = self.cursor.end_of(self.cursor.pos, |t| {
+                t.is_operator(",") || t.is_keyword(Keyword::From) || ends_clause(t)
+            });
+            items.push(self.cursor.pos..end);
+            self.cursor.pos = end;
+            if !self.cursor.eat_operator(",") {
+                break;
+            }
+        }
+
+        self.scopes.push(vec![]);
+        if self.cursor.eat(Keyword::From) {
+            self.table_references();
+        }
+        if self.cursor.eat(Keyword::Where) {
+            self.condition();
+        }
+        if self.cursor.eat(Keyword::Group) {
+            self.cursor.eat(Keyword::By);
+            self.condition();
+        }
+        if self.cursor.eat(Keyword::Having) {
+            self.condition();
+        }
+
+        let end = self.cursor.pos;
+        let mut outputs = vec![];
+        for item in items {
+            outputs.extend(self.select_item(item.start, item.end));
+        }
+        self.cursor.pos = end;
+        self.scopes.pop();
+        outputs
+    }
+
+    fn select_item(&mut self, start: usize, end: usize) -> Vec<Output> {
+        let tokens = &self.cursor.tokens[start..end];
+        if let [star] = tokens {
+            if star.is_operator("*") {
+                let scope = self.scopes.last().into_iter().flatten();
+                return scope.flat_map(Relation::star).collect();
+            }
+        }
+        if let [name, dot, star] = tokens {
+            if dot.is_operator(".") && star.is_operator("*") {
+                let name = identifier(name);
+                let scope = self.scopes.last().into_iter().flatten();
+                return scope
+                    .filter(|relation| relation.name == name)
+                    .flat_map(Relation::star)
+                    .collect();
+            }
+        }
+
+        let (expression_end, alias) = match tokens {
+            [.., as_, alias] if as_.is_keyword(Keyword::As) => (end - 2, Some(identifier(alias))),
+            [_, .., operand, alias] if alias.is_name() && ends_operand(operand) => {
+                (end - 1, Some(identifier(alias)))
+            }
+            _ => (end, None),
+        };
+        if expression_end <= start {
+            return vec![];
+        }
+        let sources = self.expression(start, expression_end);
+        let name = alias
+            .or_else(|| self.single_name(start, expression_end))
+            .unwrap_or_else(|| {
+                let span = self.cursor.tokens[start].span.start
+                    ..self.cursor.tokens[expression_end - 1].span.end;
+                Identifier::Delimited(self.src[span].to_string())
+            });
+        vec![(name, sources)]
+    }
+
+    /// Last part of an expression which is just a column name
+    fn single_name(&self, start: usize, end: usize) -> Option<Identifier> {
+        let tokens = &self.cursor.tokens[start..end];
+        let names = tokens.iter().step_by(2).all(|t| t.is_name());
+        let dots = tokens.iter().skip(1).step_by(2).all(|t| t.is_operator("."));
+        if names && dots && tokens.len() % 2 == 1 {
+            return Some(identifier(&tokens[tokens.len() - 1]));
+        }
+        None
+    }
+
+    /// Columns used by the tokens, subqueries included
+    fn expression(&mut self, start: usize, end: usize) -> Vec<ColumnRef> {
+        let mut sources = vec![];
+        let mut i = start;
+        while i < end {
+            let token = &self.cursor.tokens[i];
+            let subquery = token.is_operator("(")
+                && self
+                    .cursor
+                    .tokens
+                    .get(i + 1)
+                    .is_some_and(|t| t.is_keyword(Keyword::Select));
+            let type_name = i > start && self.cursor.tokens[i - 1].is_keyword(Keyword::As);
+
+            if subquery {
+                self.cursor.pos = i + 1;
+                for (_, more) in self.query() {
+                    extend_unique(&mut sources, more);
+                }
+                i = self.cursor.end_of(i + 1, |_| false) + 1;
+            } else if token.is_name() && !type_name {
+                self.cursor.pos = i;
+                let name = self.name();
+                let function = self.cursor.at_operator("(");
+                i = self.cursor.pos;
+                if let (Some(name), false) = (name, function) {
+                    let more = self.resolve(&name);
+                    extend_unique(&mut sources, more);
+                }
+            } else {
+                i += 1;
+            }
+        }
+        sources
+    }
+
+    /// Search condition, only its subqueries and columns read matter
+    fn condition(&mut self) {
+        let end = self.cursor.end_of(self.cursor.pos, |t| {
+            ends_clause(t)
+                || t.is_operator(",")
+                || t.is_keyword(Keyword::Join)
+                || JOIN_WORDS.iter().any(|&keyword| t.is_keyword(keyword))
+        });
+        self.expression(self.cursor.pos, end);
+        self.cursor.pos = end;
+    }
+
+    fn table_references(&mut self) {
+        loop {
+            self.table_reference();
+            if !self.cursor.eat_operator(",") {
+                break;
+            }
+        }
+    }
+
+    fn table_reference(&mut self) {
+        self.table_primary();
+        loop {
+            let start = self.cursor.pos;
+            while JOIN_WORDS.iter().any(|&keyword| self.cursor.eat(keyword)) {}
+            if !self.cursor.eat(Keyword::Join) {
+                self.cursor.pos = start;
+                break;
+            }
+            self.table_primary();
+            if self.cursor.eat(Keyword::On) {
+                self.condition();
+            } else if self.cursor.eat(Keyword::Using) {
+                self.column_list();
+            }
+        }
+    }
+
+    fn table_primary(&mut self) {
+        if self.cursor.at_operator("(") {
+            self.cursor.pos += 1;
+            if self.cursor.at(Keyword::Select) {
+                let outputs = self.query();
+                self.cursor.eat_operator(")");
+                let alias = self
+                    .alias()
+                    .unwrap_or_else(|| Identifier::Delimited("subquery".to_string()));
+                let outputs = rename(outputs, self.column_list());
+                self.scope().push(Relation {
+                    name: alias,
+                    table: None,
+                    columns: Some(outputs),
+                });
+            } else {
+                self.table_reference();
+                self.cursor.eat_operator(")");
+            }
+            return;
+        }
+
+        if let Some(name) = self.name() {
+            let alias = self.alias();
+            let renamed = self.column_list();
+            self.push_relation(name, alias, renamed);
+        }
+    }
+
+    fn push_relation(
+        &mut self,
+        name: QualifiedName,
+        alias: Option<Identifier>,
+        renamed: Option<Vec<Identifier>>,
+    ) {
+        let correlation = alias.unwrap_or_else(|| name.object().clone());
+        let view = self
+            .catalog
+            .view_query(&name)
+            .filter(|_| self.views < MAX_VIEW_DEPTH);
+        let relation = match view {
+            Some(query) => Relation {
+                name: correlation,
+                table: None,
+                columns: Some(rename(self.view_outputs(query), renamed)),
+            },
+            None => {
+                self.add_read(&name);
+                let columns = self.catalog.columns(&name).map(|columns| {
+                    let outputs = columns
+                        .iter()
+                        .map(|column| {
+                            let source = ColumnRef {
+                                table: Some(name.clone()),
+                                column: column.clone(),
+                            };
+                            (column.clone(), vec![source])
+                        })
+                        .collect();
+                    rename(outputs, renamed)
+                });
+                Relation {
+                    name: correlation,
+                    table: Some(name),
+                    columns,
+                }
+            }
+        };
+        self.scope().push(relation);
+    }
+
+    fn view_outputs(&mut self, query: &str) -> Vec<Output> {
+        let (tokens, errors) = tokenize(query);
+        if !errors.is_empty() {
+            return vec![];
+        }
+        let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+        let mut view = Analyzer::new(query, &tokens, self.catalog, self.views + 1);
+        let outputs = view.query();
+        for table in &view.reads {
+            self.add_read(table);
+        }
+        outputs
+    }
+
+    /// Sources of a column reference, searched from the innermost scope.
+    /// An unqualified column of several unknown tables has no table.
+    fn resolve(&mut self, name: &QualifiedName) -> Vec<ColumnRef> {
+        let column = name.object().clone();
+        let qualifier = name.qualifier();
+
+        let mut found = None;
+        for (depth, scope) in self.scopes.iter().enumerate().rev() {
+            let candidates: Vec<usize> = match &qualifier {
+                Some(qualifier) => (0..scope.len())
+                    .filter(|&i| scope[i].name == *qualifier.object())
+                    .collect(),
+                None => {
+                    let known: Vec<usize> = (0..scope.len())
+                        .filter(|&i| scope[i].has(&column) == Some(true))
+                        .collect();
+                    let unknown: Vec<usize> = (0..scope.len())
+                        .filter(|&i| scope[i].has(&column).is_none())
+                        .collect();
+                    match (known.len(), unknown.len()) {
+                        (0, 0) => vec![],
+                        (1, _) | (0, 1) => [known, unknown].concat(),
+                        _ => {
+                            return vec![ColumnRef {
+                                table: None,
+                                column,
+                            }]
+                        }
+                    }
+                }
+            };
+            if let Some(&i) = candidates.first() {
+                found = Some((depth, i));
+                break;
+            }
+        }
+
+        match found {
+            Some((depth, i)) => {
+                let relation = &self.scopes[depth][i];
+                let sources = relation.sources(&column);
+                if let Some(table) = relation.table.clone() {
+                    self.add_read(&table);
+                }
+                sources
+            }
+            None => vec![ColumnRef {
+                table: qualifier,
+                column,
+            }],
+        }
+    }
+
+    fn values(&mut self) -> Vec<Output> {
+        let mut outputs: Vec<Output> = vec![];
+        while self.cursor.eat_operator("(") {
+            let mut index = 0;
+            loop {
+                let end = self.cursor.end_of(self.cursor.pos, |t| t.is_operator(","));
+                let sources = self.expression(self.cursor.pos, end);
+                self.cursor.pos = end;
+                match outputs.get_mut(index) {
+                    Some((_, known)) => extend_unique(known, sources),
+                    None => {
+                        let name = Identifier::Delimited(format!("{}", index + 1));
+                        outputs.push((name, sources));
+                    }
+                }
+                index += 1;
+                if !self.cursor.eat_operator(",") {
+                    break;
+                }
+            }
+            self.cursor.eat_operator(")");
+            if !self.cursor.eat_operator(",") {
+                break;
+            }
+        }
+        outputs
+    }
+
+    fn scope(&mut self) -> &mut Vec<Relation> {
+        if self.scopes.is_empty() {
+            self.scopes.push(vec![]);
+        }
+        self.scopes.last_mut().unwrap()
+    }
+
+    fn add_read(&mut self, table: &QualifiedName) {
+        if !self.reads.contains(table) {
+            self.reads.push(table.clone());
+        }
+    }
+
+    /// `name` or `a.b.name`
+    fn name(&mut self) -> Option<QualifiedName> {
+        let mut parts = vec![];
+        while let Some(token) = self.cursor.peek().filter(|t| t.is_name()) {
+            parts.push(identifier(token));
+            self.cursor.pos += 1;
+            let dotted = self.cursor.at_operator(".")
+                && self
+                    .cursor
+                    .tokens
+                    .get(self.cursor.pos + 1)
+                    .is_some_and(|t| t.is_name());
+            if !dotted {
+                break;
+            }
+            self.cursor.pos += 1;
+        }
+        QualifiedName::new(parts).ok()
+    }
+
+    /// `AS name` or a bare name
+    fn alias(&mut self) -> Option<Identifier> {
+        self.cursor.eat(Keyword::As);
+        let token = self.cursor.peek().filter(|t| t.is_name())?;
+        self.cursor.pos += 1;
+        Some(identifier(token))
+    }
+
+    /// `(a, b, c)`
+    fn column_list(&mut self) -> Option<Vec<Identifier>> {
+        if !self.cursor.at_operator("(") {
+            return None;
+        }
+        self.cursor.pos += 1;
+        let mut names = vec![];
+        while let Some(name) = self.name() {
+            names.push(name.object().clone());
+            if !self.cursor.eat_operator(",") {
+                break;
+            }
+        }
+        self.cursor.pos = self.cursor.end_of(self.cursor.pos, |_| false);
+        self.cursor.eat_operator(")");
+        Some(names)
+    }
+}
+
+/// Words between two table references, before `JOIN`
+const JOIN_WORDS: &[Keyword] = &[
+    Keyword::Natural,
+    Keyword::Inner,
+    Keyword::Left,
+    Keyword::Right,
+    Keyword::Full,
+    Keyword::Outer,
+    Keyword::Cross,
+    Keyword::Union,
+];
+
+fn ends_clause(token: &Token) -> bool {
+    token.is_operator(";")
+        || [
+            Keyword::Where,
+            Keyword::Group,
+            Keyword::Having,
+            Keyword::Order,
+            Keyword::Union,
+            Keyword::Except,
+            Keyword::Intersect,
+        ]
+        .iter()
+        .any(|&keyword| token.is_keyword(keyword))
+}
+
+fn ends_operand(token: &Token) -> bool {
+    token.is_name()
+        || token.kind.is_literal()
+        || token.is_operator(")")
+        || token.is_keyword(Keyword::End)
+}
+
+/// Name of the token, also when it isn't a valid identifier:
+/// a bad delimited one by its body, a reserved word folded to upper case
+fn identifier(token: &Token) -> Identifier {
+    Identifier::parse(token.text).unwrap_or_else(|_| {
+        let quoted = token.text.len() >= 2 && token.kind == TokenKind::DelimitedIdentifier;
+        if quoted {
+            Identifier::Delimited(token.text[1..token.text.len() - 1].to_string())
+        } else {
+            Identifier::Delimited(token.text.to_uppercase())
+        }
+    })
+}
+
+/// Apply a derived column list, `t (x, y)`
+fn rename(outputs: Vec<Output>, names: Option<Vec<Identifier>>) -> Vec<Output> {
+    match names {
+        Some(names) => names
+            .into_iter()
+            .zip(outputs)
+            .map(|(name, (_, sources))| (name, sources))
+            .collect(),
+        None => outputs,
+    }
+}
+
+fn extend_unique(sources: &mut Vec<ColumnRef>, more: Vec<ColumnRef>) {
+    for source in more {
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+}
+
+#[cfg(test)]
+fn describe(lineage: &Lineage) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let name = |column: &ColumnRef| match &column.table {
+        Some(table) => format!("{}.{}", table, column.column),
+        None => column.column.to_string(),
+    };
+    let columns = lineage
+        .columns
+        .iter()
+        .map(|c| {
+            let sources: Vec<String> = c.sources.iter().map(name).collect();
+            format!("{} <- {}", name(&c.target), sources.join(" "))
+        })
+        .collect();
+    let tables = |names: &[QualifiedName]| names.iter().map(|n| n.to_string()).collect();
+    (tables(&lineage.reads), tables(&lineage.writes), columns)
+}
+
+#[test]
+fn lineage_of_queries() {
+    let catalog = Catalog::new().table("u", &["id", "name"]).unwrap();
+    let src = "SELECT t.a, b + 1 AS c, x.total, name \
+               FROM t JOIN u ON t.id = u.id \
+               LEFT JOIN (SELECT id, SUM(amt) total FROM w GROUP BY id) x ON x.id = t.id \
+               WHERE t.k IN (SELECT k FROM z)";
+    let (reads, writes, columns) = describe(&lineage(src, &catalog).unwrap()[0]);
+    assert_eq!(reads, vec!["t", "u", "w", "z"]);
+    assert!(writes.is_empty());
+    assert_eq!(
+        columns,
+        vec!["a <- t.a", "c <- t.b", "total <- w.amt", "name <- u.name"]
+    );
+
+    let (_, _, columns) =
+        describe(&lineage("SELECT * FROM u, (SELECT a FROM t) s (b)", &catalog).unwrap()[0]);
+    assert_eq!(columns, vec!["id <- u.id", "name <- u.name", "b <- t.a"]);
+}
+
+#[test]
+fn lineage_of_writes_and_views() {
+    let catalog = Catalog::new()
+        .table("orders", &["id", "amount", "customer"])
+        .unwrap()
+        .view(
+            "big",
+            "SELECT id, amount * 2 AS doubled FROM orders WHERE amount > 100",
+        )
+        .unwrap();
+    let src = "INSERT INTO report (oid, total) SELECT b.id, b.doubled FROM big b;\n\
+               UPDATE orders SET amount = amount + (SELECT MAX(fee) FROM fees) WHERE id = 1;\n\
+               DELETE FROM orders;\n\
+               CREATE VIEW per_customer AS SELECT customer, COUNT(*) FROM orders GROUP BY customer";
+    let statements: Vec<_> = lineage(src, &catalog)
+        .unwrap()
+        .iter()
+        .map(describe)
+        .collect();
+
+    assert_eq!(statements[0].0, vec!["orders"]);
+    assert_eq!(statements[0].1, vec!["report"]);
+    assert_eq!(
+        statements[0].2,
+        vec!["report.oid <- orders.id", "report.total <- orders.amount"]
+    );
+
+    assert_eq!(statements[1].0, vec!["orders", "fees"]);
+    assert_eq!(statements[1].1, vec!["orders"]);
+    assert_eq!(
+        statements[1].2,
+        vec!["orders.amount <- orders.amount fees.fee"]
+    );
+
+    assert!(statements[2].0.is_empty());
+    assert_eq!(statements[2].1, vec!["orders"]);
+
+    assert_eq!(
+        statements[3].2,
+        vec![
+            "per_customer.customer <- orders.customer",
+            "per_customer.\"COUNT(*)\" <- "
+        ]
+    );
+}
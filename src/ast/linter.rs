@@ -0,0 +1,608 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::flagger::{flag_tokens, Level};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, Token, TokenKind};
+use crate::util::json;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// How much a diagnostic matters, ordered from the mildest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// `info`, `warning` or `error`; `off` disables a rule and gives `None`
+    pub fn parse(name: &str) -> Result<Option<Severity>, &'static str> {
+        match name {
+            "off" => Ok(None),
+            "info" => Ok(Some(Severity::Info)),
+            "warning" => Ok(Some(Severity::Warning)),
+            "error" => Ok(Some(Severity::Error)),
+            _ => Err("lint: unknown severity"),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: &'static str,
+    /// byte range in the source
+    pub span: Range<usize>,
+}
+
+/// One `;`-separated statement as seen by the rules
+pub struct Statement<'a> {
+    /// significant tokens, without whitespace and comments
+    pub tokens: Vec<Token<'a>>,
+    /// parenthesis depth before each token
+    pub depths: Vec<usize>,
+    pub dialect: &'a dyn Dialect,
+}
+
+impl<'a> Statement<'a> {
+    fn new(tokens: Vec<Token<'a>>, dialect: &'a dyn Dialect) -> Self {
+        let mut depth = 0;
+        let depths = tokens
+            .iter()
+            .map(|token| {
+                if token.is_operator(")") {
+                    depth = depth.max(1) - 1;
+                }
+                let before = depth;
+                if token.is_operator("(") {
+                    depth += 1;
+                }
+                before
+            })
+            .collect();
+        Statement {
+            tokens,
+            depths,
+            dialect,
+        }
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        match (self.tokens.first(), self.tokens.last()) {
+            (Some(first), Some(last)) => first.span.start..last.span.end,
+            _ => 0..0,
+        }
+    }
+
+    pub fn is_keyword(&self, i: usize, keyword: Keyword) -> bool {
+        self.tokens.get(i).is_some_and(|t| t.is_keyword(keyword))
+    }
+
+    /// Index of the `)` closing the `(` at `open`, or the last token
+    pub fn closing(&self, open: usize) -> usize {
+        (open + 1..self.tokens.len())
+            .find(|&i| self.depths[i] == self.depths[open] && self.tokens[i].is_operator(")"))
+            .unwrap_or(self.tokens.len() - 1)
+    }
+
+    /// Indexes from `start` up to the end of the enclosing parentheses,
+    /// none when `start` is past the last token
+    fn same_level(&self, start: usize) -> impl Iterator<Item = usize> + '_ {
+        let depth = self.depths.get(start).copied().unwrap_or(0);
+        (start..self.tokens.len())
+            .take_while(move |&i| {
+                self.depths[i] >= depth
+                    && !(self.depths[i] == depth && self.tokens[i].is_operator(")"))
+            })
+            .filter(move |&i| self.depths[i] == depth)
+    }
+}
+
+/// Check over one statement, reports spans with messages
+pub trait Rule {
+    /// Name used in diagnostics and in the configuration
+    fn name(&self) -> &'static str;
+    fn severity(&self) -> Severity;
+    fn check(&self, statement: &Statement) -> Vec<(Range<usize>, &'static str)>;
+}
+
+/// Rule severities of a project, read from a JSON file like
+/// `{"dialect": "postgresql", "rules": {"missing-where": "error", "non-portable": "off"}}`.
+/// Names of project rules added with `Linter::with_rule` can be
+/// configured too, so any rule name is accepted.
+#[derive(Clone)]
+pub struct LintConfig {
+    pub dialect: &'static dyn Dialect,
+    /// severity per rule name, `None` turns the rule off
+    pub rules: Vec<(String, Option<Severity>)>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            dialect: dialect::DEFAULT,
+            rules: vec![],
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn parse(src: &str) -> Result<LintConfig, &'static str> {
+        let document = json::parse(src)?;
+        let mut config = LintConfig::default();
+        if let Some(name) = document.get("dialect") {
+            config.dialect = name
+                .as_str()
+                .and_then(dialect::by_name)
+                .ok_or("lint: unknown dialect")?;
+        }
+        match document.get("rules") {
+            Some(json::JsonValue::Object(rules)) => {
+                for (name, severity) in rules {
+                    let severity = severity.as_str().ok_or("lint: unknown severity")?;
+                    config
+                        .rules
+                        .push((name.clone(), Severity::parse(severity)?));
+                }
+            }
+            Some(_) => return Err("lint: `rules` must be an object"),
+            None => {}
+        }
+        Ok(config)
+    }
+
+    fn severity(&self, rule: &dyn Rule) -> Option<Severity> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(name, _)| name == rule.name())
+            .map_or(Some(rule.severity()), |(_, severity)| *severity)
+    }
+}
+
+pub struct Linter {
+    rules: Vec<(Box<dyn Rule>, Severity)>,
+    dialect: &'static dyn Dialect,
+    /// configured severities, for the rules added later
+    configured: LintConfig,
+}
+
+impl Linter {
+    /// Built-in rules with the severities of the configuration
+    pub fn new(config: &LintConfig) -> Self {
+        let rules = builtin_rules()
+            .into_iter()
+            .filter_map(|rule| {
+                let severity = config.severity(rule.as_ref())?;
+                Some((rule, severity))
+            })
+            .collect();
+        Linter {
+            rules,
+            dialect: config.dialect,
+            configured: config.clone(),
+        }
+    }
+
+    /// Add a project specific rule, with its configured severity
+    pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Self {
+        if let Some(severity) = self.configured.severity(rule.as_ref()) {
+            self.rules.push((rule, severity));
+        }
+        self
+    }
+
+    /// Diagnostics of every statement, ordered by position.
+    /// Lexical errors are reported by the `syntax` rule.
+    pub fn lint(&self, src: &str) -> Vec<Diagnostic> {
+        let (tokens, errors) = tokenize_with(src, self.dialect);
+        let mut diagnostics: Vec<Diagnostic> = errors
+            .into_iter()
+            .map(|error| Diagnostic {
+                rule: "syntax",
+                severity: Severity::Error,
+                message: error.message,
+                span: error.span,
+            })
+            .collect();
+
+        let mut significant = tokens
+            .into_iter()
+            .filter(|t| !t.kind.is_trivia())
+            .peekable();
+        while significant.peek().is_some() {
+            let mut tokens = vec![];
+            for token in significant.by_ref() {
+                if token.is_operator(";") {
+                    break;
+                }
+                tokens.push(token);
+            }
+            if tokens.is_empty() {
+                continue;
+            }
+            let statement = Statement::new(tokens, self.dialect);
+            for (rule, severity) in &self.rules {
+                for (span, message) in rule.check(&statement) {
+                    diagnostics.push(Diagnostic {
+                        rule: rule.name(),
+                        severity: *severity,
+                        message,
+                        span,
+                    });
+                }
+            }
+        }
+
+        diagnostics.sort_by_key(|d| (d.span.start, d.span.end));
+        diagnostics
+    }
+}
+
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(SelectStarInView),
+        Box::new(ImplicitCrossJoin),
+        Box::new(NotInNullable),
+        Box::new(CompareNull),
+        Box::new(MissingWhere),
+        Box::new(NonPortable),
+    ]
+}
+
+/// `CREATE VIEW v AS SELECT *`, the view changes with the tables
+pub struct SelectStarInView;
+
+impl Rule for SelectStarInView {
+    fn name(&self) -> &'static str {
+        "select-star-in-view"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        if !s.is_keyword(0, Keyword::Create) || !s.is_keyword(1, Keyword::View) {
+            return vec![];
+        }
+        let mut found = vec![];
+        for select in (0..s.tokens.len()).filter(|&i| s.is_keyword(i, Keyword::Select)) {
+            for i in s.same_level(select + 1) {
+                if s.is_keyword(i, Keyword::From) {
+                    break;
+                }
+                let prev = &s.tokens[i - 1];
+                let starts_item = prev.is_operator(",")
+                    || prev.is_operator(".")
+                    || i == select + 1
+                    || i == select + 2
+                        && (s.is_keyword(select + 1, Keyword::Distinct)
+                            || s.is_keyword(select + 1, Keyword::All));
+                if s.tokens[i].is_operator("*") && starts_item {
+                    found.push((
+                        s.tokens[i].span.clone(),
+                        "`*` in a view: list the columns, the view changes when the tables do",
+                    ));
+                }
+            }
+        }
+        found
+    }
+}
+
+/// `FROM a, b`
+pub struct ImplicitCrossJoin;
+
+impl Rule for ImplicitCrossJoin {
+    fn name(&self) -> &'static str {
+        "implicit-cross-join"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        let mut found = vec![];
+        for from in (0..s.tokens.len()).filter(|&i| s.is_keyword(i, Keyword::From)) {
+            for i in s.same_level(from + 1) {
+                let ends_from = [
+                    Keyword::Where,
+                    Keyword::Group,
+                    Keyword::Having,
+                    Keyword::Order,
+                    Keyword::Union,
+                    Keyword::Except,
+                    Keyword::Intersect,
+                ]
+                .iter()
+                .any(|&keyword| s.is_keyword(i, keyword));
+                if ends_from {
+                    break;
+                }
+                if s.tokens[i].is_operator(",") {
+                    found.push((
+                        s.tokens[i].span.clone(),
+                        "implicit cross join: use JOIN ... ON",
+                    ));
+                }
+            }
+        }
+        found
+    }
+}
+
+/// `a NOT IN (SELECT b ...)` matches nothing once `b` is NULL.
+/// Without a catalog the column counts as nullable
+/// unless the subquery filters it with `IS NOT NULL`.
+pub struct NotInNullable;
+
+impl Rule for NotInNullable {
+    fn name(&self) -> &'static str {
+        "not-in-nullable"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        let mut found = vec![];
+        for not in 0..s.tokens.len() {
+            let subquery = s.is_keyword(not, Keyword::Not)
+                && s.is_keyword(not + 1, Keyword::In)
+                && s.tokens.get(not + 2).is_some_and(|t| t.is_operator("("))
+                && s.is_keyword(not + 3, Keyword::Select);
+            if !subquery {
+                continue;
+            }
+            let close = s.closing(not + 2);
+            let inner = &s.tokens[not + 3..close];
+            // subquery still being typed
+            if inner.len() < 2 {
+                continue;
+            }
+
+            // a single selected column, possibly qualified
+            let column: Vec<&str> = inner[1..]
+                .iter()
+                .take_while(|t| !t.is_keyword(Keyword::From))
+                .map(|t| t.text)
+                .collect();
+            let filtered = match column.as_slice() {
+                [name] | [_, ".", name] => inner.windows(4).any(|w| {
+                    w[0].text.eq_ignore_ascii_case(name)
+                        && w[1].is_keyword(Keyword::Is)
+                        && w[2].is_keyword(Keyword::Not)
+                        && w[3].is_keyword(Keyword::Null)
+                }),
+                _ => false,
+            };
+            if !filtered {
+                found.push((
+                    s.tokens[not].span.start..s.tokens[close].span.end,
+                    "NOT IN over a subquery which may return NULL matches no rows: use NOT EXISTS or filter IS NOT NULL",
+                ));
+            }
+        }
+        found
+    }
+}
+
+/// `a = NULL` is never true
+pub struct CompareNull;
+
+impl Rule for CompareNull {
+    fn name(&self) -> &'static str {
+        "compare-null"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        let update = s.is_keyword(0, Keyword::Update);
+        let mut clause = None;
+        let mut found = vec![];
+        for (i, token) in s.tokens.iter().enumerate() {
+            if let (0, TokenKind::Keyword(keyword)) = (s.depths[i], token.kind) {
+                if matches!(keyword, Keyword::Set | Keyword::Where) {
+                    clause = Some(keyword);
+                }
+            }
+            // `SET a = NULL` is an assignment
+            let assignment = update && s.depths[i] == 0 && clause == Some(Keyword::Set);
+            let comparison = ["=", "<>", "!="].iter().any(|op| token.is_operator(op));
+            let null =
+                (i > 0 && s.is_keyword(i - 1, Keyword::Null)) || s.is_keyword(i + 1, Keyword::Null);
+            if comparison && null && !(assignment && token.text == "=") {
+                found.push((
+                    token.span.clone(),
+                    "comparison with NULL is never true: use IS NULL or IS NOT NULL",
+                ));
+            }
+        }
+        found
+    }
+}
+
+/// UPDATE or DELETE of every row
+pub struct MissingWhere;
+
+impl Rule for MissingWhere {
+    fn name(&self) -> &'static str {
+        "missing-where"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        let message = if s.is_keyword(0, Keyword::Update) {
+            "UPDATE without WHERE changes every row"
+        } else if s.is_keyword(0, Keyword::Delete) {
+            "DELETE without WHERE removes every row"
+        } else {
+            return vec![];
+        };
+        if s.same_level(0).any(|i| s.is_keyword(i, Keyword::Where)) {
+            return vec![];
+        }
+        vec![(s.span(), message)]
+    }
+}
+
+/// Constructs outside of SQL-92, found by the flagger
+pub struct NonPortable;
+
+impl Rule for NonPortable {
+    fn name(&self) -> &'static str {
+        "non-portable"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+        flag_tokens(&s.tokens)
+            .into_iter()
+            .filter(|flag| flag.level == Level::Extension)
+            .filter_map(|flag| Some((flag.span?, flag.feature)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+fn rules_of(src: &str, config: &LintConfig) -> Vec<(&'static str, String)> {
+    Linter::new(config)
+        .lint(src)
+        .into_iter()
+        .map(|d| (d.rule, src[d.span].to_string()))
+        .collect()
+}
+
+#[test]
+fn lint_builtin_rules() {
+    let config = LintConfig::default();
+    let found = rules_of(
+        "CREATE VIEW v AS SELECT t.*, a FROM t, u WHERE b = NULL;\n\
+         SELECT a FROM t WHERE a NOT IN (SELECT b FROM u);\n\
+         SELECT a FROM t WHERE a NOT IN (SELECT u.b FROM u WHERE u.b IS NOT NULL);\n\
+         UPDATE t SET a = NULL;\n\
+         DELETE FROM t WHERE (a) <> NULL;\n\
+         SELECT a FROM t JOIN u ON t.id = u.id LIMIT 5",
+        &config,
+    );
+    assert_eq!(
+        found,
+        vec![
+            ("select-star-in-view", "*".to_string()),
+            ("implicit-cross-join", ",".to_string()),
+            ("compare-null", "=".to_string()),
+            ("not-in-nullable", "NOT IN (SELECT b FROM u)".to_string()),
+            ("missing-where", "UPDATE t SET a = NULL".to_string()),
+            ("compare-null", "<>".to_string()),
+            ("non-portable", "LIMIT".to_string()),
+        ]
+    );
+
+    assert!(rules_of(
+        "SELECT * FROM t; SELECT a FROM (SELECT a, b FROM t) x",
+        &config
+    )
+    .is_empty());
+}
+
+#[test]
+fn lint_config_and_custom_rules() {
+    struct NoDistinct;
+    impl Rule for NoDistinct {
+        fn name(&self) -> &'static str {
+            "no-distinct"
+        }
+        fn severity(&self) -> Severity {
+            Severity::Info
+        }
+        fn check(&self, s: &Statement) -> Vec<(Range<usize>, &'static str)> {
+            s.tokens
+                .iter()
+                .filter(|t| t.is_keyword(Keyword::Distinct))
+                .map(|t| (t.span.clone(), "DISTINCT"))
+                .collect()
+        }
+    }
+
+    let config = LintConfig::parse(
+        r#"{"dialect": "mysql", "rules": {"missing-where": "warning", "non-portable": "off"}}"#,
+    )
+    .unwrap();
+    let diagnostics = Linter::new(&config)
+        .with_rule(Box::new(NoDistinct))
+        .lint("DELETE FROM `t`; SELECT DISTINCT a FROM t");
+    let found: Vec<(&str, Severity)> = diagnostics.iter().map(|d| (d.rule, d.severity)).collect();
+    assert_eq!(
+        found,
+        vec![
+            ("missing-where", Severity::Warning),
+            ("no-distinct", Severity::Info)
+        ]
+    );
+
+    let found = |config: &str| {
+        let config = LintConfig::parse(config).unwrap();
+        Linter::new(&config)
+            .with_rule(Box::new(NoDistinct))
+            .lint("SELECT DISTINCT a FROM t")
+            .iter()
+            .map(|d| (d.rule, d.severity))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        found(r#"{"rules": {"no-distinct": "error"}}"#),
+        vec![("no-distinct", Severity::Error)]
+    );
+    assert!(found(r#"{"rules": {"no-distinct": "off"}}"#).is_empty());
+
+    let errors = Linter::new(&LintConfig::default()).lint("SELECT 'open");
+    assert_eq!(errors[0].rule, "syntax");
+
+    assert!(LintConfig::parse(r#"{"rules": {"compare-null": "loud"}}"#).is_err());
+    assert!(LintConfig::parse(r#"{"dialect": "oracle"}"#).is_err());
+}
+
+#[test]
+fn lint_truncated_statements() {
+    let linter = Linter::new(&LintConfig::default());
+    for src in [
+        "SELECT a FROM",
+        "CREATE VIEW v AS SELECT",
+        "SELECT a FROM t WHERE x NOT IN (SELECT",
+        "SELECT a FROM t WHERE x NOT IN (SELECT b",
+    ]
+    .iter()
+    {
+        assert!(linter.lint(src).iter().all(|d| d.rule != "not-in-nullable"));
+    }
+
+    // every prefix of a statement, as typed in an editor
+    let src = "CREATE VIEW v AS SELECT DISTINCT t.*, a FROM t, u \
+               WHERE a NOT IN (SELECT u.b FROM u WHERE u.b IS NOT NULL) AND b = NULL";
+    for (end, _) in src.char_indices() {
+        linter.lint(&src[..end]);
+    }
+}
@@ -1,20 +1,50 @@
 mod ast_node;
-mod parser;
+pub mod parser;
 
 mod action;
+// library API, nothing in the binary searches large trees yet
+#[allow(dead_code)]
+mod arena;
+pub mod borrowed;
+pub mod builder;
 mod column_name_list;
+pub mod completion;
+mod cst;
+pub mod ddl;
+pub mod dialect;
+mod extension;
 mod factor;
+pub mod fingerprint;
+pub mod flagger;
+pub mod formatter;
+pub mod generator;
+pub mod ide;
+mod identifier;
+mod json;
+mod keyword;
+mod lexer;
+pub mod lineage;
+pub mod linter;
 mod numeric_primary;
 mod paren;
+pub mod redact;
+pub mod render;
 mod reserved_word;
+pub mod schema_diff;
 mod select_list;
+mod selector;
 mod select_statement_single_row;
 mod select_sublist;
 mod set_quantifier;
 mod sign;
+pub mod stream;
+pub mod transpile;
+mod traverse;
+pub mod unparse;
 mod unsigned_value_specification;
 mod value_expression;
 mod value_expression_primary;
+mod visit;
 
 use std::ops::DerefMut;
 use std::rc::Rc;
@@ -1,4 +1,9 @@
 use crate::ast::ast_node::AstNode;
+use crate::ast::borrowed::significant_tokens;
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::extension::{find_extensions, ExtensionNode};
+use crate::ast::reserved_word as word;
+use std::ops::Range;
 
 pub struct Parser<TSource>
 where
@@ -6,39 +11,55 @@ where
 {
     pub source: TSource,
     pub nodes: Option<Vec<AstNode>>,
+    /// byte range of every node of `nodes` in the source
+    pub spans: Option<Vec<Range<usize>>>,
+    /// vendor extensions accepted by `dialect`
+    pub extensions: Option<Vec<ExtensionNode>>,
+    pub dialect: &'static dyn Dialect,
 }
 
 impl<TSource> Parser<TSource>
 where
     TSource: Into<String> + Copy,
 {
-    fn new(source: TSource) -> Self {
-        return Parser {
+    /// Parser for strict SQL-92
+    pub fn new(source: TSource) -> Self {
+        Parser::with_dialect(source, dialect::DEFAULT)
+    }
+
+    pub fn with_dialect(source: TSource, dialect: &'static dyn Dialect) -> Self {
+        Parser {
             source,
             nodes: Option::None,
-        };
+            spans: Option::None,
+            extensions: Option::None,
+            dialect,
+        }
     }
 
+    /// `TSource` is only `Into<String>`, so the source is copied once
+    /// here; the nodes copy every word anyway. Use
+    /// `borrowed::parse_borrowed` to parse without copying.
     pub fn translate_source_to_ast_nodes(&mut self) {
         let src_string: String = self.source.into();
-        let words = src_string.split(' ');
+        let tokens = significant_tokens(&src_string, self.dialect);
 
-        let nodes: Vec<AstNode> = words
-            .into_iter()
-            .map(|w| AstNode::new(String::from(w)))
-            .collect();
+        let nodes: Vec<AstNode> = tokens.iter().map(|t| AstNode::from(t.text)).collect();
+        let spans: Vec<Range<usize>> = tokens.iter().map(|t| t.span.clone()).collect();
 
+        self.extensions = Option::from(find_extensions(&tokens, self.dialect));
         self.nodes = Option::from(nodes);
+        self.spans = Option::from(spans);
     }
 
     pub fn exec_ast_node(&mut self) {
         let vec_ast_nodes = self.nodes.as_mut().unwrap();
 
         let root = vec_ast_nodes.get(0).unwrap();
-        let root_name: &str = &root.name;
+        let root_name = root.name_uppercase();
 
-        match root_name {
-            "select" => {}
+        match root_name.as_str() {
+            word::SELECT => {}
             _ => {}
         }
     }
@@ -50,4 +71,115 @@ fn parsing() {
     parser.translate_source_to_ast_nodes();
     let mut nodes = parser.nodes.clone();
     parser.exec_ast_node();
+
+    assert_eq!(parser.spans.unwrap()[2], 9..13);
+}
+
+#[test]
+fn parsing_with_dialect() {
+    use crate::ast::dialect::MySql;
+    use crate::ast::extension::Extension;
+
+    let mut parser = Parser::with_dialect("SELECT `a b` FROM t LIMIT 5", &MySql);
+    parser.translate_source_to_ast_nodes();
+
+    assert_eq!(parser.nodes.unwrap()[1].name, "`a b`");
+    let extensions = parser.extensions.unwrap();
+    assert_eq!(extensions.len(), 2);
+    assert_eq!(extensions[1].extension, Extension::Limit("5".to_string()));
+
+    let mut parser = Parser::new("SELECT `a b` FROM t LIMIT 5");
+    parser.translate_source_to_ast_nodes();
+    assert!(parser.extensions.unwrap().is_empty());
+}
+
+#[test]
+fn parsing_matches_borrowed() {
+    use crate::ast::borrowed::parse_borrowed;
+    use crate::ast::dialect::PostgreSql;
+
+    let src = "SELECT \"Name\", f(id) FROM t LIMIT 5; DELETE FROM t";
+    let ast = parse_borrowed(src, &PostgreSql);
+    let mut parser = Parser::with_dialect(src, &PostgreSql);
+    parser.translate_source_to_ast_nodes();
+
+    let names: Vec<&str> = ast.root.tokens().map(|node| node.name).collect();
+    let nodes = parser.nodes.unwrap();
+    assert_eq!(
+        nodes.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(),
+        names
+    );
+    let spans: Vec<Range<usize>> = ast.root.tokens().map(|node| node.span.clone()).collect();
+    assert_eq!(parser.spans.unwrap(), spans);
+    assert_eq!(parser.extensions.unwrap(), ast.extensions);
+}
+
+/// The parser before the lexer: words split on spaces, lowercased
+#[cfg(test)]
+fn split_on_spaces(source: &str) -> Vec<AstNode> {
+    source
+        .split(' ')
+        .map(|w| AstNode::new(w.to_lowercase()))
+        .collect()
+}
+
+/// Throughput of the parser before the lexer, the owned parse and the
+/// borrowed tree on a large script,
+/// run with `cargo test --release parser_bench -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn parser_bench() {
+    use crate::ast::borrowed::parse_borrowed;
+    use std::time::Instant;
+
+    let statements = [
+        "SELECT o.id, c.name, SUM(o.amount) AS total FROM orders AS o JOIN customer AS c ON c.id = o.customer WHERE o.placed >= DATE '2020-01-01' GROUP BY o.id, c.name;",
+        "INSERT INTO customer (id, name, city) VALUES (1, 'Ann''s shop', 'Oslo');",
+        "UPDATE orders SET amount = amount * 1.25, note = 'raised' WHERE customer IN (SELECT id FROM customer WHERE city = 'Oslo');",
+        "-- nightly cleanup\nDELETE FROM orders WHERE \"Status\" = 'void' /* keep open ones */;",
+        "CREATE TABLE line (id INTEGER PRIMARY KEY, orders INTEGER REFERENCES orders (id), qty SMALLINT NOT NULL, price NUMERIC(10, 2));",
+    ];
+    let mut script = String::new();
+    while script.len() < 8 * 1024 * 1024 {
+        for statement in statements.iter() {
+            script.push_str(statement);
+            script.push('\n');
+        }
+    }
+    let megabytes = script.len() as f64 / (1024.0 * 1024.0);
+    let rounds = 5;
+    let time = |parse: &dyn Fn() -> usize| {
+        // best of the rounds, the others are mostly noise of the machine
+        let mut nodes = 0;
+        let mut best = f64::MAX;
+        for _ in 0..rounds {
+            let started = Instant::now();
+            nodes = parse();
+            best = best.min(started.elapsed().as_secs_f64());
+        }
+        (nodes, best)
+    };
+
+    let (split_nodes, split_time) = time(&|| split_on_spaces(&script).len());
+    let (owned_nodes, owned_time) = time(&|| {
+        let mut parser = Parser::new(script.as_str());
+        parser.translate_source_to_ast_nodes();
+        parser.nodes.unwrap().len()
+    });
+    let (borrowed_nodes, borrowed_time) = time(&|| {
+        let ast = parse_borrowed(&script, dialect::DEFAULT);
+        ast.root.pre_order().count()
+    });
+
+    println!(
+        "{:.1} MiB: split on spaces {:.1} MiB/s ({} nodes), owned {:.1} MiB/s ({} nodes), \
+         borrowed tree {:.1} MiB/s ({} nodes)",
+        megabytes,
+        megabytes / split_time,
+        split_nodes,
+        megabytes / owned_time,
+        owned_nodes,
+        megabytes / borrowed_time,
+        borrowed_nodes
+    );
 }
@@ -0,0 +1,84 @@
+use crate::ast::cst::CstNode;
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::lexer::{tokenize_with, TokenKind};
+
+/// Text which replaces every literal
+pub const MARKER: &str = "?";
+
+/// Redact a statement in strict SQL-92
+pub fn redact(src: &str) -> String {
+    redact_with(src, dialect::DEFAULT)
+}
+
+/// Replace character, numeric, datetime, bit and hex literals by `MARKER`,
+/// keywords, identifiers, comments and layout stay as they are.
+/// Input the tree can't be built for is redacted token by token,
+/// an unterminated string is still a literal up to the end of the input.
+/// Any other unterminated token, such as a delimited identifier or a
+/// comment, runs to the end of the input too and is redacted whole.
+pub fn redact_with(src: &str, dialect: &dyn Dialect) -> String {
+    match CstNode::parse_with(src, dialect) {
+        Ok(mut tree) => {
+            redact_tree(&mut tree);
+            tree.to_string()
+        }
+        Err(_) => {
+            let (tokens, errors) = tokenize_with(src, dialect);
+            let unterminated = |span| errors.iter().any(|error| &error.span == span);
+            tokens
+                .iter()
+                .map(|token| {
+                    let hides_text = token.kind != TokenKind::Unknown && unterminated(&token.span);
+                    if token.kind.is_literal() || hides_text {
+                        MARKER
+                    } else {
+                        token.text
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Redact the literals of a tree in place.
+/// The prefix of a datetime literal (`DATE '...'`) is a keyword and stays.
+pub fn redact_tree(tree: &mut CstNode) {
+    for token in tree.tokens_mut() {
+        if token.kind.is_literal() {
+            token.set_text(MARKER);
+        }
+    }
+}
+
+#[test]
+fn redact_literals() {
+    let src = "SELECT name, 'Jane' -- who\nFROM t\n  WHERE born = DATE '1990-01-01' AND id IN (1, 2.5e3)\n  AND flags = B'0101' OR h = X'FF' OR n = N'x';";
+    assert_eq!(
+        redact(src),
+        "SELECT name, ? -- who\nFROM t\n  WHERE born = DATE ? AND id IN (?, ?)\n  AND flags = ? OR h = ? OR n = ?;"
+    );
+    assert_eq!(
+        redact_with(
+            "SELECT `a` FROM t WHERE b = \"secret\"",
+            &crate::ast::dialect::MySql
+        ),
+        "SELECT `a` FROM t WHERE b = ?"
+    );
+}
+
+#[test]
+fn redact_broken_input() {
+    assert_eq!(
+        redact("SELECT a FROM t WHERE b = 42 AND c = 'card 4111"),
+        "SELECT a FROM t WHERE b = ? AND c = ?"
+    );
+    assert_eq!(redact("SELECT # 'x'"), "SELECT # ?");
+    assert_eq!(
+        redact("SELECT \"a FROM t WHERE ssn = '123-45-6789'"),
+        "SELECT ?"
+    );
+    assert_eq!(
+        redact("SELECT a FROM t WHERE b = 1 /* ssn = '123-45-6789'"),
+        "SELECT a FROM t WHERE b = ? ?"
+    );
+}
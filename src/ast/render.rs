@@ -0,0 +1,237 @@
+use crate::ast::action::Action;
+use crate::ast::ast_node::AstNode;
+#[cfg(test)]
+use crate::ast::identifier::Identifier;
+use crate::ast::paren::{LPAREN, RPAREN};
+use crate::ast::reserved_word as word;
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::set_quantifier::SetQuantifier;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// What to print next to every node label
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub kinds: bool,
+    pub spans: bool,
+}
+
+/// Display tree shared by all renderers.
+/// Both `AstNode` and typed nodes are converted into it first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderNode {
+    pub label: String,
+    pub kind: &'static str,
+    pub span: Option<Range<usize>>,
+    pub children: Vec<RenderNode>,
+}
+
+impl RenderNode {
+    pub fn new(label: &str, kind: &'static str) -> Self {
+        RenderNode {
+            label: label.to_string(),
+            kind,
+            span: None,
+            children: vec![],
+        }
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn child(mut self, child: RenderNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn caption(&self, options: RenderOptions) -> String {
+        // structural nodes without own text are shown by kind
+        if self.label.is_empty() {
+            return self.kind.to_string() + &self.span_caption(options);
+        }
+        let mut caption = self.label.clone();
+        if options.kinds {
+            let _ = write!(caption, " [{}]", self.kind);
+        }
+        caption + &self.span_caption(options)
+    }
+
+    fn span_caption(&self, options: RenderOptions) -> String {
+        match (options.spans, &self.span) {
+            (true, Some(span)) => format!(" @{}..{}", span.start, span.end),
+            _ => String::new(),
+        }
+    }
+
+    /// Indented tree drawn with box characters
+    pub fn to_ascii(&self, options: RenderOptions) -> String {
+        let mut out = self.caption(options);
+        out.push('\n');
+        self.ascii_children(options, "", &mut out);
+        out
+    }
+
+    fn ascii_children(&self, options: RenderOptions, prefix: &str, out: &mut String) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let _ = writeln!(out, "{}{}{}", prefix, branch, child.caption(options));
+            child.ascii_children(options, &format!("{}{}", prefix, indent), out);
+        }
+    }
+
+    /// Graphviz digraph, nodes are numbered in document order
+    pub fn to_dot(&self, options: RenderOptions) -> String {
+        let mut out = String::from("digraph ast {\n    node [shape=box];\n");
+        let mut next_id = 0;
+        self.dot_node(options, &mut next_id, &mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    fn dot_node(&self, options: RenderOptions, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let _ = writeln!(
+            out,
+            "    n{} [label=\"{}\"];",
+            id,
+            dot_escape(&self.caption(options))
+        );
+        for child in &self.children {
+            let child_id = child.dot_node(options, next_id, out);
+            let _ = writeln!(out, "    n{} -> n{};", id, child_id);
+        }
+        id
+    }
+}
+
+fn dot_escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl From<&AstNode> for RenderNode {
+    fn from(node: &AstNode) -> Self {
+        let mut render = RenderNode::new(&node.name, "AstNode");
+        render.children = node.children().iter().map(RenderNode::from).collect();
+        render
+    }
+}
+
+impl From<&Action> for RenderNode {
+    fn from(action: &Action) -> Self {
+        let (name, columns) = match action {
+            Action::SELECT => (word::SELECT, None),
+            Action::DELETE => (word::DELETE, None),
+            Action::USAGE => (word::USAGE, None),
+            Action::INSERT(_, list, _) => (word::INSERT, Some(list)),
+            Action::UPDATE(_, list, _) => (word::UPDATE, Some(list)),
+            Action::REFERENCES(_, list, _) => (word::REFERENCES, Some(list)),
+        };
+        let mut render = RenderNode::new(name, "Action");
+        if let Some(columns) = columns {
+            let mut list = RenderNode::new("", "ColumnNameList");
+            for column in columns {
+                list = list.child(RenderNode::new(&column.to_string(), "ColumnName"));
+            }
+            render = render
+                .child(RenderNode::new(LPAREN, "LParen"))
+                .child(list)
+                .child(RenderNode::new(RPAREN, "RParen"));
+        }
+        render
+    }
+}
+
+impl From<&SetQuantifier> for RenderNode {
+    fn from(quantifier: &SetQuantifier) -> Self {
+        match quantifier {
+            SetQuantifier::DISTINCT => RenderNode::new(word::DISTINCT, "SetQuantifier"),
+            SetQuantifier::ALL => RenderNode::new(word::ALL, "SetQuantifier"),
+        }
+    }
+}
+
+impl From<&SelectList> for RenderNode {
+    fn from(list: &SelectList) -> Self {
+        match list {
+            SelectList::ASTERISK => RenderNode::new("*", "SelectList"),
+            SelectList::SUBLISTS(sublists) => {
+                sublists
+                    .iter()
+                    .fold(RenderNode::new("", "SelectList"), |list, sublist| {
+                        list.child(RenderNode::new(&sublist.0.to_string(), "SelectSublist"))
+                    })
+            }
+        }
+    }
+}
+
+impl From<&SelectStatementSingleRow> for RenderNode {
+    fn from(stmt: &SelectStatementSingleRow) -> Self {
+        let (action, quantifier, list) = stmt;
+        let mut render = RenderNode::new("", "SelectStatementSingleRow").child(action.into());
+        if let Some(quantifier) = quantifier {
+            render = render.child(quantifier.into());
+        }
+        render.child(list.into())
+    }
+}
+
+#[test]
+fn render_ast_node_ascii() {
+    let mut update_node = AstNode::from("UPDATE");
+    let mut lp_node = AstNode::from("(");
+    lp_node.add(AstNode::from("an"));
+    update_node.add(lp_node).add(AstNode::from(")"));
+
+    let ascii = RenderNode::from(&update_node).to_ascii(RenderOptions::default());
+    assert_eq!(ascii, "UPDATE\n├── (\n│   └── an\n└── )\n");
+}
+
+#[test]
+fn render_typed_ast_with_kinds_and_spans() {
+    let action = Action::UPDATE(
+        LPAREN.to_string(),
+        vec![Identifier::Regular("bob".to_string())],
+        RPAREN.to_string(),
+    );
+    let render = RenderNode::from(&action).with_span(0..12);
+    let options = RenderOptions {
+        kinds: true,
+        spans: true,
+    };
+
+    let ascii = render.to_ascii(options);
+    assert!(ascii.starts_with("UPDATE [Action] @0..12\n"));
+    assert!(ascii.contains("├── ColumnNameList\n│   └── bob [ColumnName]\n"));
+
+    let dot = render.to_dot(RenderOptions::default());
+    assert!(dot.starts_with("digraph ast {\n"));
+    assert!(dot.contains("    n0 [label=\"UPDATE\"];\n"));
+    assert!(dot.contains("    n2 -> n3;\n"));
+}
+
+#[test]
+fn dot_escapes_labels() {
+    let dot = RenderNode::new("\"a\\b\"", "AstNode").to_dot(RenderOptions::default());
+    assert!(dot.contains("[label=\"\\\"a\\\\b\\\"\"]"));
+}
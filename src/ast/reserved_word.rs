@@ -49,6 +49,7 @@ pub const CONSTRAINTS: &str = "CONSTRAINTS";
 pub const CONTINUE: &str = "CONTINUE";
 pub const CONVERT: &str = "CONVERT";
 pub const CORRESPONDING: &str = "CORRESPONDING";
+pub const COUNT: &str = "COUNT";
 pub const CREATE: &str = "CREATE";
 pub const CROSS: &str = "CROSS";
 pub const CURRENT: &str = "CURRENT";
@@ -137,7 +138,7 @@ pub const ISOLATION: &str = "ISOLATION";
 pub const JOIN: &str = "JOIN";
 
 // K
-pub const KEY: &str = "KEY ";
+pub const KEY: &str = "KEY";
 
 // L
 pub const LANGUAGE: &str = "LANGUAGE";
@@ -237,7 +238,7 @@ pub const TIMEZONE_HOUR: &str = "TIMEZONE_HOUR";
 pub const TIMEZONE_MINUTE: &str = "TIMEZONE_MINUTE";
 pub const TO: &str = "TO";
 pub const TRAILING: &str = "TRAILING";
-pub const TRANSACTION: &str = "V";
+pub const TRANSACTION: &str = "TRANSACTION";
 pub const TRANSLATE: &str = "TRANSLATE";
 pub const TRANSLATION: &str = "TRANSLATION";
 pub const TRIM: &str = "TRIM";
@@ -269,7 +270,7 @@ pub const WORK: &str = "WORK";
 pub const WRITE: &str = "WRITE";
 
 // Y
-pub const YEAR: &str = "YEAR ";
+pub const YEAR: &str = "YEAR";
 
 // Z
 pub const ZONE: &str = "ZONE";
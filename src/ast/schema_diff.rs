@@ -0,0 +1,584 @@
+use crate::ast::ddl::Constraint;
+use crate::ast::ddl::{
+    parse_schema, ColumnDefinition, DomainDefinition, Schema, TableDefinition, ViewDefinition,
+};
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize, Token, TokenKind};
+use crate::ast::unparse::ToSql;
+
+/// One statement of a migration.
+/// A step whose `sql` is a comment can't be expressed in SQL-92
+/// and is left to do by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub sql: String,
+    /// drops data or objects which are not created again
+    pub destructive: bool,
+}
+
+impl Step {
+    fn new(sql: String) -> Self {
+        Step {
+            sql,
+            destructive: false,
+        }
+    }
+
+    fn destructive(sql: String) -> Self {
+        Step {
+            sql,
+            destructive: true,
+        }
+    }
+
+    fn manual(note: String) -> Self {
+        Step::new(format!("-- manual: {}", note))
+    }
+}
+
+/// Migration script between two DDL scripts
+pub fn migration(from: &str, to: &str) -> Result<String, &'static str> {
+    Ok(script(&diff(&parse_schema(from)?, &parse_schema(to)?)))
+}
+
+/// Statements ended by `;`, destructive ones after a warning comment
+pub fn script(steps: &[Step]) -> String {
+    let mut out = String::new();
+    for step in steps {
+        if step.destructive {
+            out.push_str("-- DESTRUCTIVE\n");
+        }
+        out.push_str(&step.sql);
+        out.push_str(if step.sql.starts_with("--") {
+            "\n"
+        } else {
+            ";\n"
+        });
+    }
+    out
+}
+
+/// Steps turning `from` into `to`, in dependency order:
+/// views and constraints are dropped first, domains and tables
+/// are created before the columns and constraints using them,
+/// dropped tables and domains go last but for the views created again.
+pub fn diff(from: &Schema, to: &Schema) -> Vec<Step> {
+    let mut steps = vec![];
+
+    let dropped_tables: Vec<&TableDefinition> = from
+        .tables
+        .iter()
+        .filter(|t| find_table(to, &t.name).is_none())
+        .collect();
+    let added_tables: Vec<&TableDefinition> = to
+        .tables
+        .iter()
+        .filter(|t| find_table(from, &t.name).is_none())
+        .collect();
+    let changed_tables: Vec<(&TableDefinition, &TableDefinition)> = from
+        .tables
+        .iter()
+        .filter_map(|old| Some((old, find_table(to, &old.name)?)))
+        .filter(|(old, new)| old != new)
+        .collect();
+
+    // views go when they change or when something they read changes shape
+    let mut reshaped: Vec<&QualifiedName> = dropped_tables.iter().map(|t| &t.name).collect();
+    reshaped.extend(
+        changed_tables
+            .iter()
+            .filter(|(old, new)| drops_columns(old, new))
+            .map(|(old, _)| &old.name),
+    );
+    let mut rebuilt: Vec<&ViewDefinition> = vec![];
+    loop {
+        let more: Vec<&ViewDefinition> = from
+            .views
+            .iter()
+            .filter(|v| !rebuilt.contains(v))
+            .filter(|v| {
+                find_view(to, &v.name) != Some(v)
+                    || v.references.iter().any(|r| reshaped.contains(&r))
+            })
+            .collect();
+        if more.is_empty() {
+            break;
+        }
+        for view in more {
+            reshaped.push(&view.name);
+            rebuilt.push(view);
+        }
+    }
+
+    // 1. views, dependents first
+    for view in dependency_order(rebuilt.clone(), |v| &v.name, |v| v.references.clone())
+        .into_iter()
+        .rev()
+    {
+        let sql = format!("DROP VIEW {} RESTRICT", view.name.to_sql());
+        steps.push(if find_view(to, &view.name).is_some() {
+            Step::new(sql)
+        } else {
+            Step::destructive(sql)
+        });
+    }
+
+    // constraints kept on a column which is dropped and added again
+    // for a new type, and foreign keys referencing it
+    let retyped: Vec<(&QualifiedName, &Identifier)> = changed_tables
+        .iter()
+        .flat_map(|(old, new)| {
+            old.columns
+                .iter()
+                .filter(move |c| {
+                    new.column(&c.name)
+                        .is_some_and(|n| n.data_type != c.data_type)
+                })
+                .map(move |c| (&old.name, &c.name))
+        })
+        .collect();
+    let mut rebound = vec![];
+    for table in &from.tables {
+        for constraint in &table.constraints {
+            let kept =
+                find_table(to, &table.name).is_some_and(|t| t.constraints.contains(constraint));
+            let uses_retyped = retyped
+                .iter()
+                .any(|(owner, column)| uses_column(constraint, &table.name, owner, column));
+            if kept && uses_retyped {
+                rebound.push((&table.name, constraint));
+            }
+        }
+    }
+
+    // 2. constraints which go, change or are rebound, foreign keys first
+    let mut dropped_constraints = rebound.clone();
+    for (old, new) in &changed_tables {
+        for constraint in &old.constraints {
+            if !new.constraints.contains(constraint) {
+                dropped_constraints.push((&old.name, constraint));
+            }
+        }
+    }
+    dropped_constraints.sort_by_key(|(_, c)| c.references.is_none());
+    for (table, constraint) in dropped_constraints {
+        steps.push(match &constraint.name {
+            Some(name) => Step::new(format!(
+                "ALTER TABLE {} DROP CONSTRAINT {} RESTRICT",
+                table.to_sql(),
+                name.to_sql()
+            )),
+            None => Step::manual(format!(
+                "drop the unnamed constraint {} of {}",
+                constraint.definition,
+                table.to_sql()
+            )),
+        });
+    }
+
+    // 3. domains
+    for domain in &to.domains {
+        match find_domain(from, &domain.name) {
+            None => steps.push(Step::new(domain.to_sql())),
+            Some(old) if old.data_type != domain.data_type => {
+                // columns keep their values with the type of the old domain
+                steps.push(Step::destructive(format!(
+                    "DROP DOMAIN {} CASCADE",
+                    old.name.to_sql()
+                )));
+                steps.push(Step::new(domain.to_sql()));
+            }
+            Some(old) => alter_domain(&mut steps, old, domain),
+        }
+    }
+
+    // 4. new tables, referenced ones first, foreign keys to
+    // tables created later are added afterwards
+    let mut created: Vec<&QualifiedName> = vec![];
+    let mut deferred = vec![];
+    for table in dependency_order(added_tables, |t| &t.name, references_of) {
+        let mut now = table.clone();
+        now.constraints.clear();
+        for c in &table.constraints {
+            let later = c.references.as_ref().is_some_and(|r| {
+                *r != table.name
+                    && !created.contains(&r)
+                    && find_table(from, r).is_none()
+                    && find_table(to, r).is_some()
+            });
+            if later {
+                deferred.push((&table.name, c));
+            } else {
+                now.constraints.push(c.clone());
+            }
+        }
+        steps.push(Step::new(now.to_sql()));
+        created.push(&table.name);
+    }
+
+    // 5. columns and constraints of changed tables
+    let mut added_constraints = rebound;
+    for (old, new) in &changed_tables {
+        alter_columns(&mut steps, old, new);
+        for constraint in &new.constraints {
+            if !old.constraints.contains(constraint) {
+                added_constraints.push((&new.name, constraint));
+            }
+        }
+    }
+    added_constraints.extend(deferred);
+    added_constraints.sort_by_key(|(_, c)| c.references.is_some());
+    for (table, constraint) in added_constraints {
+        steps.push(Step::new(format!(
+            "ALTER TABLE {} ADD {}",
+            table.to_sql(),
+            constraint.to_sql()
+        )));
+    }
+
+    // 6. dropped tables, referencing ones first
+    for table in dependency_order(dropped_tables, |t| &t.name, references_of)
+        .into_iter()
+        .rev()
+    {
+        steps.push(Step::destructive(format!(
+            "DROP TABLE {} RESTRICT",
+            table.name.to_sql()
+        )));
+    }
+
+    // 7. dropped domains
+    for domain in &from.domains {
+        if find_domain(to, &domain.name).is_none() {
+            steps.push(Step::destructive(format!(
+                "DROP DOMAIN {} RESTRICT",
+                domain.name.to_sql()
+            )));
+        }
+    }
+
+    // 8. new and rebuilt views
+    let views: Vec<&ViewDefinition> = to
+        .views
+        .iter()
+        .filter(|v| find_view(from, &v.name).is_none() || rebuilt.iter().any(|r| r.name == v.name))
+        .collect();
+    for view in dependency_order(views, |v| &v.name, |v| v.references.clone()) {
+        steps.push(Step::new(view.to_sql()));
+    }
+
+    steps
+}
+
+fn alter_domain(steps: &mut Vec<Step>, old: &DomainDefinition, new: &DomainDefinition) {
+    let name = new.name.to_sql();
+    if old.default != new.default {
+        steps.push(Step::new(match &new.default {
+            Some(default) => format!("ALTER DOMAIN {} SET DEFAULT {}", name, default),
+            None => format!("ALTER DOMAIN {} DROP DEFAULT", name),
+        }));
+    }
+    for constraint in &old.constraints {
+        if !new.constraints.contains(constraint) {
+            steps.push(match &constraint.name {
+                Some(constraint) => Step::new(format!(
+                    "ALTER DOMAIN {} DROP CONSTRAINT {}",
+                    name,
+                    constraint.to_sql()
+                )),
+                None => Step::manual(format!(
+                    "drop the unnamed constraint {} of domain {}",
+                    constraint.definition, name
+                )),
+            });
+        }
+    }
+    for constraint in &new.constraints {
+        if !old.constraints.contains(constraint) {
+            steps.push(Step::new(format!(
+                "ALTER DOMAIN {} ADD {}",
+                name,
+                constraint.to_sql()
+            )));
+        }
+    }
+}
+
+/// Added, retyped, dropped columns and changed defaults.
+/// SQL-92 can't change a column type, the column is dropped and added again.
+fn alter_columns(steps: &mut Vec<Step>, old: &TableDefinition, new: &TableDefinition) {
+    let table = new.name.to_sql();
+    // NOT NULL without a default fails on a table with rows,
+    // the column is added nullable and filled by hand
+    let add = |steps: &mut Vec<Step>, column: &ColumnDefinition| {
+        let mut nullable = column.clone();
+        nullable.not_null &= column.default.is_some();
+        steps.push(Step::new(format!(
+            "ALTER TABLE {} ADD COLUMN {}",
+            table,
+            nullable.to_sql()
+        )));
+        if nullable.not_null != column.not_null {
+            steps.push(Step::manual(format!(
+                "fill {}.{}, then ALTER TABLE {} ADD CHECK ({} IS NOT NULL)",
+                table,
+                column.name.to_sql(),
+                table,
+                column.name.to_sql()
+            )));
+        }
+    };
+    let drop = |column: &ColumnDefinition| {
+        format!(
+            "ALTER TABLE {} DROP COLUMN {} RESTRICT",
+            table,
+            column.name.to_sql()
+        )
+    };
+
+    for column in &new.columns {
+        let before = match old.column(&column.name) {
+            Some(before) => before,
+            None => {
+                add(steps, column);
+                continue;
+            }
+        };
+        if before.data_type != column.data_type {
+            steps.push(Step::destructive(drop(before)));
+            add(steps, column);
+            continue;
+        }
+        let name = column.name.to_sql();
+        if before.default != column.default {
+            steps.push(Step::new(match &column.default {
+                Some(default) => format!(
+                    "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
+                    table, name, default
+                ),
+                None => format!("ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT", table, name),
+            }));
+        }
+        match (before.not_null, column.not_null) {
+            // NOT NULL is short for this check constraint
+            (false, true) => steps.push(Step::new(format!(
+                "ALTER TABLE {} ADD CHECK ({} IS NOT NULL)",
+                table, name
+            ))),
+            (true, false) => steps.push(Step::manual(format!(
+                "drop the NOT NULL constraint of {}.{}",
+                table, name
+            ))),
+            _ => {}
+        }
+    }
+    for column in &old.columns {
+        if new.column(&column.name).is_none() {
+            steps.push(Step::destructive(drop(column)));
+        }
+    }
+}
+
+/// Whether some column of the table goes away or changes type
+fn drops_columns(old: &TableDefinition, new: &TableDefinition) -> bool {
+    old.columns.iter().any(|column| {
+        new.column(&column.name)
+            .is_none_or(|now| now.data_type != column.data_type)
+    })
+}
+
+/// Whether the constraint of `table` names `column` of `owner`,
+/// on either side of REFERENCES. A foreign key without a column list
+/// may reference any column of the table.
+fn uses_column(
+    constraint: &Constraint,
+    table: &QualifiedName,
+    owner: &QualifiedName,
+    column: &Identifier,
+) -> bool {
+    let (tokens, _) = tokenize(&constraint.definition);
+    let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.kind.is_trivia()).collect();
+    let names = |tokens: &[Token]| {
+        tokens.iter().any(|t| {
+            matches!(
+                t.kind,
+                TokenKind::Identifier | TokenKind::DelimitedIdentifier
+            ) && Identifier::parse(t.text).is_ok_and(|name| name == *column)
+        })
+    };
+    let split = tokens
+        .iter()
+        .position(|t| t.is_keyword(Keyword::References))
+        .unwrap_or(tokens.len());
+    let (own, referenced) = tokens.split_at(split);
+    if table == owner && names(own) {
+        return true;
+    }
+    if constraint.references.as_ref() != Some(owner) {
+        return false;
+    }
+    match referenced.iter().position(|t| t.is_operator("(")) {
+        Some(list) => names(&referenced[list..]),
+        None => true,
+    }
+}
+
+fn references_of(table: &TableDefinition) -> Vec<QualifiedName> {
+    table
+        .constraints
+        .iter()
+        .filter_map(|c| c.references.clone())
+        .collect()
+}
+
+/// Items after those they depend on, in source order otherwise.
+/// A cycle is broken at its first item.
+fn dependency_order<'a, T>(
+    mut items: Vec<&'a T>,
+    name: impl Fn(&T) -> &QualifiedName,
+    depends: impl Fn(&T) -> Vec<QualifiedName>,
+) -> Vec<&'a T> {
+    let mut ordered: Vec<&'a T> = vec![];
+    while !items.is_empty() {
+        let ready = items
+            .iter()
+            .position(|item| {
+                depends(item).iter().all(|dependency| {
+                    dependency == name(item) || !items.iter().any(|other| name(other) == dependency)
+                })
+            })
+            .unwrap_or(0);
+        ordered.push(items.remove(ready));
+    }
+    ordered
+}
+
+fn find_table<'a>(schema: &'a Schema, name: &QualifiedName) -> Option<&'a TableDefinition> {
+    schema.tables.iter().find(|t| t.name == *name)
+}
+
+fn find_view<'a>(schema: &'a Schema, name: &QualifiedName) -> Option<&'a ViewDefinition> {
+    schema.views.iter().find(|v| v.name == *name)
+}
+
+fn find_domain<'a>(schema: &'a Schema, name: &QualifiedName) -> Option<&'a DomainDefinition> {
+    schema.domains.iter().find(|d| d.name == *name)
+}
+
+#[cfg(test)]
+const BEFORE: &str = "
+    CREATE DOMAIN money AS NUMERIC(10, 2) DEFAULT 0;
+    CREATE DOMAIN code AS CHAR(3);
+    CREATE TABLE customer (id INTEGER PRIMARY KEY, name VARCHAR(40), fax VARCHAR(20));
+    CREATE TABLE orders (
+        id INTEGER PRIMARY KEY,
+        customer INTEGER CONSTRAINT fk_customer REFERENCES customer (id),
+        amount money,
+        note VARCHAR(100)
+    );
+    CREATE TABLE legacy (id INTEGER, orders INTEGER REFERENCES orders (id));
+    CREATE VIEW big AS SELECT id, amount FROM orders WHERE amount > 100;
+    CREATE VIEW contacts AS SELECT name, fax FROM customer";
+
+#[test]
+fn diff_of_identical_schemas_is_empty() {
+    let schema = parse_schema(BEFORE).unwrap();
+    assert!(diff(&schema, &schema).is_empty());
+}
+
+#[test]
+fn migration_in_dependency_order() {
+    let after = "
+        CREATE DOMAIN money AS NUMERIC(10, 2) DEFAULT 1 CONSTRAINT positive CHECK (VALUE >= 0);
+        CREATE TABLE customer (id INTEGER PRIMARY KEY, name VARCHAR(60) NOT NULL, region INTEGER REFERENCES region (id));
+        CREATE TABLE orders (
+            id INTEGER PRIMARY KEY,
+            customer INTEGER CONSTRAINT fk_customer REFERENCES customer (id),
+            amount money DEFAULT 5,
+            note VARCHAR(100) NOT NULL
+        );
+        CREATE TABLE region (id INTEGER PRIMARY KEY, country INTEGER REFERENCES country (id));
+        CREATE TABLE country (id INTEGER PRIMARY KEY);
+        CREATE VIEW big AS SELECT id, amount FROM orders WHERE amount > 100;
+        CREATE VIEW contacts AS SELECT name FROM customer;
+        CREATE VIEW vip AS SELECT name FROM contacts";
+
+    let script = migration(BEFORE, after).unwrap();
+    assert_eq!(
+        script,
+        "DROP VIEW contacts RESTRICT;
+ALTER DOMAIN money SET DEFAULT 1;
+ALTER DOMAIN money ADD CONSTRAINT positive CHECK (VALUE >= 0);
+CREATE TABLE country (
+    id INTEGER,
+    PRIMARY KEY (id)
+);
+CREATE TABLE region (
+    id INTEGER,
+    country INTEGER,
+    PRIMARY KEY (id),
+    FOREIGN KEY (country) REFERENCES country (id)
+);
+-- DESTRUCTIVE
+ALTER TABLE customer DROP COLUMN name RESTRICT;
+ALTER TABLE customer ADD COLUMN name VARCHAR (60);
+-- manual: fill customer.name, then ALTER TABLE customer ADD CHECK (name IS NOT NULL)
+ALTER TABLE customer ADD COLUMN region INTEGER;
+-- DESTRUCTIVE
+ALTER TABLE customer DROP COLUMN fax RESTRICT;
+ALTER TABLE orders ALTER COLUMN amount SET DEFAULT 5;
+ALTER TABLE orders ADD CHECK (note IS NOT NULL);
+ALTER TABLE customer ADD FOREIGN KEY (region) REFERENCES region (id);
+-- DESTRUCTIVE
+DROP TABLE legacy RESTRICT;
+-- DESTRUCTIVE
+DROP DOMAIN code RESTRICT;
+CREATE VIEW contacts AS SELECT name FROM customer;
+CREATE VIEW vip AS SELECT name FROM contacts;
+"
+    );
+}
+
+#[test]
+fn retyped_key_keeps_its_constraints() {
+    let before = "
+        CREATE TABLE customer (id INTEGER PRIMARY KEY CHECK (id > 0), name VARCHAR(40));
+        CREATE TABLE orders (
+            id INTEGER,
+            customer INTEGER CONSTRAINT fk_customer REFERENCES customer (id)
+        )";
+    let after = "
+        CREATE TABLE customer (id BIGINT PRIMARY KEY CHECK (id > 0), name VARCHAR(40));
+        CREATE TABLE orders (
+            id INTEGER,
+            customer INTEGER CONSTRAINT fk_customer REFERENCES customer (id)
+        )";
+
+    assert_eq!(
+        migration(before, after).unwrap(),
+        "ALTER TABLE orders DROP CONSTRAINT fk_customer RESTRICT;
+-- manual: drop the unnamed constraint PRIMARY KEY (id) of customer
+-- manual: drop the unnamed constraint CHECK (id > 0) of customer
+-- DESTRUCTIVE
+ALTER TABLE customer DROP COLUMN id RESTRICT;
+ALTER TABLE customer ADD COLUMN id BIGINT;
+ALTER TABLE customer ADD PRIMARY KEY (id);
+ALTER TABLE customer ADD CHECK (id > 0);
+ALTER TABLE orders ADD CONSTRAINT fk_customer FOREIGN KEY (customer) REFERENCES customer (id);
+"
+    );
+}
+
+#[test]
+fn added_not_null_column() {
+    let before = "CREATE TABLE t (id INTEGER)";
+    let after = "CREATE TABLE t (id INTEGER, a INTEGER NOT NULL, b INTEGER DEFAULT 0 NOT NULL)";
+    assert_eq!(
+        migration(before, after).unwrap(),
+        "ALTER TABLE t ADD COLUMN a INTEGER;
+-- manual: fill t.a, then ALTER TABLE t ADD CHECK (a IS NOT NULL)
+ALTER TABLE t ADD COLUMN b INTEGER DEFAULT 0 NOT NULL;
+"
+    );
+}
@@ -1,3 +1,7 @@
+use crate::ast::select_sublist::SelectSublist;
+
+#[derive(Debug, PartialEq)]
 pub enum SelectList {
     ASTERISK,
+    SUBLISTS(Vec<SelectSublist>),
 }
@@ -1 +1,5 @@
-pub struct SelectSublist();
+use crate::ast::identifier::QualifiedName;
+
+/// Derived column of a select list, a column reference so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectSublist(pub QualifiedName);
@@ -0,0 +1,500 @@
+use crate::ast::ast_node::AstNode;
+
+/// Compiled node selector.
+///
+/// Syntax (CSS-like):
+/// * `name` - node with the name, case-insensitive
+/// * `"("` - node with exactly this name
+/// * `*` - any node
+/// * `:leaf`, `:first-child`, `:last-child`, `:nth-child(n)`,
+///   `:has(selector)`, `:not(name:filter, ...)` - predicate filters
+/// * `a b` - `b` anywhere below `a`, `a > b` - `b` is a child of `a`
+/// * `a ~ b` - `b` is a following sibling of `a`, `a + b` - the next one
+/// * `a, b` - union
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Vec<Step>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    combinator: Combinator,
+    compound: Compound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    Sibling,
+    Adjacent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Compound {
+    name: NameFilter,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NameFilter {
+    Any,
+    Word(String),
+    Exact(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Leaf,
+    FirstChild,
+    LastChild,
+    NthChild(usize),
+    Has(Selector),
+    Not(Vec<Compound>),
+}
+
+impl Selector {
+    pub fn parse(src: &str) -> Result<Self, &'static str> {
+        let mut reader = SelectorReader {
+            chars: src.chars().collect(),
+            pos: 0,
+        };
+        let selector = reader.selector()?;
+        reader.skip_ws();
+        if reader.pos != reader.chars.len() {
+            return Err("selector: unexpected character");
+        }
+        Ok(selector)
+    }
+
+    /// Matched nodes of the tree, in document (pre-order) order
+    pub fn select<'a>(&self, root: &'a AstNode) -> Vec<&'a AstNode> {
+        let flat = FlatTree::new(root);
+        self.eval(&flat, 0, true)
+            .into_iter()
+            .map(|id| flat.nodes[id].node)
+            .collect()
+    }
+
+    /// Run `f` on every matched node.
+    /// Matches are visited in reverse document order, so changing
+    /// the children of one match never invalidates a pending one.
+    pub fn select_mut(&self, root: &mut AstNode, mut f: impl FnMut(&mut AstNode)) -> usize {
+        let paths: Vec<Vec<usize>> = {
+            let flat = FlatTree::new(root);
+            self.eval(&flat, 0, true)
+                .into_iter()
+                .map(|id| flat.path(id))
+                .collect()
+        };
+
+        let mut count = 0;
+        for path in paths.iter().rev() {
+            if let Some(node) = root.node_at_mut(path) {
+                f(node);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Ids of matched nodes of `scope` subtree.
+    /// The first step looks at `scope` itself only if `inclusive`.
+    fn eval(&self, flat: &FlatTree, scope: usize, inclusive: bool) -> Vec<usize> {
+        let end = flat.subtree_end(scope);
+        let start = if inclusive { scope } else { scope + 1 };
+
+        let mut matched = vec![false; flat.nodes.len()];
+        for steps in &self.alternatives {
+            let mut current: Vec<usize> = (start..end)
+                .filter(|&id| flat.matches(id, &steps[0].compound))
+                .collect();
+
+            for step in &steps[1..] {
+                let mut next = vec![false; flat.nodes.len()];
+                for &id in &current {
+                    match step.combinator {
+                        Combinator::Descendant => {
+                            next[id + 1..flat.subtree_end(id)].fill(true);
+                        }
+                        Combinator::Child => {
+                            for &c in &flat.nodes[id].children {
+                                next[c] = true;
+                            }
+                        }
+                        Combinator::Sibling => {
+                            for s in flat.following_siblings(id) {
+                                next[*s] = true;
+                            }
+                        }
+                        Combinator::Adjacent => {
+                            if let Some(s) = flat.following_siblings(id).first() {
+                                next[*s] = true;
+                            }
+                        }
+                    }
+                }
+                current = (0..next.len())
+                    .filter(|&id| next[id] && flat.matches(id, &step.compound))
+                    .collect();
+            }
+
+            for id in current {
+                matched[id] = true;
+            }
+        }
+
+        (0..matched.len()).filter(|&id| matched[id]).collect()
+    }
+}
+
+impl AstNode {
+    /// Borrowed nodes matched by selector, in document order
+    pub fn select(&self, selector: &str) -> Result<Vec<&AstNode>, &'static str> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    /// Apply `f` to every node matched by selector, returns the match count
+    pub fn select_mut(
+        &mut self,
+        selector: &str,
+        f: impl FnMut(&mut AstNode),
+    ) -> Result<usize, &'static str> {
+        Ok(Selector::parse(selector)?.select_mut(self, f))
+    }
+}
+
+/// Pre-order flattening of the tree, node id is its pre-order index
+struct FlatTree<'a> {
+    nodes: Vec<FlatNode<'a>>,
+}
+
+struct FlatNode<'a> {
+    node: &'a AstNode,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    position: usize,
+    end: usize,
+}
+
+impl<'a> FlatTree<'a> {
+    fn new(root: &'a AstNode) -> Self {
+        let mut flat = FlatTree { nodes: vec![] };
+        flat.push(root, None, 0);
+        flat
+    }
+
+    fn push(&mut self, node: &'a AstNode, parent: Option<usize>, position: usize) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(FlatNode {
+            node,
+            parent,
+            children: vec![],
+            position,
+            end: id + 1,
+        });
+        for (i, child) in node.children().iter().enumerate() {
+            let child_id = self.push(child, Some(id), i);
+            self.nodes[id].children.push(child_id);
+        }
+        self.nodes[id].end = self.nodes.len();
+        id
+    }
+
+    fn subtree_end(&self, id: usize) -> usize {
+        self.nodes[id].end
+    }
+
+    fn siblings(&self, id: usize) -> &[usize] {
+        match self.nodes[id].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &[],
+        }
+    }
+
+    fn following_siblings(&self, id: usize) -> &[usize] {
+        let siblings = self.siblings(id);
+        let position = self.nodes[id].position;
+        if siblings.is_empty() {
+            return siblings;
+        }
+        &siblings[position + 1..]
+    }
+
+    fn path(&self, mut id: usize) -> Vec<usize> {
+        let mut path = vec![];
+        while let Some(parent) = self.nodes[id].parent {
+            path.push(self.nodes[id].position);
+            id = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn matches(&self, id: usize, compound: &Compound) -> bool {
+        let flat_node = &self.nodes[id];
+        let name_ok = match &compound.name {
+            NameFilter::Any => true,
+            NameFilter::Word(word) => flat_node.node.name.eq_ignore_ascii_case(word),
+            NameFilter::Exact(name) => &flat_node.node.name == name,
+        };
+        if !name_ok {
+            return false;
+        }
+
+        compound.filters.iter().all(|filter| match filter {
+            Filter::Leaf => flat_node.children.is_empty(),
+            Filter::FirstChild => flat_node.parent.is_some() && flat_node.position == 0,
+            Filter::LastChild => {
+                flat_node.parent.is_some() && flat_node.position + 1 == self.siblings(id).len()
+            }
+            Filter::NthChild(n) => flat_node.parent.is_some() && flat_node.position + 1 == *n,
+            Filter::Has(selector) => !selector.eval(self, id, false).is_empty(),
+            Filter::Not(compounds) => !compounds.iter().any(|c| self.matches(id, c)),
+        })
+    }
+}
+
+struct SelectorReader {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl SelectorReader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.pos != start
+    }
+
+    fn selector(&mut self) -> Result<Selector, &'static str> {
+        let mut alternatives = vec![self.steps()?];
+        loop {
+            self.skip_ws();
+            if self.peek() != Some(',') {
+                return Ok(Selector { alternatives });
+            }
+            self.pos += 1;
+            alternatives.push(self.steps()?);
+        }
+    }
+
+    fn steps(&mut self) -> Result<Vec<Step>, &'static str> {
+        self.skip_ws();
+        let mut steps = vec![Step {
+            combinator: Combinator::Descendant,
+            compound: self.compound()?,
+        }];
+
+        loop {
+            let had_ws = self.skip_ws();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('~') => Combinator::Sibling,
+                Some('+') => Combinator::Adjacent,
+                None | Some(',') | Some(')') => return Ok(steps),
+                Some(_) if had_ws => Combinator::Descendant,
+                Some(_) => return Err("selector: expect combinator"),
+            };
+            if combinator != Combinator::Descendant {
+                self.pos += 1;
+                self.skip_ws();
+            }
+            steps.push(Step {
+                combinator,
+                compound: self.compound()?,
+            });
+        }
+    }
+
+    fn compound(&mut self) -> Result<Compound, &'static str> {
+        let name = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                NameFilter::Any
+            }
+            Some('"') => NameFilter::Exact(self.quoted()?),
+            Some(':') => NameFilter::Any,
+            Some(c) if is_word_char(c) => NameFilter::Word(self.word()),
+            _ => return Err("selector: expect node name"),
+        };
+
+        let mut filters = vec![];
+        while self.peek() == Some(':') {
+            self.pos += 1;
+            let pseudo = self.word();
+            let filter = match pseudo.as_str() {
+                "leaf" => Filter::Leaf,
+                "first-child" => Filter::FirstChild,
+                "last-child" => Filter::LastChild,
+                "nth-child" => {
+                    let arg = self.argument(|r| Ok(r.word()))?;
+                    match arg.parse::<usize>() {
+                        Ok(n) if n > 0 => Filter::NthChild(n),
+                        _ => return Err("selector: invalid nth-child index"),
+                    }
+                }
+                "has" => Filter::Has(self.argument(SelectorReader::selector)?),
+                "not" => {
+                    let selector = self.argument(SelectorReader::selector)?;
+                    if selector.alternatives.iter().any(|steps| steps.len() != 1) {
+                        return Err("selector: :not takes names and filters only");
+                    }
+                    let compounds = selector.alternatives.into_iter();
+                    Filter::Not(
+                        compounds
+                            .map(|mut steps| steps.remove(0).compound)
+                            .collect(),
+                    )
+                }
+                _ => return Err("selector: unknown filter"),
+            };
+            filters.push(filter);
+        }
+
+        Ok(Compound { name, filters })
+    }
+
+    fn argument<T>(
+        &mut self,
+        inner: impl FnOnce(&mut Self) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        if self.peek() != Some('(') {
+            return Err("selector: expect '('");
+        }
+        self.pos += 1;
+        self.skip_ws();
+        let value = inner(self)?;
+        self.skip_ws();
+        if self.peek() != Some(')') {
+            return Err("selector: expect ')'");
+        }
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_word_char) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn quoted(&mut self) -> Result<String, &'static str> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("selector: unterminated string"),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    out.push(self.peek().ok_or("selector: unterminated string")?);
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '>' | '~' | '+' | ',' | ':' | '"' | '(' | ')' | '*')
+}
+
+#[cfg(test)]
+fn update_tree() -> AstNode {
+    let mut update_node = AstNode::from("UPDATE");
+    let mut lp_node = AstNode::from("(");
+    lp_node.add(AstNode::from("AN"));
+    update_node
+        .add(lp_node)
+        .add(AstNode::from("ANYWAY"))
+        .add(AstNode::from(")"));
+    update_node
+}
+
+#[test]
+fn select_child_and_sibling() {
+    let tree = update_tree();
+
+    let names = |nodes: Vec<&AstNode>| -> Vec<String> {
+        nodes.into_iter().map(|n| n.name.clone()).collect()
+    };
+
+    assert_eq!(
+        names(tree.select(r#"update > "(" ~ *"#).unwrap()),
+        vec!["ANYWAY", ")"]
+    );
+    assert_eq!(names(tree.select(r#""(" + *"#).unwrap()), vec!["ANYWAY"]);
+    assert_eq!(names(tree.select("update an").unwrap()), vec!["AN"]);
+    assert_eq!(names(tree.select("update > an").unwrap()).len(), 0);
+}
+
+#[test]
+fn select_filters_in_document_order() {
+    let tree = update_tree();
+
+    let names: Vec<&str> = tree
+        .select("*:leaf, update")
+        .unwrap()
+        .into_iter()
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["UPDATE", "AN", "ANYWAY", ")"]);
+
+    let has: Vec<&str> = tree
+        .select("*:has(an)")
+        .unwrap()
+        .into_iter()
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(has, vec!["UPDATE", "("]);
+
+    let not: Vec<&str> = tree
+        .select("update > *:not(:first-child):not(:last-child)")
+        .unwrap()
+        .into_iter()
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(not, vec!["ANYWAY"]);
+
+    let nth = tree.select("update > :nth-child(3)").unwrap();
+    assert_eq!(nth[0].name, ")");
+}
+
+#[test]
+fn select_mut_rewrites_in_place() {
+    let mut tree = update_tree();
+
+    let count = tree
+        .select_mut("*:leaf", |node| node.name = node.name.to_lowercase())
+        .unwrap();
+
+    assert_eq!(count, 3);
+    assert_eq!(tree.children()[0].children()[0].name, "an");
+    assert_eq!(tree.children()[1].name, "anyway");
+    assert_eq!(tree.name, "UPDATE");
+}
+
+#[test]
+fn invalid_selector() {
+    let tree = update_tree();
+    assert!(tree.select("update >").is_err());
+    assert!(tree.select("\"(").is_err());
+    assert!(tree.select("*:unknown").is_err());
+    assert!(tree.select("*:not(update an)").is_err());
+}
@@ -0,0 +1,309 @@
+use crate::ast::cst::CstNode;
+use crate::ast::dialect::{self, Dialect};
+use std::fmt::{Display, Formatter};
+use std::io::{ErrorKind, Read};
+
+/// Bytes asked from the reader at once
+const CHUNK_LEN: usize = 64 * 1024;
+/// Default limit of one statement, longer ones are skipped
+pub const MAX_STATEMENT_LEN: usize = 64 * 1024 * 1024;
+
+/// Statement of a stream with its position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    /// stream offset of the first byte of `text`
+    pub offset: u64,
+    /// source from the end of the previous statement, leading
+    /// whitespace left out, up to and including the `;`
+    pub text: String,
+    /// script with this one statement
+    pub tree: CstNode,
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(std::io::Error),
+    /// invalid UTF-8, a lexer error or a statement over the limit,
+    /// at a stream offset
+    Invalid {
+        message: &'static str,
+        offset: u64,
+    },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "{}", error),
+            ReadError::Invalid { message, offset } => write!(f, "{} at byte {}", message, offset),
+        }
+    }
+}
+
+/// Where the scanner is, only `;` outside quotes and comments ends a statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scan {
+    Normal,
+    /// after `-`, maybe a line comment
+    Dash,
+    /// after `/`, maybe a block comment
+    Slash,
+    Quote(u8),
+    LineComment,
+    BlockComment,
+    /// after `*` in a block comment
+    BlockStar,
+}
+
+/// Statements of a reader, one at a time.
+/// Only the statement being read is buffered, so memory stays under
+/// the statement limit plus one read whatever the size of the input.
+/// After an error the iteration goes on with the next statement.
+pub struct StatementReader<R> {
+    reader: R,
+    dialect: &'static dyn Dialect,
+    /// ASCII characters which open string literals and delimited identifiers
+    quotes: Vec<u8>,
+    max_len: usize,
+    buffer: Vec<u8>,
+    /// stream offset of `buffer[0]`
+    offset: u64,
+    scanned: usize,
+    scan: Scan,
+    /// dropping the rest of a statement over the limit
+    skipping: bool,
+    eof: bool,
+}
+
+impl<R: Read> StatementReader<R> {
+    /// Reader of strict SQL-92
+    pub fn new(reader: R) -> Self {
+        StatementReader::with_dialect(reader, dialect::DEFAULT)
+    }
+
+    pub fn with_dialect(reader: R, dialect: &'static dyn Dialect) -> Self {
+        let quotes = dialect
+            .identifier_quotes()
+            .iter()
+            .chain(dialect.string_quotes())
+            .filter(|c| c.is_ascii())
+            .map(|&c| c as u8)
+            .collect();
+        StatementReader {
+            reader,
+            dialect,
+            quotes,
+            max_len: MAX_STATEMENT_LEN,
+            buffer: vec![],
+            offset: 0,
+            scanned: 0,
+            scan: Scan::Normal,
+            skipping: false,
+            eof: false,
+        }
+    }
+
+    /// Longest statement in bytes
+    pub fn max_statement_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Whether the statement ends after `byte`
+    fn step(&mut self, byte: u8) -> bool {
+        self.scan = match (self.scan, byte) {
+            (Scan::Normal, b';') => return true,
+            (Scan::Normal, b'-') => Scan::Dash,
+            (Scan::Normal, b'/') => Scan::Slash,
+            (Scan::Normal, quote) if self.quotes.contains(&quote) => Scan::Quote(quote),
+            (Scan::Normal, _) => Scan::Normal,
+            (Scan::Dash, b'-') => Scan::LineComment,
+            (Scan::Slash, b'*') => Scan::BlockComment,
+            (Scan::Dash, _) | (Scan::Slash, _) => {
+                self.scan = Scan::Normal;
+                return self.step(byte);
+            }
+            // a doubled quote closes and opens again
+            (Scan::Quote(quote), byte) if byte == quote => Scan::Normal,
+            (Scan::Quote(quote), _) => Scan::Quote(quote),
+            (Scan::LineComment, b'\n') => Scan::Normal,
+            (Scan::LineComment, _) => Scan::LineComment,
+            (Scan::BlockComment, b'*') | (Scan::BlockStar, b'*') => Scan::BlockStar,
+            (Scan::BlockStar, b'/') => Scan::Normal,
+            (Scan::BlockComment, _) | (Scan::BlockStar, _) => Scan::BlockComment,
+        };
+        false
+    }
+
+    /// Statement of the first `len` buffered bytes, `None` when
+    /// they are only whitespace and comments or the rest of a skipped one
+    fn take(&mut self, len: usize) -> Option<Result<Statement, ReadError>> {
+        let mut bytes: Vec<u8> = self.buffer.drain(..len).collect();
+        let start = self.offset;
+        self.offset += len as u64;
+        self.scanned = 0;
+        if std::mem::replace(&mut self.skipping, false) {
+            return None;
+        }
+
+        let blank = bytes.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        bytes.drain(..blank);
+        let offset = start + blank as u64;
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(error) => {
+                return Some(Err(ReadError::Invalid {
+                    message: "invalid UTF-8",
+                    offset: offset + error.utf8_error().valid_up_to() as u64,
+                }))
+            }
+        };
+        match CstNode::parse_with(&text, self.dialect) {
+            Ok(tree) if tree.statements().is_empty() => None,
+            Ok(tree) => Some(Ok(Statement { offset, text, tree })),
+            Err(error) => Some(Err(ReadError::Invalid {
+                message: error.message,
+                offset: offset + error.span.start as u64,
+            })),
+        }
+    }
+}
+
+impl<R: Read> Iterator for StatementReader<R> {
+    type Item = Result<Statement, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.scanned < self.buffer.len() {
+                let byte = self.buffer[self.scanned];
+                self.scanned += 1;
+                if self.step(byte) {
+                    match self.take(self.scanned) {
+                        Some(item) => return Some(item),
+                        None => continue,
+                    }
+                }
+            }
+
+            if self.buffer.len() > self.max_len {
+                let start = self.offset;
+                self.offset += self.buffer.len() as u64;
+                self.buffer.clear();
+                self.scanned = 0;
+                if !std::mem::replace(&mut self.skipping, true) {
+                    return Some(Err(ReadError::Invalid {
+                        message: "statement too long",
+                        offset: start,
+                    }));
+                }
+            }
+
+            if self.eof {
+                if self.buffer.is_empty() {
+                    return None;
+                }
+                match self.take(self.buffer.len()) {
+                    Some(item) => return Some(item),
+                    None => continue,
+                }
+            }
+
+            let len = self.buffer.len();
+            self.buffer.resize(len + CHUNK_LEN, 0);
+            let read = loop {
+                match self.reader.read(&mut self.buffer[len..]) {
+                    Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                    read => break read,
+                }
+            };
+            match read {
+                Ok(read) => {
+                    self.buffer.truncate(len + read);
+                    self.eof = read == 0;
+                }
+                Err(error) => {
+                    self.buffer.truncate(len);
+                    self.eof = true;
+                    return Some(Err(ReadError::Io(error)));
+                }
+            }
+        }
+    }
+}
+
+/// Reader which hands out a few bytes per call
+#[cfg(test)]
+struct Trickle<'a>(&'a [u8], usize);
+
+#[cfg(test)]
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.1 = self.1 % 3 + 1;
+        let len = self.1.min(buf.len()).min(self.0.len());
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn statements_of_a_stream() {
+    let dump = "-- dump; header\n\
+                CREATE TABLE \"a;b\" (x VARCHAR(10));\n\
+                INSERT INTO \"a;b\" VALUES ('it''s; fine');\r\n\
+                /* block; with * and ; */ INSERT INTO \"a;b\" VALUES ('--not a comment');\n\
+                SELECT x FROM \"a;b\" -- trailing; comment\n\
+                WHERE x <> '/*'\n\
+                ;\n\
+                -- only a comment at the end\n";
+    let statements: Vec<Statement> = StatementReader::new(Trickle(dump.as_bytes(), 0))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(statements.len(), 4);
+    for statement in &statements {
+        assert!(dump[statement.offset as usize..].starts_with(&statement.text));
+        assert_eq!(statement.tree.to_string(), statement.text);
+        assert_eq!(statement.tree.statements().len(), 1);
+    }
+    assert_eq!(statements[0].offset, 0);
+    assert!(statements[0].text.starts_with("-- dump; header\nCREATE"));
+    assert!(statements[2]
+        .text
+        .starts_with("/* block; with * and ; */ INSERT"));
+    assert!(statements[3].text.ends_with("WHERE x <> '/*'\n;"));
+
+    let last = StatementReader::new("SELECT 1; SELECT 2".as_bytes())
+        .map(|s| s.unwrap().offset)
+        .collect::<Vec<_>>();
+    assert_eq!(last, vec![0, 10]);
+}
+
+#[test]
+fn stream_errors_are_skipped() {
+    let long = format!("SELECT '{}';", "x".repeat(100));
+    let dump = format!("SELECT 1;\n{}\nSELECT #;\nSELECT 2; SELECT 'open", long);
+    let mut bytes = dump.into_bytes();
+    bytes.extend_from_slice(b"\xff';");
+    let mut reader = StatementReader::new(Trickle(&bytes, 0)).max_statement_len(50);
+
+    assert_eq!(reader.next().unwrap().unwrap().text, "SELECT 1;");
+    match reader.next().unwrap() {
+        Err(ReadError::Invalid { message, offset }) => {
+            assert_eq!((message, offset), ("statement too long", 9));
+        }
+        other => panic!("{:?}", other),
+    }
+    match reader.next().unwrap() {
+        Err(ReadError::Invalid { message, offset }) => {
+            assert_eq!((message, offset), ("unexpected character", 128));
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(reader.next().unwrap().unwrap().text, "SELECT 2;");
+    match reader.next().unwrap() {
+        Err(error) => assert_eq!(error.to_string(), "invalid UTF-8 at byte 153"),
+        other => panic!("{:?}", other),
+    }
+    assert!(reader.next().is_none());
+}
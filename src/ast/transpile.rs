@@ -0,0 +1,714 @@
+use crate::ast::dialect::{DataType, Dialect, Feature};
+use crate::ast::extension::{find_extensions, Extension, ExtensionNode};
+use crate::ast::keyword::Keyword;
+use crate::ast::lexer::{tokenize_with, Token, TokenKind};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// Construct of the source which the target dialect can't express
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranspileError {
+    pub construct: &'static str,
+    /// name of the target dialect
+    pub target: &'static str,
+    /// byte range in the source
+    pub span: Range<usize>,
+}
+
+impl Display for TranspileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}..{}: {} can't be expressed in {}",
+            self.span.start, self.span.end, self.construct, self.target
+        )
+    }
+}
+
+/// Rewrite SQL written for `from` so that `to` accepts it.
+/// Whitespace and comments are kept; every construct the target
+/// can't express is reported instead of emitting wrong SQL.
+/// The typed AST and its unparser only cover the `SELECT` head, so this
+/// rewrites the token stream, guided by the dialect's extension nodes.
+pub fn transpile(
+    src: &str,
+    from: &dyn Dialect,
+    to: &dyn Dialect,
+) -> Result<String, Vec<TranspileError>> {
+    let (out, errors) = rewrite(src, from, to);
+    if errors.is_empty() {
+        Ok(out)
+    } else {
+        Err(errors)
+    }
+}
+
+fn rewrite(src: &str, from: &dyn Dialect, to: &dyn Dialect) -> (String, Vec<TranspileError>) {
+    let (tokens, lex_errors) = tokenize_with(src, from);
+    let extensions = find_extensions(&tokens, from);
+    let mut rewriter = Rewriter::new(tokens, from, to);
+    for error in lex_errors {
+        rewriter.error(error.message, error.span);
+    }
+
+    rewriter.concat_functions(src);
+    rewriter.tokens();
+    rewriter.extensions(src, &extensions);
+    rewriter.casts();
+    rewriter.concat_operators();
+
+    (rewriter.out.concat(), rewriter.errors)
+}
+
+/// Rewritten text of every token, trivia included.
+/// A structural rewrite stores the text of a whole token range
+/// in its first token and hides the others.
+struct Rewriter<'a> {
+    tokens: Vec<Token<'a>>,
+    out: Vec<String>,
+    hidden: Vec<bool>,
+    /// start of the rewritten range ending at the token
+    group_start: Vec<usize>,
+    /// end of the rewritten range starting at the token
+    group_end: Vec<usize>,
+    /// ranges which are a `CONCAT(...)` built here, by start
+    concat_group: Vec<bool>,
+    from: &'a dyn Dialect,
+    to: &'a dyn Dialect,
+    errors: Vec<TranspileError>,
+}
+
+impl<'a> Rewriter<'a> {
+    fn new(tokens: Vec<Token<'a>>, from: &'a dyn Dialect, to: &'a dyn Dialect) -> Self {
+        let len = tokens.len();
+        Rewriter {
+            out: tokens.iter().map(|t| t.text.to_string()).collect(),
+            tokens,
+            hidden: vec![false; len],
+            group_start: (0..len).collect(),
+            group_end: (0..len).collect(),
+            concat_group: vec![false; len],
+            from,
+            to,
+            errors: vec![],
+        }
+    }
+
+    fn error(&mut self, construct: &'static str, span: Range<usize>) {
+        self.errors.push(TranspileError {
+            construct,
+            target: self.to.name(),
+            span,
+        });
+    }
+
+    fn span(&self, first: usize, last: usize) -> Range<usize> {
+        self.tokens[first].span.start..self.tokens[last].span.end
+    }
+
+    fn splice(&mut self, first: usize, last: usize, text: String) {
+        self.out[first] = text;
+        for i in first + 1..=last {
+            self.out[i].clear();
+            self.hidden[i] = true;
+        }
+        self.group_start[last] = first;
+        self.group_end[first] = last;
+    }
+
+    /// Rewritten text of the range, without outer whitespace
+    fn text(&self, first: usize, last: usize) -> String {
+        self.out[first..=last].concat().trim().to_string()
+    }
+
+    fn significant(&self, i: usize) -> bool {
+        !self.tokens[i].kind.is_trivia() && !self.hidden[i]
+    }
+
+    fn prev_significant(&self, i: usize) -> Option<usize> {
+        (0..i).rev().find(|&j| !self.tokens[j].kind.is_trivia())
+    }
+
+    fn next_significant(&self, i: usize) -> Option<usize> {
+        (i + 1..self.tokens.len()).find(|&j| !self.tokens[j].kind.is_trivia())
+    }
+
+    fn matching_close(&self, open: usize) -> Option<usize> {
+        let mut depth = 0;
+        for i in open..self.tokens.len() {
+            if self.tokens[i].is_operator("(") {
+                depth += 1;
+            } else if self.tokens[i].is_operator(")") {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        }
+        None
+    }
+
+    fn matching_open(&self, close: usize) -> Option<usize> {
+        let mut depth = 0;
+        for i in (0..=close).rev() {
+            if self.tokens[i].is_operator(")") {
+                depth += 1;
+            } else if self.tokens[i].is_operator("(") {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        }
+        None
+    }
+
+    /// Name of a function call whose `(` is at `open`
+    fn call_name(&self, open: usize) -> Option<usize> {
+        let name = self.prev_significant(open)?;
+        let adjacent = self.tokens[name].span.end == self.tokens[open].span.start;
+        match self.tokens[name].kind {
+            TokenKind::Identifier => Some(name),
+            TokenKind::Keyword(_) if adjacent => Some(name),
+            _ => None,
+        }
+    }
+
+    /// First token of the primary which ends right before `i`
+    fn operand_before(&self, i: usize) -> Option<usize> {
+        let last = self.prev_significant(i)?;
+        let mut first = self.group_start[last];
+        if first == last && self.tokens[last].is_operator(")") {
+            first = self.matching_open(last)?;
+            if let Some(name) = self.call_name(first) {
+                first = name;
+            }
+        }
+        Some(self.group_start[first].min(first))
+    }
+
+    /// Last token of the primary which starts right after `i`
+    fn operand_after(&self, i: usize) -> Option<usize> {
+        let first = self.next_significant(i)?;
+        if self.group_end[first] != first {
+            return Some(self.group_end[first]);
+        }
+        let token = &self.tokens[first];
+        if token.is_operator("(") {
+            return self.matching_close(first);
+        }
+        if let Some(open) = self.next_significant(first) {
+            if self.tokens[open].is_operator("(") && self.call_name(open) == Some(first) {
+                return self.matching_close(open);
+            }
+        }
+        match token.kind {
+            TokenKind::Operator => None,
+            _ => Some(first),
+        }
+    }
+
+    /// `CONCAT(a, b)` into `(a || b)` for targets without the function
+    fn concat_functions(&mut self, src: &str) {
+        if !self.from.supports(Feature::ConcatFunction) || self.to.supports(Feature::ConcatFunction)
+        {
+            return;
+        }
+        for i in 0..self.tokens.len() {
+            let is_concat = self.tokens[i].kind == TokenKind::Identifier
+                && self.tokens[i].text.eq_ignore_ascii_case("CONCAT");
+            let open = match self.next_significant(i) {
+                Some(open)
+                    if is_concat && !self.hidden[i] && self.tokens[open].is_operator("(") =>
+                {
+                    open
+                }
+                _ => continue,
+            };
+            let close = match self.matching_close(open) {
+                Some(close) => close,
+                None => continue,
+            };
+            if !self.to.has_concat_operator() {
+                self.error("CONCAT function", self.span(i, close));
+                continue;
+            }
+
+            // arguments are rewritten on their own, split on top-level commas
+            let mut args = vec![];
+            let mut depth = 0;
+            let mut start = self.tokens[open].span.end;
+            for j in open + 1..=close {
+                let token = self.tokens[j].clone();
+                if token.is_operator("(") {
+                    depth += 1;
+                } else if token.is_operator(")") && depth > 0 {
+                    depth -= 1;
+                } else if depth == 0 && (token.is_operator(",") || j == close) {
+                    let (arg, errors) = rewrite(&src[start..token.span.start], self.from, self.to);
+                    for error in errors {
+                        let span = error.span.start + start..error.span.end + start;
+                        self.error(error.construct, span);
+                    }
+                    args.push(arg.trim().to_string());
+                    start = token.span.end;
+                }
+            }
+            self.splice(i, close, format!("({})", args.join(" || ")));
+        }
+    }
+
+    /// Quoting styles and type names, token by token
+    fn tokens(&mut self) {
+        let mut statement: Option<Keyword> = None;
+        let mut parens: Vec<bool> = vec![];
+        let mut prev: [Option<usize>; 2] = [None, None];
+
+        for i in 0..self.tokens.len() {
+            if !self.significant(i) {
+                continue;
+            }
+            let token = self.tokens[i].clone();
+            let prev_is =
+                |n: usize, f: &dyn Fn(&Token) -> bool| prev[n].is_some_and(|p| f(&self.tokens[p]));
+            let is_name = |t: &Token| {
+                matches!(
+                    t.kind,
+                    TokenKind::Identifier | TokenKind::DelimitedIdentifier
+                )
+            };
+
+            let type_position = prev_is(0, &|t| t.is_operator("::"))
+                || prev_is(0, &|t| t.is_keyword(Keyword::As)) && parens.last() == Some(&true)
+                || statement == Some(Keyword::Create)
+                    && parens.len() == 1
+                    && prev_is(0, &is_name)
+                    && prev_is(1, &|t| t.is_operator("(") || t.is_operator(","))
+                || statement == Some(Keyword::Alter)
+                    && prev_is(0, &is_name)
+                    && prev_is(1, &|t| {
+                        t.is_keyword(Keyword::Add) || t.is_keyword(Keyword::Column)
+                    });
+
+            match token.kind {
+                TokenKind::Operator if token.text == ";" => {
+                    statement = None;
+                    parens.clear();
+                }
+                TokenKind::Operator if token.text == "(" => {
+                    parens.push(prev_is(0, &|t| t.is_keyword(Keyword::Cast)))
+                }
+                TokenKind::Operator if token.text == ")" => {
+                    parens.pop();
+                }
+                TokenKind::DelimitedIdentifier => self.requote_identifier(i),
+                TokenKind::String => self.requote_string(i),
+                TokenKind::Unknown => self.error("unknown character", token.span.clone()),
+                TokenKind::Identifier | TokenKind::Keyword(_) if type_position => self.data_type(i),
+                _ => {}
+            }
+
+            if statement.is_none() {
+                if let TokenKind::Keyword(keyword) = token.kind {
+                    statement = Some(keyword);
+                }
+            }
+            prev = [Some(i), prev[0]];
+        }
+    }
+
+    fn requote_identifier(&mut self, i: usize) {
+        let text = self.tokens[i].text;
+        let quote = text.chars().next().unwrap();
+        let target = self.to.identifier_quotes()[0];
+        if self.to.identifier_quotes().contains(&quote) {
+            return;
+        }
+        let body = self.tokens[i]
+            .unquoted()
+            .replace(&quote.to_string().repeat(2), &quote.to_string());
+        self.out[i] = format!(
+            "{q}{}{q}",
+            body.replace(target, &target.to_string().repeat(2)),
+            q = target
+        );
+    }
+
+    fn requote_string(&mut self, i: usize) {
+        let text = self.tokens[i].text;
+        let quote = text.chars().next().unwrap();
+        if self.to.string_quotes().contains(&quote) {
+            return;
+        }
+        let body = self.tokens[i]
+            .unquoted()
+            .replace(&quote.to_string().repeat(2), &quote.to_string());
+        self.out[i] = format!("'{}'", body.replace('\'', "''"));
+    }
+
+    fn data_type(&mut self, i: usize) {
+        let data_type = match self.from.parse_type(self.tokens[i].text) {
+            Some(data_type) => data_type,
+            None => return,
+        };
+        // `DOUBLE PRECISION` is two tokens
+        let mut last = i;
+        let mut source = self.tokens[i].text.to_uppercase();
+        if let Some(next) = self.next_significant(i) {
+            if data_type == DataType::Double && self.tokens[next].is_keyword(Keyword::Precision) {
+                last = next;
+                source.push_str(" PRECISION");
+            }
+        }
+
+        match self.to.type_name(data_type) {
+            Some(name) if name == source => {}
+            Some(name) => self.splice(i, last, name.to_string()),
+            None => self.error(type_construct(data_type), self.span(i, last)),
+        }
+    }
+
+    fn token_range(&self, span: &Range<usize>) -> (usize, usize) {
+        let first = self.tokens.partition_point(|t| t.span.start < span.start);
+        let last = self.tokens.partition_point(|t| t.span.end < span.end);
+        (first, last)
+    }
+
+    fn extensions(&mut self, src: &str, nodes: &[ExtensionNode]) {
+        let mut row_limit: Vec<ExtensionNode> = vec![];
+
+        for node in nodes {
+            let (first, last) = self.token_range(&node.span);
+            if self.hidden[first] {
+                continue;
+            }
+            match &node.extension {
+                Extension::Limit(offset) if self.limit_count(last).is_some() => {
+                    // `LIMIT offset, count`
+                    let count = self.limit_count(last).unwrap();
+                    let span = node.span.start..self.tokens[count].span.end;
+                    if !row_limit.is_empty() {
+                        self.row_limit(&row_limit);
+                        row_limit.clear();
+                    }
+                    let limit = Extension::Limit(self.tokens[count].text.to_string());
+                    let offset = Extension::Offset(offset.clone());
+                    self.row_limit(&[
+                        ExtensionNode {
+                            extension: limit,
+                            span: span.clone(),
+                        },
+                        ExtensionNode {
+                            extension: offset,
+                            span,
+                        },
+                    ]);
+                }
+                Extension::Limit(_) | Extension::Offset(_) | Extension::FetchFirst(_) => {
+                    let adjacent = row_limit
+                        .last()
+                        .is_some_and(|prev| src[prev.span.end..node.span.start].trim().is_empty());
+                    if !adjacent && !row_limit.is_empty() {
+                        self.row_limit(&row_limit);
+                        row_limit.clear();
+                    }
+                    row_limit.push(node.clone());
+                }
+                Extension::ILike if !self.to.supports(Feature::ILike) => {
+                    self.error("ILIKE", node.span.clone())
+                }
+                Extension::Serial(_) if !self.to.supports(Feature::Serial) => {
+                    self.error("SERIAL type", node.span.clone())
+                }
+                Extension::AutoIncrement if self.to.supports(Feature::AutoIncrement) => {
+                    let keyword = self.to.auto_increment_keyword().to_string();
+                    self.splice(first, last, keyword);
+                }
+                Extension::AutoIncrement => self.error("AUTO_INCREMENT", node.span.clone()),
+                // quoting and casts have their own passes
+                _ => {}
+            }
+        }
+        if !row_limit.is_empty() {
+            self.row_limit(&row_limit);
+        }
+    }
+
+    /// Count of `LIMIT offset, count` whose offset is at `i`
+    fn limit_count(&self, i: usize) -> Option<usize> {
+        let comma = self.next_significant(i)?;
+        let count = self.next_significant(comma)?;
+        let is_count = matches!(
+            self.tokens[count].kind,
+            TokenKind::Number | TokenKind::Parameter
+        );
+        Some(count).filter(|_| self.tokens[comma].is_operator(",") && is_count)
+    }
+
+    /// `LIMIT`/`OFFSET`/`FETCH FIRST` clause in the form of the target
+    fn row_limit(&mut self, nodes: &[ExtensionNode]) {
+        let comma_form = nodes.windows(2).any(|pair| pair[0].span == pair[1].span);
+        let mut limit = None;
+        let mut offset = None;
+        let mut fetch_first = false;
+        for node in nodes {
+            match &node.extension {
+                Extension::Limit(count) => limit = Some(count),
+                Extension::FetchFirst(count) => {
+                    limit = Some(count);
+                    fetch_first = true;
+                }
+                Extension::Offset(count) => offset = Some(count),
+                _ => {}
+            }
+        }
+        let form = if fetch_first {
+            Feature::FetchFirst
+        } else {
+            Feature::LimitOffset
+        };
+        if self.to.supports(form) && !comma_form {
+            return;
+        }
+
+        let mut parts = vec![];
+        if self.to.supports(Feature::LimitOffset) {
+            parts.extend(limit.map(|n| format!("LIMIT {}", n)));
+            parts.extend(offset.map(|n| format!("OFFSET {}", n)));
+        } else if self.to.supports(Feature::FetchFirst) {
+            parts.extend(offset.map(|n| format!("OFFSET {} ROWS", n)));
+            parts.extend(limit.map(|n| format!("FETCH FIRST {} ROWS ONLY", n)));
+        } else {
+            let span = nodes[0].span.start..nodes[nodes.len() - 1].span.end;
+            self.error("row limit clause", span);
+            return;
+        }
+
+        let (first, _) = self.token_range(&nodes[0].span);
+        let (_, last) = self.token_range(&nodes[nodes.len() - 1].span);
+        self.splice(first, last, parts.join(" "));
+    }
+
+    /// `a::t` into `CAST(a AS t)`
+    fn casts(&mut self) {
+        if self.to.supports(Feature::DoubleColonCast) {
+            return;
+        }
+        for i in 0..self.tokens.len() {
+            if !self.significant(i) || !self.tokens[i].is_operator("::") {
+                continue;
+            }
+            match (self.operand_before(i), self.operand_after(i)) {
+                (Some(first), Some(last)) => {
+                    let operand = self.text(first, i - 1);
+                    let data_type = self.text(i + 1, last);
+                    self.splice(first, last, format!("CAST({} AS {})", operand, data_type));
+                }
+                _ => self.error("'::' cast", self.tokens[i].span.clone()),
+            }
+        }
+    }
+
+    /// `a || b` into `CONCAT(a, b)` for targets without the operator,
+    /// and a `||` which is a logical OR in the source into `OR`
+    fn concat_operators(&mut self) {
+        if !self.from.has_concat_operator() && self.to.has_concat_operator() {
+            for i in 0..self.tokens.len() {
+                if self.significant(i) && self.tokens[i].is_operator("||") {
+                    let space =
+                        |j: usize| j < self.tokens.len() && !self.tokens[j].kind.is_trivia();
+                    self.out[i] = format!(
+                        "{}OR{}",
+                        if i > 0 && space(i - 1) { " " } else { "" },
+                        if space(i + 1) { " " } else { "" }
+                    );
+                }
+            }
+            return;
+        }
+        if self.to.has_concat_operator() || !self.from.has_concat_operator() {
+            return;
+        }
+        for i in 0..self.tokens.len() {
+            if !self.significant(i) || !self.tokens[i].is_operator("||") {
+                continue;
+            }
+            if !self.to.supports(Feature::ConcatFunction) {
+                self.error("concatenation", self.tokens[i].span.clone());
+                continue;
+            }
+            match (self.operand_before(i), self.operand_after(i)) {
+                (Some(first), Some(last)) => {
+                    let left = self.text(first, i - 1);
+                    let right = self.text(i + 1, last);
+                    // `a || b || c` becomes one call
+                    let text = match left.strip_suffix(')') {
+                        Some(args) if self.concat_group[first] => format!("{}, {})", args, right),
+                        _ => format!("CONCAT({}, {})", left, right),
+                    };
+                    self.splice(first, last, text);
+                    self.concat_group[first] = true;
+                }
+                _ => self.error("concatenation", self.tokens[i].span.clone()),
+            }
+        }
+    }
+}
+
+fn type_construct(data_type: DataType) -> &'static str {
+    match data_type {
+        DataType::TinyInt => "TINYINT type",
+        DataType::SmallInt => "SMALLINT type",
+        DataType::Integer => "INTEGER type",
+        DataType::BigInt => "BIGINT type",
+        DataType::Real => "REAL type",
+        DataType::Double => "DOUBLE PRECISION type",
+        DataType::Text => "TEXT type",
+        DataType::Binary => "binary string type",
+        DataType::Timestamp => "TIMESTAMP type",
+        DataType::Boolean => "BOOLEAN type",
+    }
+}
+
+#[test]
+fn transpile_quoting_and_limits() {
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92};
+
+    let sql = transpile(
+        "SELECT `a`, \"it's\" FROM t LIMIT 10 OFFSET 5 -- page",
+        &MySql,
+        &PostgreSql,
+    );
+    assert_eq!(
+        sql.unwrap(),
+        "SELECT \"a\", 'it''s' FROM t LIMIT 10 OFFSET 5 -- page"
+    );
+
+    let sql = transpile(
+        "SELECT a FROM t OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY",
+        &PostgreSql,
+        &MySql,
+    );
+    assert_eq!(sql.unwrap(), "SELECT a FROM t LIMIT 10 OFFSET 5");
+
+    let errors = transpile("SELECT a FROM t LIMIT 10", &MySql, &Sql92).unwrap_err();
+    assert_eq!(errors[0].construct, "row limit clause");
+    assert_eq!(errors[0].span, 16..24);
+    assert_eq!(
+        errors[0].to_string(),
+        "16..24: row limit clause can't be expressed in sql92"
+    );
+
+    for src in ["SELECT `\u{e9}", "SELECT 'x\u{e9}", "SELECT \""] {
+        let errors = transpile(src, &MySql, &Sql92).unwrap_err();
+        assert!(errors[0].construct.starts_with("unterminated"));
+    }
+}
+
+#[test]
+fn transpile_casts_and_concatenation() {
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92, Sqlite};
+
+    let sql = transpile("SELECT (a + 1)::int8, f(b)::text", &PostgreSql, &Sqlite);
+    assert_eq!(
+        sql.unwrap(),
+        "SELECT CAST((a + 1) AS INTEGER), CAST(f(b) AS text)"
+    );
+
+    let sql = transpile("SELECT a || 'x' || b FROM t", &PostgreSql, &MySql);
+    assert_eq!(sql.unwrap(), "SELECT CONCAT(a, 'x', b) FROM t");
+
+    let sql = transpile("SELECT CONCAT(a, CONCAT(b, 'y')) FROM t", &MySql, &Sql92);
+    assert_eq!(sql.unwrap(), "SELECT (a || (b || 'y')) FROM t");
+
+    let errors = transpile("SELECT a::text WHERE b ILIKE 'x'", &PostgreSql, &Sql92).unwrap_err();
+    let constructs: Vec<&str> = errors.iter().map(|e| e.construct).collect();
+    assert_eq!(constructs, vec!["TEXT type", "ILIKE"]);
+}
+
+#[test]
+fn transpile_column_definitions() {
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92, Sqlite};
+
+    let sql = transpile(
+        "CREATE TABLE t (id INT AUTO_INCREMENT, ts DATETIME, x DOUBLE)",
+        &MySql,
+        &Sqlite,
+    );
+    assert_eq!(
+        sql.unwrap(),
+        "CREATE TABLE t (id INTEGER AUTOINCREMENT, ts TEXT, x REAL)"
+    );
+
+    let sql = transpile(
+        "CREATE TABLE t (x DOUBLE PRECISION, b BOOL)",
+        &PostgreSql,
+        &MySql,
+    );
+    assert_eq!(sql.unwrap(), "CREATE TABLE t (x DOUBLE, b BOOLEAN)");
+
+    let errors = transpile("CREATE TABLE t (id SERIAL)", &PostgreSql, &Sqlite).unwrap_err();
+    assert_eq!(errors[0].construct, "SERIAL type");
+
+    let src = "CREATE TABLE t (x REAL, y FLOAT4)";
+    for dialect in [&PostgreSql as &dyn Dialect, &Sql92] {
+        let sql = transpile(src, dialect, dialect);
+        assert_eq!(sql.unwrap(), "CREATE TABLE t (x REAL, y REAL)");
+    }
+    let sql = transpile("CREATE TABLE t (x REAL)", &MySql, &PostgreSql);
+    assert_eq!(sql.unwrap(), "CREATE TABLE t (x DOUBLE PRECISION)");
+
+    let errors = transpile("CREATE TABLE t (x TINYINT)", &MySql, &PostgreSql).unwrap_err();
+    assert_eq!(errors[0].construct, "TINYINT type");
+}
+
+#[test]
+fn transpile_mysql_only_syntax() {
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92, Sqlite};
+
+    let sql = transpile("SELECT a FROM t LIMIT 10, 20", &MySql, &PostgreSql);
+    assert_eq!(sql.unwrap(), "SELECT a FROM t LIMIT 20 OFFSET 10");
+    let sql = transpile("SELECT a FROM t LIMIT 10 , ?", &MySql, &Sqlite);
+    assert_eq!(sql.unwrap(), "SELECT a FROM t LIMIT ? OFFSET 10");
+    let errors = transpile("SELECT a FROM t LIMIT 10, 20", &MySql, &Sql92).unwrap_err();
+    assert_eq!(errors[0].construct, "row limit clause");
+    assert_eq!(errors[0].span, 16..28);
+
+    let sql = transpile("SELECT a FROM t WHERE a = 1 || b||c", &MySql, &PostgreSql);
+    assert_eq!(sql.unwrap(), "SELECT a FROM t WHERE a = 1 OR b OR c");
+    let sql = transpile("SELECT a || b FROM t", &MySql, &MySql);
+    assert_eq!(sql.unwrap(), "SELECT a || b FROM t");
+}
+
+#[test]
+fn transpiled_sql_parses_in_target() {
+    use crate::ast::cst::CstNode;
+    use crate::ast::dialect::{MySql, PostgreSql, Sql92, Sqlite};
+
+    let cases: [(&str, &dyn Dialect, &dyn Dialect); 5] = [
+        (
+            "SELECT `a`, \"it's\" FROM t LIMIT 10 OFFSET 5 -- page",
+            &MySql,
+            &PostgreSql,
+        ),
+        (
+            "SELECT a FROM t OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY",
+            &PostgreSql,
+            &MySql,
+        ),
+        ("SELECT (a + 1)::int8, f(b)::text", &PostgreSql, &Sqlite),
+        ("SELECT CONCAT(a, CONCAT(b, 'y')) FROM t", &MySql, &Sql92),
+        (
+            "CREATE TABLE t (id INT AUTO_INCREMENT, ts DATETIME, x DOUBLE)",
+            &MySql,
+            &Sqlite,
+        ),
+    ];
+    for (src, from, to) in cases.iter() {
+        let sql = transpile(src, *from, *to).unwrap();
+        let tree = CstNode::parse_with(&sql, *to).unwrap();
+        assert_eq!(tree.to_string(), sql);
+        assert_eq!(tree.statements().len(), 1);
+        assert!(CstNode::parse_tolerant(&sql, *to).1.is_empty(), "{}", sql);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::ast::ast_node::AstNode;
+use std::collections::VecDeque;
+
+impl AstNode {
+    /// Node first, then its children, left to right
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        PreOrder {
+            stack: vec![(0, self)],
+        }
+    }
+
+    /// Children first, then the node itself
+    pub fn post_order(&self) -> PostOrder<'_> {
+        PostOrder {
+            stack: vec![(self, 0)],
+        }
+    }
+
+    /// Level by level, left to right
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        let mut queue = VecDeque::new();
+        queue.push_back((0, self));
+        BreadthFirst { queue }
+    }
+
+    /// Pre-order walk yielding `(depth, node)` pairs, root is 0
+    pub fn walk_with_depth(&self) -> WithDepth<'_> {
+        self.pre_order().with_depth()
+    }
+
+    pub fn cursor(&self) -> Cursor<'_> {
+        Cursor {
+            root: self,
+            stack: vec![],
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_> {
+        CursorMut {
+            root: self,
+            path: vec![],
+        }
+    }
+}
+
+pub struct PreOrder<'a> {
+    stack: Vec<(usize, &'a AstNode)>,
+}
+
+impl<'a> PreOrder<'a> {
+    /// Same walk, yielding `(depth, node)` pairs
+    pub fn with_depth(self) -> WithDepth<'a> {
+        WithDepth { inner: self }
+    }
+
+    fn next_with_depth(&mut self) -> Option<(usize, &'a AstNode)> {
+        let (depth, node) = self.stack.pop()?;
+        for child in node.children().iter().rev() {
+            self.stack.push((depth + 1, child));
+        }
+        Some((depth, node))
+    }
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = &'a AstNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|(_, node)| node)
+    }
+}
+
+pub struct WithDepth<'a> {
+    inner: PreOrder<'a>,
+}
+
+impl<'a> Iterator for WithDepth<'a> {
+    type Item = (usize, &'a AstNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_with_depth()
+    }
+}
+
+pub struct PostOrder<'a> {
+    /// node and the index of its next unvisited child
+    stack: Vec<(&'a AstNode, usize)>,
+}
+
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = &'a AstNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, next_child) = self.stack.last_mut()?;
+            let node: &'a AstNode = node;
+            match node.children().get(*next_child) {
+                Some(child) => {
+                    *next_child += 1;
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    return Some(node);
+                }
+            }
+        }
+    }
+}
+
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<(usize, &'a AstNode)>,
+}
+
+impl<'a> BreadthFirst<'a> {
+    /// Same walk, yielding `(depth, node)` pairs
+    pub fn with_depth(self) -> impl Iterator<Item = (usize, &'a AstNode)> {
+        let mut queue = self.queue;
+        std::iter::from_fn(move || {
+            let (depth, node) = queue.pop_front()?;
+            queue.extend(node.children().iter().map(|child| (depth + 1, child)));
+            Some((depth, node))
+        })
+    }
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = &'a AstNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.queue.pop_front()?;
+        self.queue
+            .extend(node.children().iter().map(|child| (depth + 1, child)));
+        Some(node)
+    }
+}
+
+/// Read-only position in a tree with parent and sibling navigation.
+/// Every `goto_*` returns false and stays in place when there is no such node.
+pub struct Cursor<'a> {
+    root: &'a AstNode,
+    /// ancestors of the current node below the root, with their child index
+    stack: Vec<(&'a AstNode, usize)>,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn node(&self) -> &'a AstNode {
+        self.stack.last().map_or(self.root, |(node, _)| node)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Child indexes from the root to the current node
+    pub fn path(&self) -> Vec<usize> {
+        self.stack.iter().map(|(_, i)| *i).collect()
+    }
+
+    pub fn parent(&self) -> Option<&'a AstNode> {
+        match self.stack.len() {
+            0 => None,
+            1 => Some(self.root),
+            n => Some(self.stack[n - 2].0),
+        }
+    }
+
+    pub fn goto_parent(&mut self) -> bool {
+        self.stack.pop().is_some()
+    }
+
+    pub fn goto_child(&mut self, index: usize) -> bool {
+        match self.node().children().get(index) {
+            Some(child) => {
+                self.stack.push((child, index));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn goto_first_child(&mut self) -> bool {
+        self.goto_child(0)
+    }
+
+    pub fn goto_last_child(&mut self) -> bool {
+        let len = self.node().children().len();
+        len > 0 && self.goto_child(len - 1)
+    }
+
+    pub fn goto_next_sibling(&mut self) -> bool {
+        self.goto_sibling(1)
+    }
+
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        self.goto_sibling(-1)
+    }
+
+    fn goto_sibling(&mut self, offset: isize) -> bool {
+        let parent = match self.parent() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let (_, index) = self.stack[self.stack.len() - 1];
+        let sibling_index = index as isize + offset;
+        if sibling_index < 0 {
+            return false;
+        }
+        match parent.children().get(sibling_index as usize) {
+            Some(sibling) => {
+                *self.stack.last_mut().unwrap() = (sibling, sibling_index as usize);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Mutable counterpart of `Cursor`, tracks the current node by path.
+/// Editing methods keep the cursor on a valid node.
+pub struct CursorMut<'a> {
+    root: &'a mut AstNode,
+    path: Vec<usize>,
+}
+
+impl<'a> CursorMut<'a> {
+    pub fn node(&self) -> &AstNode {
+        self.root.node_at(&self.path).unwrap()
+    }
+
+    pub fn node_mut(&mut self) -> &mut AstNode {
+        self.root.node_at_mut(&self.path).unwrap()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    pub fn goto_parent(&mut self) -> bool {
+        self.path.pop().is_some()
+    }
+
+    pub fn goto_child(&mut self, index: usize) -> bool {
+        if index >= self.node().children().len() {
+            return false;
+        }
+        self.path.push(index);
+        true
+    }
+
+    pub fn goto_first_child(&mut self) -> bool {
+        self.goto_child(0)
+    }
+
+    pub fn goto_next_sibling(&mut self) -> bool {
+        let index = match self.path.last() {
+            Some(index) => *index + 1,
+            None => return false,
+        };
+        self.path.pop();
+        let exists = index < self.node().children().len();
+        self.path.push(if exists { index } else { index - 1 });
+        exists
+    }
+
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some(index) if *index > 0 => {
+                *index -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Insert node as sibling right after the current one
+    pub fn insert_after(&mut self, node: AstNode) -> bool {
+        let (index, parent_path) = match self.path.split_last() {
+            Some((index, parent_path)) => (*index, parent_path),
+            None => return false,
+        };
+        self.root
+            .node_at_mut(parent_path)
+            .unwrap()
+            .insert(index + 1, node);
+        true
+    }
+
+    /// Insert node as sibling right before the current one,
+    /// the cursor stays on the same node
+    pub fn insert_before(&mut self, node: AstNode) -> bool {
+        let (index, parent_path) = match self.path.split_last() {
+            Some((index, parent_path)) => (*index, parent_path),
+            None => return false,
+        };
+        self.root
+            .node_at_mut(parent_path)
+            .unwrap()
+            .insert(index, node);
+        *self.path.last_mut().unwrap() += 1;
+        true
+    }
+
+    /// Swap the current subtree with node, returns the old subtree
+    pub fn replace(&mut self, node: AstNode) -> AstNode {
+        std::mem::replace(self.node_mut(), node)
+    }
+
+    /// Detach the current subtree and move to its parent.
+    /// The root can't be detached.
+    pub fn detach(&mut self) -> Option<AstNode> {
+        let detached = self.root.detach(&self.path)?;
+        self.path.pop();
+        Some(detached)
+    }
+}
+
+#[cfg(test)]
+fn sample_tree() -> AstNode {
+    //      a
+    //    / | \
+    //   b  e  f
+    //  / \     \
+    // c   d     g
+    let mut b = AstNode::from("b");
+    b.add(AstNode::from("c")).add(AstNode::from("d"));
+    let mut f = AstNode::from("f");
+    f.add(AstNode::from("g"));
+    let mut a = AstNode::from("a");
+    a.add(b).add(AstNode::from("e")).add(f);
+    a
+}
+
+#[cfg(test)]
+fn names<'a>(nodes: impl Iterator<Item = &'a AstNode>) -> String {
+    nodes.map(|node| node.name.as_str()).collect()
+}
+
+#[test]
+fn traversal_orders() {
+    let tree = sample_tree();
+
+    assert_eq!(names(tree.pre_order()), "abcdefg");
+    assert_eq!(names(tree.post_order()), "cdbegfa");
+    assert_eq!(names(tree.breadth_first()), "abefcdg");
+
+    let depths: Vec<usize> = tree.walk_with_depth().map(|(d, _)| d).collect();
+    assert_eq!(depths, vec![0, 1, 2, 2, 1, 1, 2]);
+
+    let bfs_depths: Vec<usize> = tree.breadth_first().with_depth().map(|(d, _)| d).collect();
+    assert_eq!(bfs_depths, vec![0, 1, 1, 1, 2, 2, 2]);
+}
+
+#[test]
+fn cursor_navigation() {
+    let tree = sample_tree();
+    let mut cursor = tree.cursor();
+
+    assert!(!cursor.goto_parent());
+    assert!(!cursor.goto_next_sibling());
+    assert!(cursor.goto_first_child());
+    assert!(cursor.goto_last_child());
+    assert_eq!(cursor.node().name, "d");
+    assert_eq!(cursor.parent().unwrap().name, "b");
+    assert_eq!(cursor.path(), vec![0, 1]);
+    assert!(!cursor.goto_next_sibling());
+    assert!(cursor.goto_prev_sibling());
+    assert_eq!(cursor.node().name, "c");
+    assert!(cursor.goto_parent());
+    assert!(cursor.goto_next_sibling());
+    assert!(cursor.goto_next_sibling());
+    assert_eq!(cursor.node().name, "f");
+    assert_eq!(cursor.depth(), 1);
+}
+
+#[test]
+fn cursor_mut_edits() {
+    let mut tree = sample_tree();
+    {
+        let mut cursor = tree.cursor_mut();
+        assert!(cursor.detach().is_none());
+
+        cursor.goto_child(1);
+        assert_eq!(cursor.node().name, "e");
+        assert!(cursor.insert_before(AstNode::from("x")));
+        assert_eq!(cursor.node().name, "e");
+        assert!(cursor.insert_after(AstNode::from("y")));
+
+        let old = cursor.replace(AstNode::from("z"));
+        assert_eq!(old.name, "e");
+
+        assert!(cursor.goto_next_sibling());
+        assert!(cursor.goto_next_sibling());
+        assert!(!cursor.goto_next_sibling());
+        assert_eq!(cursor.node().name, "f");
+
+        let detached = cursor.detach().unwrap();
+        assert_eq!(detached.children()[0].name, "g");
+        assert_eq!(cursor.depth(), 0);
+
+        cursor.node_mut().name = String::from("root");
+    }
+
+    assert_eq!(names(tree.pre_order()), "rootbcdxzy");
+}
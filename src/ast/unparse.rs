@@ -0,0 +1,114 @@
+use crate::ast::action::Action;
+use crate::ast::identifier::{Identifier, QualifiedName};
+use crate::ast::paren::{LPAREN, RPAREN};
+use crate::ast::reserved_word as word;
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::set_quantifier::SetQuantifier;
+
+/// SQL-92 text of a typed node, parsing it gives back the same node.
+/// Identifiers are written in their shortest spelling.
+pub trait ToSql {
+    fn to_sql(&self) -> String;
+}
+
+impl ToSql for Identifier {
+    fn to_sql(&self) -> String {
+        self.to_minimal_sql()
+    }
+}
+
+impl ToSql for QualifiedName {
+    fn to_sql(&self) -> String {
+        let parts: Vec<String> = self.parts().iter().map(ToSql::to_sql).collect();
+        parts.join(".")
+    }
+}
+
+impl ToSql for SetQuantifier {
+    fn to_sql(&self) -> String {
+        match self {
+            SetQuantifier::DISTINCT => word::DISTINCT,
+            SetQuantifier::ALL => word::ALL,
+        }
+        .to_string()
+    }
+}
+
+impl ToSql for SelectList {
+    fn to_sql(&self) -> String {
+        match self {
+            SelectList::ASTERISK => "*".to_string(),
+            SelectList::SUBLISTS(sublists) => {
+                let columns: Vec<String> = sublists.iter().map(|s| s.0.to_sql()).collect();
+                columns.join(", ")
+            }
+        }
+    }
+}
+
+impl ToSql for Action {
+    fn to_sql(&self) -> String {
+        let (action, columns) = match self {
+            Action::SELECT => (word::SELECT, None),
+            Action::DELETE => (word::DELETE, None),
+            Action::USAGE => (word::USAGE, None),
+            Action::INSERT(_, list, _) => (word::INSERT, Some(list)),
+            Action::UPDATE(_, list, _) => (word::UPDATE, Some(list)),
+            Action::REFERENCES(_, list, _) => (word::REFERENCES, Some(list)),
+        };
+        match columns {
+            Some(list) if !list.is_empty() => {
+                let names: Vec<String> = list.iter().map(ToSql::to_sql).collect();
+                format!("{} {}{}{}", action, LPAREN, names.join(", "), RPAREN)
+            }
+            _ => action.to_string(),
+        }
+    }
+}
+
+impl ToSql for SelectStatementSingleRow {
+    fn to_sql(&self) -> String {
+        let (action, quantifier, list) = self;
+        match quantifier {
+            Some(quantifier) => format!(
+                "{} {} {}",
+                action.to_sql(),
+                quantifier.to_sql(),
+                list.to_sql()
+            ),
+            None => format!("{} {}", action.to_sql(), list.to_sql()),
+        }
+    }
+}
+
+#[test]
+fn unparse_round_trips() {
+    use crate::ast::cst::CstNode;
+
+    let statement = (
+        Action::SELECT,
+        Some(SetQuantifier::DISTINCT),
+        SelectList::ASTERISK,
+    );
+    assert_eq!(statement.to_sql(), "SELECT DISTINCT *");
+    let tree = CstNode::parse(&statement.to_sql()).unwrap();
+    assert_eq!(
+        tree.statements()[0].select_statement_single_row(),
+        Some(statement)
+    );
+
+    let action = Action::UPDATE(
+        LPAREN.to_string(),
+        vec![
+            Identifier::Regular("a".to_string()),
+            Identifier::Regular("order".to_string()),
+            Identifier::Delimited("Mixed".to_string()),
+        ],
+        RPAREN.to_string(),
+    );
+    assert_eq!(action.to_sql(), "UPDATE (a, \"ORDER\", \"Mixed\")");
+    let grant = format!("GRANT {} ON t TO u", action.to_sql());
+    let tree = CstNode::parse(&grant).unwrap();
+    assert_eq!(tree.statements()[0].action(), Some(action));
+}
@@ -0,0 +1,468 @@
+use crate::ast::action::Action;
+use crate::ast::ast_node::AstNode;
+use crate::ast::column_name_list::ColumnNameList;
+use crate::ast::identifier::Identifier;
+use crate::ast::numeric_primary::NumericPrimary;
+use crate::ast::select_list::SelectList;
+use crate::ast::select_statement_single_row::SelectStatementSingleRow;
+use crate::ast::set_quantifier::SetQuantifier;
+use crate::ast::unsigned_value_specification::{
+    ExactNumericLiteral, UnsignedNumericLiteral, UnsignedValueSpecification,
+};
+use crate::ast::value_expression::ValueExpression;
+
+/// Read-only AST walker.
+/// Every method falls back to the matching `walk_*` fn,
+/// so an impl overrides only the nodes it cares about.
+pub trait Visit {
+    fn visit_ast_node(&mut self, node: &AstNode) {
+        walk_ast_node(self, node)
+    }
+
+    fn visit_select_statement_single_row(&mut self, stmt: &SelectStatementSingleRow) {
+        walk_select_statement_single_row(self, stmt)
+    }
+
+    fn visit_action(&mut self, action: &Action) {
+        walk_action(self, action)
+    }
+
+    fn visit_paren(&mut self, _paren: &str) {}
+
+    fn visit_column_name_list(&mut self, list: &ColumnNameList) {
+        walk_column_name_list(self, list)
+    }
+
+    fn visit_column_name(&mut self, _name: &Identifier) {}
+
+    fn visit_set_quantifier(&mut self, _quantifier: &SetQuantifier) {}
+
+    fn visit_select_list(&mut self, _list: &SelectList) {}
+
+    fn visit_value_expression(&mut self, expr: &ValueExpression) {
+        walk_value_expression(self, expr)
+    }
+
+    fn visit_numeric_primary(&mut self, primary: &NumericPrimary) {
+        walk_numeric_primary(self, primary)
+    }
+
+    fn visit_unsigned_value_specification(&mut self, spec: &UnsignedValueSpecification) {
+        walk_unsigned_value_specification(self, spec)
+    }
+
+    fn visit_unsigned_numeric_literal(&mut self, literal: &UnsignedNumericLiteral) {
+        walk_unsigned_numeric_literal(self, literal)
+    }
+
+    fn visit_exact_numeric_literal(&mut self, _literal: &ExactNumericLiteral) {}
+}
+
+pub fn walk_ast_node<V: Visit + ?Sized>(visitor: &mut V, node: &AstNode) {
+    for child in node.children() {
+        visitor.visit_ast_node(child);
+    }
+}
+
+pub fn walk_select_statement_single_row<V: Visit + ?Sized>(
+    visitor: &mut V,
+    stmt: &SelectStatementSingleRow,
+) {
+    let (action, quantifier, list) = stmt;
+    visitor.visit_action(action);
+    if let Some(quantifier) = quantifier {
+        visitor.visit_set_quantifier(quantifier);
+    }
+    visitor.visit_select_list(list);
+}
+
+pub fn walk_action<V: Visit + ?Sized>(visitor: &mut V, action: &Action) {
+    match action {
+        Action::INSERT(lp, list, rp)
+        | Action::UPDATE(lp, list, rp)
+        | Action::REFERENCES(lp, list, rp) => {
+            visitor.visit_paren(lp);
+            visitor.visit_column_name_list(list);
+            visitor.visit_paren(rp);
+        }
+        Action::SELECT | Action::DELETE | Action::USAGE => {}
+    }
+}
+
+pub fn walk_column_name_list<V: Visit + ?Sized>(visitor: &mut V, list: &ColumnNameList) {
+    for name in list {
+        visitor.visit_column_name(name);
+    }
+}
+
+pub fn walk_value_expression<V: Visit + ?Sized>(_visitor: &mut V, expr: &ValueExpression) {
+    match *expr {}
+}
+
+pub fn walk_numeric_primary<V: Visit + ?Sized>(_visitor: &mut V, primary: &NumericPrimary) {
+    match *primary {}
+}
+
+pub fn walk_unsigned_value_specification<V: Visit + ?Sized>(
+    _visitor: &mut V,
+    spec: &UnsignedValueSpecification,
+) {
+    match *spec {}
+}
+
+pub fn walk_unsigned_numeric_literal<V: Visit + ?Sized>(
+    visitor: &mut V,
+    literal: &UnsignedNumericLiteral,
+) {
+    match literal {
+        UnsignedNumericLiteral::ExactNumericLiteral(exact) => {
+            visitor.visit_exact_numeric_literal(exact)
+        }
+    }
+}
+
+/// In-place AST walker.
+/// Same shape as `Visit`, but every node is handed out as `&mut`.
+pub trait VisitMut {
+    fn visit_ast_node_mut(&mut self, node: &mut AstNode) {
+        walk_ast_node_mut(self, node)
+    }
+
+    fn visit_select_statement_single_row_mut(&mut self, stmt: &mut SelectStatementSingleRow) {
+        walk_select_statement_single_row_mut(self, stmt)
+    }
+
+    fn visit_action_mut(&mut self, action: &mut Action) {
+        walk_action_mut(self, action)
+    }
+
+    fn visit_paren_mut(&mut self, _paren: &mut String) {}
+
+    fn visit_column_name_list_mut(&mut self, list: &mut ColumnNameList) {
+        walk_column_name_list_mut(self, list)
+    }
+
+    fn visit_column_name_mut(&mut self, _name: &mut Identifier) {}
+
+    fn visit_set_quantifier_mut(&mut self, _quantifier: &mut SetQuantifier) {}
+
+    fn visit_select_list_mut(&mut self, _list: &mut SelectList) {}
+
+    fn visit_value_expression_mut(&mut self, expr: &mut ValueExpression) {
+        walk_value_expression_mut(self, expr)
+    }
+
+    fn visit_numeric_primary_mut(&mut self, primary: &mut NumericPrimary) {
+        walk_numeric_primary_mut(self, primary)
+    }
+
+    fn visit_unsigned_value_specification_mut(&mut self, spec: &mut UnsignedValueSpecification) {
+        walk_unsigned_value_specification_mut(self, spec)
+    }
+
+    fn visit_unsigned_numeric_literal_mut(&mut self, literal: &mut UnsignedNumericLiteral) {
+        walk_unsigned_numeric_literal_mut(self, literal)
+    }
+
+    fn visit_exact_numeric_literal_mut(&mut self, _literal: &mut ExactNumericLiteral) {}
+}
+
+pub fn walk_ast_node_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut AstNode) {
+    for child in node.children_mut() {
+        visitor.visit_ast_node_mut(child);
+    }
+}
+
+pub fn walk_select_statement_single_row_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    stmt: &mut SelectStatementSingleRow,
+) {
+    let (action, quantifier, list) = stmt;
+    visitor.visit_action_mut(action);
+    if let Some(quantifier) = quantifier {
+        visitor.visit_set_quantifier_mut(quantifier);
+    }
+    visitor.visit_select_list_mut(list);
+}
+
+pub fn walk_action_mut<V: VisitMut + ?Sized>(visitor: &mut V, action: &mut Action) {
+    match action {
+        Action::INSERT(lp, list, rp)
+        | Action::UPDATE(lp, list, rp)
+        | Action::REFERENCES(lp, list, rp) => {
+            visitor.visit_paren_mut(lp);
+            visitor.visit_column_name_list_mut(list);
+            visitor.visit_paren_mut(rp);
+        }
+        Action::SELECT | Action::DELETE | Action::USAGE => {}
+    }
+}
+
+pub fn walk_column_name_list_mut<V: VisitMut + ?Sized>(visitor: &mut V, list: &mut ColumnNameList) {
+    for name in list.iter_mut() {
+        visitor.visit_column_name_mut(name);
+    }
+}
+
+pub fn walk_value_expression_mut<V: VisitMut + ?Sized>(
+    _visitor: &mut V,
+    expr: &mut ValueExpression,
+) {
+    match *expr {}
+}
+
+pub fn walk_numeric_primary_mut<V: VisitMut + ?Sized>(
+    _visitor: &mut V,
+    primary: &mut NumericPrimary,
+) {
+    match *primary {}
+}
+
+pub fn walk_unsigned_value_specification_mut<V: VisitMut + ?Sized>(
+    _visitor: &mut V,
+    spec: &mut UnsignedValueSpecification,
+) {
+    match *spec {}
+}
+
+pub fn walk_unsigned_numeric_literal_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    literal: &mut UnsignedNumericLiteral,
+) {
+    match literal {
+        UnsignedNumericLiteral::ExactNumericLiteral(exact) => {
+            visitor.visit_exact_numeric_literal_mut(exact)
+        }
+    }
+}
+
+/// Rebuilding AST transformer.
+/// Takes every node by value and returns its replacement,
+/// by default the same node with folded children.
+pub trait Fold {
+    fn fold_ast_node(&mut self, node: AstNode) -> AstNode {
+        fold_ast_node(self, node)
+    }
+
+    fn fold_select_statement_single_row(
+        &mut self,
+        stmt: SelectStatementSingleRow,
+    ) -> SelectStatementSingleRow {
+        fold_select_statement_single_row(self, stmt)
+    }
+
+    fn fold_action(&mut self, action: Action) -> Action {
+        fold_action(self, action)
+    }
+
+    fn fold_paren(&mut self, paren: String) -> String {
+        paren
+    }
+
+    fn fold_column_name_list(&mut self, list: ColumnNameList) -> ColumnNameList {
+        list.into_iter()
+            .map(|name| self.fold_column_name(name))
+            .collect()
+    }
+
+    fn fold_column_name(&mut self, name: Identifier) -> Identifier {
+        name
+    }
+
+    fn fold_set_quantifier(&mut self, quantifier: SetQuantifier) -> SetQuantifier {
+        quantifier
+    }
+
+    fn fold_select_list(&mut self, list: SelectList) -> SelectList {
+        list
+    }
+
+    fn fold_value_expression(&mut self, expr: ValueExpression) -> ValueExpression {
+        fold_value_expression(self, expr)
+    }
+
+    fn fold_numeric_primary(&mut self, primary: NumericPrimary) -> NumericPrimary {
+        fold_numeric_primary(self, primary)
+    }
+
+    fn fold_unsigned_value_specification(
+        &mut self,
+        spec: UnsignedValueSpecification,
+    ) -> UnsignedValueSpecification {
+        fold_unsigned_value_specification(self, spec)
+    }
+
+    fn fold_unsigned_numeric_literal(
+        &mut self,
+        literal: UnsignedNumericLiteral,
+    ) -> UnsignedNumericLiteral {
+        fold_unsigned_numeric_literal(self, literal)
+    }
+
+    fn fold_exact_numeric_literal(&mut self, literal: ExactNumericLiteral) -> ExactNumericLiteral {
+        literal
+    }
+}
+
+pub fn fold_ast_node<F: Fold + ?Sized>(folder: &mut F, mut node: AstNode) -> AstNode {
+    let children = std::mem::take(node.children_mut());
+    for child in children {
+        let child = folder.fold_ast_node(child);
+        node.add(child);
+    }
+    node
+}
+
+pub fn fold_select_statement_single_row<F: Fold + ?Sized>(
+    folder: &mut F,
+    stmt: SelectStatementSingleRow,
+) -> SelectStatementSingleRow {
+    let (action, quantifier, list) = stmt;
+    (
+        folder.fold_action(action),
+        quantifier.map(|q| folder.fold_set_quantifier(q)),
+        folder.fold_select_list(list),
+    )
+}
+
+pub fn fold_action<F: Fold + ?Sized>(folder: &mut F, action: Action) -> Action {
+    match action {
+        Action::INSERT(lp, list, rp) => Action::INSERT(
+            folder.fold_paren(lp),
+            folder.fold_column_name_list(list),
+            folder.fold_paren(rp),
+        ),
+        Action::UPDATE(lp, list, rp) => Action::UPDATE(
+            folder.fold_paren(lp),
+            folder.fold_column_name_list(list),
+            folder.fold_paren(rp),
+        ),
+        Action::REFERENCES(lp, list, rp) => Action::REFERENCES(
+            folder.fold_paren(lp),
+            folder.fold_column_name_list(list),
+            folder.fold_paren(rp),
+        ),
+        other => other,
+    }
+}
+
+pub fn fold_value_expression<F: Fold + ?Sized>(
+    _folder: &mut F,
+    expr: ValueExpression,
+) -> ValueExpression {
+    match expr {}
+}
+
+pub fn fold_numeric_primary<F: Fold + ?Sized>(
+    _folder: &mut F,
+    primary: NumericPrimary,
+) -> NumericPrimary {
+    match primary {}
+}
+
+pub fn fold_unsigned_value_specification<F: Fold + ?Sized>(
+    _folder: &mut F,
+    spec: UnsignedValueSpecification,
+) -> UnsignedValueSpecification {
+    match spec {}
+}
+
+pub fn fold_unsigned_numeric_literal<F: Fold + ?Sized>(
+    folder: &mut F,
+    literal: UnsignedNumericLiteral,
+) -> UnsignedNumericLiteral {
+    match literal {
+        UnsignedNumericLiteral::ExactNumericLiteral(exact) => {
+            UnsignedNumericLiteral::ExactNumericLiteral(folder.fold_exact_numeric_literal(exact))
+        }
+    }
+}
+
+#[test]
+fn visit_counts_ast_nodes() {
+    struct Counter(usize);
+    impl Visit for Counter {
+        fn visit_ast_node(&mut self, node: &AstNode) {
+            self.0 += 1;
+            walk_ast_node(self, node)
+        }
+    }
+
+    let mut root = AstNode::from("update");
+    let mut lp = AstNode::from("(");
+    lp.add(AstNode::from("bob"));
+    root.add(lp).add(AstNode::from(")"));
+
+    let mut counter = Counter(0);
+    counter.visit_ast_node(&root);
+    assert_eq!(counter.0, 4);
+}
+
+#[test]
+fn visit_collects_column_names() {
+    struct Columns(Vec<String>);
+    impl Visit for Columns {
+        fn visit_column_name(&mut self, name: &Identifier) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    let stmt: SelectStatementSingleRow = (
+        Action::UPDATE(
+            "(".to_string(),
+            vec![
+                Identifier::Regular("a".to_string()),
+                Identifier::Delimited("B".to_string()),
+            ],
+            ")".to_string(),
+        ),
+        Some(SetQuantifier::ALL),
+        SelectList::ASTERISK,
+    );
+
+    let mut columns = Columns(vec![]);
+    columns.visit_select_statement_single_row(&stmt);
+    assert_eq!(columns.0, vec!["a".to_string(), "\"B\"".to_string()]);
+}
+
+#[test]
+fn visit_mut_renames_nodes() {
+    struct Upper;
+    impl VisitMut for Upper {
+        fn visit_ast_node_mut(&mut self, node: &mut AstNode) {
+            node.name = node.name_uppercase();
+            walk_ast_node_mut(self, node)
+        }
+    }
+
+    let mut root = AstNode::from("select");
+    root.add(AstNode::from("a"));
+    Upper.visit_ast_node_mut(&mut root);
+
+    assert_eq!(root.name, "SELECT");
+    assert_eq!(root.children()[0].name, "A");
+}
+
+#[test]
+fn fold_rewrites_action_columns() {
+    struct Quote;
+    impl Fold for Quote {
+        fn fold_column_name(&mut self, name: Identifier) -> Identifier {
+            Identifier::Delimited(name.value().to_uppercase())
+        }
+    }
+
+    let action = Action::INSERT(
+        "(".to_string(),
+        vec![Identifier::Regular("a".to_string())],
+        ")".to_string(),
+    );
+    let action = Quote.fold_action(action);
+    match action {
+        Action::INSERT(_, columns, _) => {
+            assert!(columns[0].is_delimited());
+            assert_eq!(columns[0].value(), "A");
+        }
+        _ => panic!("expect INSERT"),
+    }
+}
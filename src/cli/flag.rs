@@ -0,0 +1,89 @@
+use crate::ast::flagger::{flag_source, Level, StatementLevel};
+use std::io::Read;
+
+/// `flag [FILE...]`
+///
+/// SQL-92 flagger: prints `file:line:column: level` for every statement,
+/// then one indented line per feature which raises its level.
+/// The exit code is 1 when some statement uses a vendor extension.
+pub fn run(args: &[String]) -> Result<i32, String> {
+    if let Some(option) = args.iter().find(|arg| arg.starts_with("--")) {
+        return Err(format!("flag: unknown option `{}`", option));
+    }
+
+    let mut sources = vec![];
+    if args.is_empty() {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| format!("flag: can't read stdin: {}", e))?;
+        sources.push(("<stdin>".to_string(), source));
+    }
+    for file in args {
+        let source = std::fs::read_to_string(file)
+            .map_err(|e| format!("flag: can't read {}: {}", file, e))?;
+        sources.push((file.clone(), source));
+    }
+
+    let mut extended = false;
+    for (name, source) in &sources {
+        for statement in flag_source(source) {
+            extended |= statement.flags.iter().any(|f| f.level == Level::Extension);
+            print!("{}", describe(name, source, &statement));
+        }
+    }
+    Ok(if extended { 1 } else { 0 })
+}
+
+/// 1-based line and column of a byte offset, the column in chars
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+fn describe(name: &str, source: &str, statement: &StatementLevel) -> String {
+    let (line, column) = line_column(source, statement.span.start);
+    let mut text = format!(
+        "{}:{}:{}: {}\n",
+        name,
+        line,
+        column,
+        statement.level.as_str()
+    );
+    for flag in &statement.flags {
+        let at = match &flag.span {
+            Some(span) => {
+                let (line, column) = line_column(source, span.start);
+                format!(" at {}:{}", line, column)
+            }
+            None => String::new(),
+        };
+        text.push_str(&format!(
+            "    {}: {}{}\n",
+            flag.level.as_str(),
+            flag.feature,
+            at
+        ));
+    }
+    text
+}
+
+#[test]
+fn flag_output() {
+    let source = "SELECT a FROM t;\nSELECT \u{e9}, CAST(a AS INTEGER) FROM t LIMIT 5";
+    let statements = flag_source(source);
+    assert_eq!(
+        describe("a.sql", source, &statements[0]),
+        "a.sql:1:1: entry\n"
+    );
+    assert_eq!(
+        describe("a.sql", source, &statements[1]),
+        "a.sql:2:1: extension\n    intermediate: CAST specification at 2:11\n    \
+         extension: row limit clause at 2:37\n"
+    );
+    assert!(run(&["--bogus".to_string()]).is_err());
+}
@@ -0,0 +1,142 @@
+use crate::ast::dialect;
+use crate::ast::formatter::{format, CommaStyle, FormatOptions, KeywordCase};
+use std::io::Read;
+
+/// `fmt [--check] [options] [FILE...]`
+///
+/// Files are rewritten in place, without files stdin is formatted to stdout.
+/// With `--check` nothing is written; the exit code is 1 when
+/// some input isn't formatted, and the names of such files are printed.
+pub fn run(args: &[String]) -> Result<i32, String> {
+    let (options, check, files) = parse_args(args)?;
+
+    if files.is_empty() {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| format!("fmt: can't read stdin: {}", e))?;
+        let formatted = format_source(&source, &options, "<stdin>")?;
+        if check {
+            return Ok(if formatted == source { 0 } else { 1 });
+        }
+        print!("{}", formatted);
+        return Ok(0);
+    }
+
+    let mut unformatted = 0;
+    for file in &files {
+        if !check_file(file, &options, check)? {
+            unformatted += 1;
+            if check {
+                println!("{}", file);
+            }
+        }
+    }
+    Ok(if check && unformatted > 0 { 1 } else { 0 })
+}
+
+fn parse_args(args: &[String]) -> Result<(FormatOptions, bool, Vec<String>), String> {
+    let mut options = FormatOptions::default();
+    let mut check = false;
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("fmt: `{}` needs a value", name))
+        };
+        match arg.as_str() {
+            "--check" => check = true,
+            "--keyword-case" => {
+                options.keyword_case = match value(arg)? {
+                    "upper" => KeywordCase::Upper,
+                    "lower" => KeywordCase::Lower,
+                    "preserve" => KeywordCase::Preserve,
+                    other => return Err(format!("fmt: unknown keyword case `{}`", other)),
+                }
+            }
+            "--comma" => {
+                options.comma = match value(arg)? {
+                    "trailing" => CommaStyle::Trailing,
+                    "leading" => CommaStyle::Leading,
+                    other => return Err(format!("fmt: unknown comma style `{}`", other)),
+                }
+            }
+            "--indent" => options.indent = number(arg, value(arg)?)?,
+            "--width" => options.width = number(arg, value(arg)?)?,
+            "--dialect" => {
+                let name = value(arg)?;
+                options.dialect = dialect::by_name(name)
+                    .ok_or_else(|| format!("fmt: unknown dialect `{}`", name))?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("fmt: unknown option `{}`", arg)),
+            _ => files.push(arg.clone()),
+        }
+    }
+    Ok((options, check, files))
+}
+
+fn number(name: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("fmt: `{}` needs a number, got `{}`", name, value))
+}
+
+fn format_source(source: &str, options: &FormatOptions, name: &str) -> Result<String, String> {
+    format(source, options).map_err(|e| {
+        format!(
+            "fmt: {}: {} at {}..{}",
+            name, e.message, e.span.start, e.span.end
+        )
+    })
+}
+
+/// Whether the file was already formatted, rewrites it unless `check`
+fn check_file(path: &str, options: &FormatOptions, check: bool) -> Result<bool, String> {
+    let source =
+        std::fs::read_to_string(path).map_err(|e| format!("fmt: can't read {}: {}", path, e))?;
+    let formatted = format_source(&source, options, path)?;
+    if formatted == source {
+        return Ok(true);
+    }
+    if !check {
+        std::fs::write(path, formatted).map_err(|e| format!("fmt: can't write {}: {}", path, e))?;
+    }
+    Ok(false)
+}
+
+#[test]
+fn fmt_options() {
+    let args: Vec<String> = "--check --keyword-case lower --width 40 --comma leading a.sql"
+        .split(' ')
+        .map(String::from)
+        .collect();
+    let (options, check, files) = parse_args(&args).unwrap();
+    assert!(check);
+    assert_eq!(options.keyword_case, KeywordCase::Lower);
+    assert_eq!(options.width, 40);
+    assert_eq!(options.comma, CommaStyle::Leading);
+    assert_eq!(files, vec!["a.sql"]);
+
+    assert!(parse_args(&["--width".to_string()]).is_err());
+    assert!(parse_args(&["--indent".to_string(), "x".to_string()]).is_err());
+    assert!(parse_args(&["--bogus".to_string()]).is_err());
+}
+
+#[test]
+fn fmt_check_and_rewrite_file() {
+    let path = std::env::temp_dir().join(format!("qdb-fmt-{}.sql", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "select a from t").unwrap();
+    let options = FormatOptions::default();
+
+    assert!(!check_file(path, &options, true).unwrap());
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "select a from t");
+    assert!(!check_file(path, &options, false).unwrap());
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "SELECT a\nFROM t\n");
+    assert!(check_file(path, &options, true).unwrap());
+
+    std::fs::remove_file(path).unwrap();
+}
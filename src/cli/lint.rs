@@ -0,0 +1,123 @@
+use crate::ast::dialect;
+use crate::ast::linter::{Diagnostic, LintConfig, Linter, Severity};
+use std::io::Read;
+
+/// Project configuration read when `--config` isn't given
+const CONFIG_FILE: &str = "qdb-lint.json";
+
+/// `lint [--config FILE] [--dialect <name>] [FILE...]`
+///
+/// Prints `file:line:column: severity[rule]: message` for every diagnostic,
+/// the exit code is 1 when some diagnostic is a warning or an error.
+pub fn run(args: &[String]) -> Result<i32, String> {
+    let (config, files) = parse_args(args)?;
+    let linter = Linter::new(&config);
+
+    let mut sources = vec![];
+    if files.is_empty() {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| format!("lint: can't read stdin: {}", e))?;
+        sources.push(("<stdin>".to_string(), source));
+    }
+    for file in files {
+        let source = std::fs::read_to_string(&file)
+            .map_err(|e| format!("lint: can't read {}: {}", file, e))?;
+        sources.push((file, source));
+    }
+
+    let mut failed = false;
+    for (name, source) in &sources {
+        for diagnostic in linter.lint(source) {
+            failed |= diagnostic.severity >= Severity::Warning;
+            println!("{}", describe(name, source, &diagnostic));
+        }
+    }
+    Ok(if failed { 1 } else { 0 })
+}
+
+fn parse_args(args: &[String]) -> Result<(LintConfig, Vec<String>), String> {
+    let mut config_file = None;
+    let mut dialect_name = None;
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                config_file = Some(args.next().ok_or("lint: `--config` needs a file")?);
+            }
+            "--dialect" => {
+                dialect_name = Some(args.next().ok_or("lint: `--dialect` needs a name")?);
+            }
+            _ if arg.starts_with("--") => return Err(format!("lint: unknown option `{}`", arg)),
+            _ => files.push(arg.clone()),
+        }
+    }
+
+    let mut config = match config_file {
+        Some(path) => read_config(path)?,
+        None if std::path::Path::new(CONFIG_FILE).exists() => read_config(CONFIG_FILE)?,
+        None => LintConfig::default(),
+    };
+    if let Some(name) = dialect_name {
+        config.dialect =
+            dialect::by_name(name).ok_or_else(|| format!("lint: unknown dialect `{}`", name))?;
+    }
+    Ok((config, files))
+}
+
+fn read_config(path: &str) -> Result<LintConfig, String> {
+    let source =
+        std::fs::read_to_string(path).map_err(|e| format!("lint: can't read {}: {}", path, e))?;
+    LintConfig::parse(&source).map_err(|e| format!("{} in {}", e, path))
+}
+
+/// One line per diagnostic, with 1-based line and column, the column in chars
+fn describe(name: &str, source: &str, diagnostic: &Diagnostic) -> String {
+    let before = &source[..diagnostic.span.start];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+        .chars()
+        .count()
+        + 1;
+    format!(
+        "{}:{}:{}: {}[{}]: {}",
+        name, line, column, diagnostic.severity, diagnostic.rule, diagnostic.message
+    )
+}
+
+#[test]
+fn lint_options_and_output() {
+    let path = std::env::temp_dir().join(format!("qdb-lint-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, r#"{"rules": {"missing-where": "info"}}"#).unwrap();
+
+    let args: Vec<String> = vec!["--config", path, "--dialect", "postgresql", "a.sql"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let (config, files) = parse_args(&args).unwrap();
+    assert_eq!(config.dialect.name(), "postgresql");
+    assert_eq!(files, vec!["a.sql"]);
+
+    let source = "SELECT a FROM t;\nDELETE FROM t";
+    let diagnostics = Linter::new(&config).lint(source);
+    assert_eq!(diagnostics[0].severity, Severity::Info);
+    assert_eq!(
+        describe("a.sql", source, &diagnostics[0]),
+        "a.sql:2:1: info[missing-where]: DELETE without WHERE removes every row"
+    );
+
+    let source = "SELECT '\u{e9}' FROM t;\nDELETE FROM t; SELECT \u{e9} FROM t;DELETE FROM t";
+    let diagnostics = Linter::new(&config).lint(source);
+    assert_eq!(
+        describe("a.sql", source, &diagnostics[1]),
+        "a.sql:2:32: info[missing-where]: DELETE without WHERE removes every row"
+    );
+
+    std::fs::remove_file(path).unwrap();
+    assert!(parse_args(&["--dialect".to_string()]).is_err());
+    assert!(parse_args(&["--bogus".to_string()]).is_err());
+}
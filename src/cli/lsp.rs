@@ -0,0 +1,567 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::formatter::{format, FormatOptions};
+use crate::ast::ide::{self, Symbol, SymbolKind};
+use crate::util::json::{self, JsonValue};
+use std::io::{BufRead, Read, Write};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: f64 = -32700.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const INVALID_PARAMS: f64 = -32602.0;
+
+/// LSP `TextDocumentSyncKind.Full`
+const SYNC_FULL: f64 = 1.0;
+/// LSP `DiagnosticSeverity.Error`
+const SEVERITY_ERROR: f64 = 1.0;
+/// LSP `CompletionItemKind.Keyword`
+const COMPLETION_KEYWORD: f64 = 14.0;
+
+/// Largest message body read into memory, longer ones are skipped
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// `lsp [--dialect <name>]`
+///
+/// Language server on stdin and stdout: syntax diagnostics, reserved word
+/// completion, formatting, document symbols and go to definition.
+pub fn run(args: &[String]) -> Result<i32, String> {
+    let mut dialect = dialect::DEFAULT;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dialect" => {
+                let name = args.next().ok_or("lsp: `--dialect` needs a name")?;
+                dialect = dialect::by_name(name)
+                    .ok_or_else(|| format!("lsp: unknown dialect `{}`", name))?;
+            }
+            _ => return Err(format!("lsp: unknown argument `{}`", arg)),
+        }
+    }
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    Server::new(dialect)
+        .serve(&mut stdin.lock(), &mut stdout.lock())
+        .map_err(|e| format!("lsp: {}", e))
+}
+
+/// Open documents, the client sends their full text on every change
+struct Server {
+    dialect: &'static dyn Dialect,
+    documents: Vec<(String, String)>,
+    shutdown: bool,
+}
+
+impl Server {
+    fn new(dialect: &'static dyn Dialect) -> Self {
+        Server {
+            dialect,
+            documents: vec![],
+            shutdown: false,
+        }
+    }
+
+    /// Messages until `exit`, the exit code is 1 when `shutdown` didn't come first
+    fn serve(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> std::io::Result<i32> {
+        while let Some(body) = read_message(input)? {
+            let message = match json::parse(&body) {
+                Ok(message) => message,
+                Err(_) => {
+                    let reply = response(JsonValue::Null, Err((PARSE_ERROR, "parse error")));
+                    write_message(output, &reply.to_string())?;
+                    continue;
+                }
+            };
+            if method(&message) == Some("exit") {
+                return Ok(if self.shutdown { 0 } else { 1 });
+            }
+            for reply in self.handle(&message) {
+                write_message(output, &reply.to_string())?;
+            }
+        }
+        Ok(1)
+    }
+
+    /// Responses and notifications to send for one message
+    fn handle(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        let params = message.get("params").unwrap_or(&JsonValue::Null);
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.notification(method(message).unwrap_or(""), params),
+        };
+        let result = match method(message).unwrap_or("") {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/formatting" => self.formatting(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "textDocument/definition" => self.definition(params),
+            _ => Err((METHOD_NOT_FOUND, "method not found")),
+        };
+        vec![response(id, result)]
+    }
+
+    fn notification(&mut self, method: &str, params: &JsonValue) -> Vec<JsonValue> {
+        let uri = match document_uri(params) {
+            Some(uri) => uri.to_string(),
+            None => return vec![],
+        };
+        let text = match method {
+            "textDocument/didOpen" => params
+                .get("textDocument")
+                .and_then(|d| d.get("text"))
+                .and_then(JsonValue::as_str),
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(JsonValue::as_array)
+                .and_then(|changes| changes.last())
+                .and_then(|change| change.get("text"))
+                .and_then(JsonValue::as_str),
+            "textDocument/didClose" => {
+                self.documents.retain(|(open, _)| *open != uri);
+                return vec![publish_diagnostics(&uri, vec![])];
+            }
+            _ => None,
+        };
+        let text = match text {
+            Some(text) => text.to_string(),
+            None => return vec![],
+        };
+
+        let diagnostics = ide::diagnostics(&text, self.dialect)
+            .into_iter()
+            .map(|error| {
+                object(vec![
+                    ("range", range(&text, error.span.start, error.span.end)),
+                    ("severity", JsonValue::Number(SEVERITY_ERROR)),
+                    ("source", JsonValue::from("qdb")),
+                    ("message", JsonValue::from(error.message)),
+                ])
+            })
+            .collect();
+        match self.documents.iter_mut().find(|(open, _)| *open == uri) {
+            Some(document) => document.1 = text,
+            None => self.documents.push((uri.clone(), text)),
+        }
+        vec![publish_diagnostics(&uri, diagnostics)]
+    }
+
+    fn document(&self, params: &JsonValue) -> Result<&str, (f64, &'static str)> {
+        let uri = document_uri(params).ok_or((INVALID_PARAMS, "missing textDocument.uri"))?;
+        self.documents
+            .iter()
+            .find(|(open, _)| open == uri)
+            .map(|(_, text)| text.as_str())
+            .ok_or((INVALID_PARAMS, "document is not open"))
+    }
+
+    /// Reserved words completing the word before the cursor
+    fn completion(&self, params: &JsonValue) -> Result<JsonValue, (f64, &'static str)> {
+        let text = self.document(params)?;
+        let at = position_param(text, params)?;
+        let start = text[..at]
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !c.is_alphanumeric() && c != '_')
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let items = ide::keywords(&text[start..at])
+            .into_iter()
+            .map(|word| {
+                object(vec![
+                    ("label", JsonValue::from(word)),
+                    ("kind", JsonValue::Number(COMPLETION_KEYWORD)),
+                ])
+            })
+            .collect();
+        Ok(JsonValue::Array(items))
+    }
+
+    /// One edit replacing the document, none when it can't be formatted
+    fn formatting(&self, params: &JsonValue) -> Result<JsonValue, (f64, &'static str)> {
+        let text = self.document(params)?;
+        let options = FormatOptions {
+            dialect: self.dialect,
+            ..FormatOptions::default()
+        };
+        let edits = match format(text, &options) {
+            Ok(formatted) if formatted != text => vec![object(vec![
+                ("range", range(text, 0, text.len())),
+                ("newText", JsonValue::from(formatted)),
+            ])],
+            _ => vec![],
+        };
+        Ok(JsonValue::Array(edits))
+    }
+
+    fn document_symbols(&self, params: &JsonValue) -> Result<JsonValue, (f64, &'static str)> {
+        let text = self.document(params)?;
+        let symbols = ide::symbols(text, self.dialect)
+            .iter()
+            .map(|symbol| document_symbol(text, symbol))
+            .collect();
+        Ok(JsonValue::Array(symbols))
+    }
+
+    fn definition(&self, params: &JsonValue) -> Result<JsonValue, (f64, &'static str)> {
+        let text = self.document(params)?;
+        let at = position_param(text, params)?;
+        Ok(match ide::definition(text, at, self.dialect) {
+            Some(span) => object(vec![
+                ("uri", JsonValue::from(document_uri(params).unwrap_or(""))),
+                ("range", range(text, span.start, span.end)),
+            ]),
+            None => JsonValue::Null,
+        })
+    }
+}
+
+/// What the server offers in the `initialize` result
+fn capabilities() -> JsonValue {
+    object(vec![
+        (
+            "capabilities",
+            object(vec![
+                ("textDocumentSync", JsonValue::Number(SYNC_FULL)),
+                ("completionProvider", object(vec![])),
+                ("documentFormattingProvider", JsonValue::Bool(true)),
+                ("documentSymbolProvider", JsonValue::Bool(true)),
+                ("definitionProvider", JsonValue::Bool(true)),
+            ]),
+        ),
+        (
+            "serverInfo",
+            object(vec![
+                ("name", JsonValue::from("qdb-ast")),
+                ("version", JsonValue::from(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+fn response(id: JsonValue, result: Result<JsonValue, (f64, &'static str)>) -> JsonValue {
+    let reply = match result {
+        Ok(result) => ("result", result),
+        Err((code, message)) => (
+            "error",
+            object(vec![
+                ("code", JsonValue::Number(code)),
+                ("message", JsonValue::from(message)),
+            ]),
+        ),
+    };
+    object(vec![("jsonrpc", JsonValue::from("2.0")), ("id", id), reply])
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<JsonValue>) -> JsonValue {
+    object(vec![
+        ("jsonrpc", JsonValue::from("2.0")),
+        ("method", JsonValue::from("textDocument/publishDiagnostics")),
+        (
+            "params",
+            object(vec![
+                ("uri", JsonValue::from(uri)),
+                ("diagnostics", JsonValue::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn document_symbol(text: &str, symbol: &Symbol) -> JsonValue {
+    // LSP SymbolKind: Field, Interface, Function, Struct
+    let kind = match symbol.kind {
+        SymbolKind::Column => 8.0,
+        SymbolKind::View => 11.0,
+        SymbolKind::Statement => 12.0,
+        SymbolKind::Table => 23.0,
+    };
+    object(vec![
+        ("name", JsonValue::from(symbol.name.as_str())),
+        ("kind", JsonValue::Number(kind)),
+        ("range", range(text, symbol.span.start, symbol.span.end)),
+        (
+            "selectionRange",
+            range(text, symbol.selection.start, symbol.selection.end),
+        ),
+        (
+            "children",
+            JsonValue::Array(
+                symbol
+                    .children
+                    .iter()
+                    .map(|child| document_symbol(text, child))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn object(fields: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+fn method(message: &JsonValue) -> Option<&str> {
+    message.get("method").and_then(JsonValue::as_str)
+}
+
+fn document_uri(params: &JsonValue) -> Option<&str> {
+    params
+        .get("textDocument")
+        .and_then(|d| d.get("uri"))
+        .and_then(JsonValue::as_str)
+}
+
+fn position_param(text: &str, params: &JsonValue) -> Result<usize, (f64, &'static str)> {
+    let position = params.get("position");
+    let field = |name| {
+        position
+            .and_then(|p| p.get(name))
+            .and_then(JsonValue::as_f64)
+            .ok_or((INVALID_PARAMS, "missing position"))
+    };
+    Ok(offset(
+        text,
+        field("line")? as usize,
+        field("character")? as usize,
+    ))
+}
+
+fn range(text: &str, start: usize, end: usize) -> JsonValue {
+    object(vec![
+        ("start", position(text, start)),
+        ("end", position(text, end)),
+    ])
+}
+
+/// LSP position of a byte offset, characters count in UTF-16 units
+fn position(text: &str, offset: usize) -> JsonValue {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    object(vec![
+        (
+            "line",
+            JsonValue::Number(before.matches('\n').count() as f64),
+        ),
+        ("character", JsonValue::Number(character as f64)),
+    ])
+}
+
+/// Byte offset of an LSP position, clamped to its line
+fn offset(text: &str, line: usize, character: usize) -> usize {
+    let line_start = match line {
+        0 => 0,
+        _ => match text.match_indices('\n').nth(line - 1) {
+            Some((i, _)) => i + 1,
+            None => return text.len(),
+        },
+    };
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Body of the next `Content-Length` framed message, None at the end of input.
+/// A body longer than `MAX_MESSAGE_LEN` is read past and comes back empty.
+fn read_message(input: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.unwrap_or(0);
+    if length > MAX_MESSAGE_LEN {
+        std::io::copy(
+            &mut input.by_ref().take(length as u64),
+            &mut std::io::sink(),
+        )?;
+        return Ok(Some(String::new()));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn write_message(output: &mut impl Write, body: &str) -> std::io::Result<()> {
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+#[test]
+fn lsp_session() {
+    let text =
+        "CREATE TABLE t (id INTEGER, \u{e9}t\u{e9} INTEGER);\nselect id, \u{e9}t\u{e9} from t where";
+    let messages = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a.sql","text":{}}}}}}}"#,
+            JsonValue::from(text)
+        ),
+        r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/completion","params":{"textDocument":{"uri":"file:///a.sql"},"position":{"line":1,"character":24}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":3,"method":"textDocument/definition","params":{"textDocument":{"uri":"file:///a.sql"},"position":{"line":1,"character":12}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":4,"method":"textDocument/documentSymbol","params":{"textDocument":{"uri":"file:///a.sql"}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":5,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.sql"},"options":{}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":6,"method":"textDocument/hover","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":7,"method":"shutdown"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ];
+    let mut input = vec![];
+    for message in &messages {
+        write_message(&mut input, message).unwrap();
+    }
+    let mut output = vec![];
+    let code = Server::new(dialect::DEFAULT)
+        .serve(&mut &input[..], &mut output)
+        .unwrap();
+    assert_eq!(code, 0);
+
+    let mut output = &output[..];
+    let mut replies = vec![];
+    while let Some(body) = read_message(&mut output).unwrap() {
+        replies.push(json::parse(&body).unwrap());
+    }
+    assert_eq!(replies.len(), 8);
+
+    let result = |i: usize| replies[i].get("result").unwrap();
+    assert_eq!(
+        result(0)
+            .get("capabilities")
+            .and_then(|c| c.get("definitionProvider")),
+        Some(&JsonValue::Bool(true))
+    );
+    assert_eq!(
+        replies[1]
+            .get("params")
+            .unwrap()
+            .get("diagnostics")
+            .unwrap()
+            .to_string(),
+        r#"[{"range":{"start":{"line":1,"character":27},"end":{"line":1,"character":27}},"severity":1,"source":"qdb","message":"expected condition"}]"#
+    );
+    assert_eq!(
+        result(2).to_string(),
+        r#"[{"label":"WHEN","kind":14},{"label":"WHENEVER","kind":14},{"label":"WHERE","kind":14}]"#
+    );
+    assert_eq!(
+        result(3).to_string(),
+        r#"{"uri":"file:///a.sql","range":{"start":{"line":0,"character":28},"end":{"line":0,"character":31}}}"#
+    );
+    let symbols = result(4).as_array().unwrap();
+    assert_eq!(
+        symbols[0].get("name").and_then(JsonValue::as_str),
+        Some("t")
+    );
+    assert_eq!(
+        symbols[0]
+            .get("children")
+            .and_then(JsonValue::as_array)
+            .map(Vec::len),
+        Some(2)
+    );
+    assert_eq!(
+        result(5).as_array().unwrap()[0]
+            .get("newText")
+            .and_then(JsonValue::as_str)
+            .map(|t| t.starts_with("CREATE TABLE t")),
+        Some(true)
+    );
+    assert_eq!(
+        replies[6].get("error").and_then(|e| e.get("code")),
+        Some(&JsonValue::Number(METHOD_NOT_FOUND))
+    );
+    assert_eq!(result(7), &JsonValue::Null);
+}
+
+#[test]
+fn positions_count_utf16_units() {
+    let text = "a\u{1F600}b\nc";
+    assert_eq!(offset(text, 0, 3), 5);
+    assert_eq!(offset(text, 0, 99), 6);
+    assert_eq!(offset(text, 1, 0), 7);
+    assert_eq!(offset(text, 5, 0), text.len());
+    assert_eq!(position(text, 5).to_string(), r#"{"line":0,"character":3}"#);
+}
+
+#[test]
+fn completion_after_multibyte_separator() {
+    let messages = [
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///b.sql","text":"SELECT \u00abwh"}}}"#,
+        r#"{"jsonrpc":"2.0","id":1,"method":"textDocument/completion","params":{"textDocument":{"uri":"file:///b.sql"},"position":{"line":0,"character":10}}}"#,
+    ];
+    let mut input = vec![];
+    for message in messages.iter() {
+        write_message(&mut input, message).unwrap();
+    }
+    let mut output = vec![];
+    Server::new(dialect::DEFAULT)
+        .serve(&mut &input[..], &mut output)
+        .unwrap();
+
+    let mut output = &output[..];
+    let mut replies = vec![];
+    while let Some(body) = read_message(&mut output).unwrap() {
+        replies.push(json::parse(&body).unwrap());
+    }
+    assert_eq!(
+        replies[1].get("result").unwrap().to_string(),
+        r#"[{"label":"WHEN","kind":14},{"label":"WHENEVER","kind":14},{"label":"WHERE","kind":14}]"#
+    );
+}
+
+#[test]
+fn bad_messages_get_parse_errors() {
+    let mut input = vec![];
+    write_message(&mut input, "{\"jsonrpc\":").unwrap();
+    write_message(
+        &mut input,
+        r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#,
+    )
+    .unwrap();
+    // claims more than the limit, nothing that large is allocated
+    write!(input, "Content-Length: {}\r\n\r\n{{}}", MAX_MESSAGE_LEN + 1).unwrap();
+    let mut output = vec![];
+    Server::new(dialect::DEFAULT)
+        .serve(&mut &input[..], &mut output)
+        .unwrap();
+
+    let mut output = &output[..];
+    let mut replies = vec![];
+    while let Some(body) = read_message(&mut output).unwrap() {
+        replies.push(body);
+    }
+    let parse_error =
+        r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"parse error"}}"#;
+    assert_eq!(
+        replies,
+        vec![
+            parse_error,
+            r#"{"jsonrpc":"2.0","id":1,"result":null}"#,
+            parse_error
+        ]
+    );
+}
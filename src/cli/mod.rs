@@ -0,0 +1,55 @@
+mod flag;
+mod fmt;
+mod lint;
+mod lsp;
+mod transpile;
+mod tree;
+
+const USAGE: &str = "usage: qdb-ast <command> [options]
+
+commands:
+    fmt [--check] [FILE...]             format SQL files in place, or stdin to stdout
+        [--keyword-case upper|lower|preserve] [--indent N] [--width N]
+        [--comma trailing|leading] [--dialect <name>]
+    flag [FILE...]                      report the SQL-92 level of every statement,
+                                        read from the files or stdin
+    lint [--config FILE] [FILE...]      report rule violations, the configuration
+         [--dialect <name>]             defaults to qdb-lint.json when present
+    lsp [--dialect <name>]              language server on stdin and stdout
+    transpile --from <name> --to <name> rewrite SQL of one dialect for another,
+        [FILE]                          read from FILE or stdin
+    tree [--dot] [--kinds] [--spans]    print the tree of the query read from stdin
+         [--dialect <name>]             sql92 (default), postgresql, mysql or sqlite";
+
+/// Run subcommand by name, returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    let result = match command {
+        "flag" => flag::run(rest),
+        "fmt" => fmt::run(rest),
+        "lint" => lint::run(rest),
+        "lsp" => lsp::run(rest),
+        "transpile" => transpile::run(rest),
+        "tree" => tree::run(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        _ => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("{}", message);
+            2
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::ast::dialect::{self, Dialect};
+use crate::ast::transpile::transpile;
+use std::io::Read;
+
+/// `transpile --from <name> --to <name> [FILE]`
+///
+/// Prints the SQL rewritten for the target dialect. Constructs the
+/// target can't express are listed on stderr instead, with exit code 1.
+pub fn run(args: &[String]) -> Result<i32, String> {
+    let (from, to, file) = parse_args(args)?;
+    let (name, source) = match file {
+        Some(file) => {
+            let source = std::fs::read_to_string(file)
+                .map_err(|e| format!("transpile: can't read {}: {}", file, e))?;
+            (file.as_str(), source)
+        }
+        None => {
+            let mut source = String::new();
+            std::io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|e| format!("transpile: can't read stdin: {}", e))?;
+            ("<stdin>", source)
+        }
+    };
+
+    match transpile(&source, from, to) {
+        Ok(sql) => {
+            print!("{}", sql);
+            Ok(0)
+        }
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}: {}", name, error);
+            }
+            Ok(1)
+        }
+    }
+}
+
+type Args<'a> = (
+    &'static dyn Dialect,
+    &'static dyn Dialect,
+    Option<&'a String>,
+);
+
+fn parse_args(args: &[String]) -> Result<Args<'_>, String> {
+    let mut from = None;
+    let mut to = None;
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--from" => &mut from,
+            "--to" => &mut to,
+            _ if arg.starts_with("--") => {
+                return Err(format!("transpile: unknown option `{}`", arg))
+            }
+            _ if file.is_none() => {
+                file = Some(arg);
+                continue;
+            }
+            _ => return Err("transpile: only one file can be given".to_string()),
+        };
+        let name = args
+            .next()
+            .ok_or_else(|| format!("transpile: `{}` needs a dialect name", arg))?;
+        *target = Some(
+            dialect::by_name(name)
+                .ok_or_else(|| format!("transpile: unknown dialect `{}`", name))?,
+        );
+    }
+
+    match (from, to) {
+        (Some(from), Some(to)) => Ok((from, to, file)),
+        _ => Err("transpile: `--from` and `--to` are required".to_string()),
+    }
+}
+
+#[test]
+fn transpile_arguments() {
+    let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let given = args(&["--from", "mysql", "--to", "postgresql", "a.sql"]);
+    let (from, to, file) = parse_args(&given).unwrap();
+    assert_eq!((from.name(), to.name()), ("mysql", "postgresql"));
+    assert_eq!(file.map(String::as_str), Some("a.sql"));
+
+    assert!(parse_args(&args(&["--from", "mysql"])).is_err());
+    assert!(parse_args(&args(&["--from", "oracle", "--to", "sql92"])).is_err());
+    assert!(parse_args(&args(&["--to"])).is_err());
+    assert!(parse_args(&args(&["--from", "mysql", "--to", "sqlite", "a", "b"])).is_err());
+}
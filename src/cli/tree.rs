@@ -0,0 +1,66 @@
+use crate::ast::dialect;
+use crate::ast::parser::Parser;
+use crate::ast::render::{RenderNode, RenderOptions};
+use std::io::Read;
+
+/// `tree [--dot] [--kinds] [--spans] [--dialect <name>]`
+pub fn run(args: &[String]) -> Result<i32, String> {
+    let mut source = String::new();
+    std::io::stdin()
+        .read_to_string(&mut source)
+        .map_err(|e| format!("tree: can't read stdin: {}", e))?;
+
+    print!("{}", render(source.trim_end(), args)?);
+    Ok(0)
+}
+
+fn render(source: &str, args: &[String]) -> Result<String, String> {
+    let mut dot = false;
+    let mut options = RenderOptions::default();
+    let mut dialect = dialect::DEFAULT;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dot" => dot = true,
+            "--dialect" => {
+                let name = args.next().ok_or("tree: `--dialect` needs a name")?;
+                dialect = dialect::by_name(name)
+                    .ok_or_else(|| format!("tree: unknown dialect `{}`", name))?;
+            }
+            "--kinds" => options.kinds = true,
+            "--spans" => options.spans = true,
+            _ => return Err(format!("tree: unknown option `{}`", arg)),
+        }
+    }
+
+    let mut parser = Parser::with_dialect(source, dialect);
+    parser.translate_source_to_ast_nodes();
+
+    let mut root = RenderNode::new("query", "Query").with_span(0..source.len());
+    let nodes = parser.nodes.unwrap_or_default();
+    let spans = parser.spans.unwrap_or_default();
+    for (node, span) in nodes.iter().zip(spans) {
+        root = root.child(RenderNode::from(node).with_span(span));
+    }
+
+    Ok(if dot {
+        root.to_dot(options)
+    } else {
+        root.to_ascii(options)
+    })
+}
+
+#[test]
+fn tree_renders_query_words() {
+    let ascii = render("SELECT a", &["--spans".to_string()]).unwrap();
+    assert_eq!(ascii, "query @0..8\n├── SELECT @0..6\n└── a @7..8\n");
+
+    let dot = render("SELECT a", &["--dot".to_string()]).unwrap();
+    assert!(dot.contains("    n0 -> n2;\n"));
+
+    assert!(render("SELECT", &["--bogus".to_string()]).is_err());
+    assert!(render("SELECT", &["--dialect".to_string()]).is_err());
+
+    let args = ["--dialect".to_string(), "mysql".to_string()];
+    assert!(render("SELECT `a`", &args).unwrap().ends_with("└── `a`\n"));
+}
@@ -1,2 +1,2 @@
-pub mod sql92;
+lalrpop_mod!(pub sql92, "/grm/sql92.rs");
 mod slq92_test;
\ No newline at end of file
@@ -8,9 +8,13 @@ use crate::grm::sql92;
 /// The module which describes all abstract syntax tree
 /// (types, notations and etc.).
 pub mod ast;
+mod cli;
 mod err;
 mod types;
 mod util;
 mod grm;
 
-fn main() {}
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cli::run(&args));
+}
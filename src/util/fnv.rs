@@ -0,0 +1,47 @@
+use std::hash::Hasher;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a, stable across runs and platforms
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    FnvHasher::default().fold(bytes)
+}
+
+/// FNV-1a as a `Hasher`, much cheaper than SipHash on short keys.
+/// No protection against chosen keys, so only for fixed tables.
+pub struct FnvHasher(u64);
+
+impl FnvHasher {
+    fn fold(&self, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(self.0, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = self.fold(bytes);
+    }
+}
+
+#[test]
+fn fnv1a_known_values() {
+    assert_eq!(fnv1a(b""), OFFSET_BASIS);
+    assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+
+    let mut hasher = FnvHasher::default();
+    hasher.write(b"a");
+    assert_eq!(hasher.finish(), fnv1a(b"a"));
+}
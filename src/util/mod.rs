@@ -1 +1,3 @@
 mod AvlTree;
+pub mod fnv;
+pub mod json;